@@ -4,7 +4,7 @@
 /// Uses Tauri's sidecar mechanism so Core inherits screen recording permission
 /// from the parent ETCH app.
 
-use std::io::{BufRead, BufReader, Write};
+use std::io::{BufRead, BufReader, Read, Write};
 use std::sync::Mutex;
 use std::thread;
 use std::time::Duration;
@@ -226,6 +226,139 @@ fn generate_socket_path() -> String {
     }
 }
 
+// ============================================================================
+// Core Socket Framing
+// ============================================================================
+
+/// Frame tag for a JSON control message (see etch-core `socket::framing`)
+const CORE_TAG_JSON: u8 = 0;
+/// Frame tag for a video frame with raw bytes
+const CORE_TAG_VIDEO_FRAME: u8 = 1;
+/// Tag bit set when the frame payload is deflate-compressed
+const CORE_FLAG_COMPRESSED: u8 = 0x80;
+
+/// Whether to ask Core for compressed binary framing.
+/// Off by default - only worthwhile for raw RGBA frame relay.
+fn core_compression_enabled() -> bool {
+    matches!(
+        std::env::var("ETCH_CORE_COMPRESSION").as_deref(),
+        Ok("1") | Ok("true")
+    )
+}
+
+/// Read one length-prefixed frame from Core and convert it to the JSON
+/// message the frontend expects. Returns `Ok(None)` on EOF.
+fn read_core_frame<R: Read>(reader: &mut R) -> std::io::Result<Option<String>> {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use std::io::{Error, ErrorKind};
+
+    let mut len_bytes = [0u8; 4];
+    match reader.read_exact(&mut len_bytes) {
+        Ok(()) => {}
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+
+    let len = u32::from_le_bytes(len_bytes) as usize;
+    if len == 0 {
+        return Err(Error::new(ErrorKind::InvalidData, "empty frame"));
+    }
+    let mut frame = vec![0u8; len];
+    reader.read_exact(&mut frame)?;
+
+    let tag = frame[0];
+    let payload = if tag & CORE_FLAG_COMPRESSED != 0 {
+        let mut inflated = Vec::new();
+        flate2::read::DeflateDecoder::new(&frame[1..]).read_to_end(&mut inflated)?;
+        inflated
+    } else {
+        frame.split_off(1)
+    };
+
+    match tag & !CORE_FLAG_COMPRESSED {
+        CORE_TAG_JSON => String::from_utf8(payload)
+            .map(Some)
+            .map_err(|e| Error::new(ErrorKind::InvalidData, e)),
+        CORE_TAG_VIDEO_FRAME => {
+            // [u32 LE header length][JSON header][raw frame bytes]
+            if payload.len() < 4 {
+                return Err(Error::new(ErrorKind::InvalidData, "truncated video frame"));
+            }
+            let header_len = u32::from_le_bytes([payload[0], payload[1], payload[2], payload[3]]) as usize;
+            if payload.len() < 4 + header_len {
+                return Err(Error::new(ErrorKind::InvalidData, "truncated video frame header"));
+            }
+            let mut header: serde_json::Value = serde_json::from_slice(&payload[4..4 + header_len])
+                .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+            header["frame_data"] = serde_json::Value::String(STANDARD.encode(&payload[4 + header_len..]));
+            Ok(Some(header.to_string()))
+        }
+        other => Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("unknown frame tag {}", other),
+        )),
+    }
+}
+
+/// Read messages from Core and emit them to the frontend as `core-message`
+fn run_core_reader<R: Read>(app_handle: AppHandle, reader: R, framed: bool) {
+    if framed {
+        let mut reader = BufReader::new(reader);
+        loop {
+            match read_core_frame(&mut reader) {
+                Ok(Some(json)) => {
+                    if let Err(e) = app_handle.emit("core-message", json) {
+                        log::error!("Failed to emit core-message: {}", e);
+                    }
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    log::error!("Socket read error: {}", e);
+                    break;
+                }
+            }
+        }
+    } else {
+        let reader = BufReader::new(reader);
+        for line in reader.lines() {
+            match line {
+                Ok(json) => {
+                    log::info!("[Core →] {}", json);
+                    // Emit to frontend
+                    if let Err(e) = app_handle.emit("core-message", json) {
+                        log::error!("Failed to emit core-message: {}", e);
+                    }
+                }
+                Err(e) => {
+                    log::error!("Socket read error: {}", e);
+                    break;
+                }
+            }
+        }
+    }
+    log::info!("Socket reader thread ended");
+}
+
+/// Negotiate compressed binary framing with Core if enabled.
+/// Returns whether the reader should expect framed messages.
+fn negotiate_core_framing<W: Write>(stream: &mut W) -> bool {
+    if !core_compression_enabled() {
+        return false;
+    }
+
+    let hello = r#"{"type":"hello","framing":"binary","compress":true}"#;
+    match stream.write_all(format!("{}\n", hello).as_bytes()) {
+        Ok(()) => {
+            log::info!("Requested compressed binary framing from Core");
+            true
+        }
+        Err(e) => {
+            log::error!("Failed to negotiate Core framing: {}", e);
+            false
+        }
+    }
+}
+
 /// Spawn the Core binary using Tauri's sidecar mechanism
 /// This ensures Core inherits screen recording permission from the parent app
 #[tauri::command]
@@ -312,7 +445,7 @@ pub async fn spawn_core(app: AppHandle, state: State<'_, CoreState>) -> Result<S
     // Wait for Core to start its socket server with retry
     // Core can take up to 1-2 seconds to initialize
     #[cfg(unix)]
-    let stream = {
+    let mut stream = {
         let max_retries = 20;
         let retry_delay = Duration::from_millis(100);
         let mut last_error = String::new();
@@ -350,6 +483,8 @@ pub async fn spawn_core(app: AppHandle, state: State<'_, CoreState>) -> Result<S
             .try_clone()
             .map_err(|e| format!("Failed to clone stream: {}", e))?;
 
+        let framed = negotiate_core_framing(&mut stream);
+
         // Store the writer stream
         {
             let mut socket = state.socket.lock().map_err(|e| e.to_string())?;
@@ -358,31 +493,13 @@ pub async fn spawn_core(app: AppHandle, state: State<'_, CoreState>) -> Result<S
 
         // Spawn a thread to read from the socket and emit events
         let app_handle = app.clone();
-        thread::spawn(move || {
-            let reader = BufReader::new(reader_stream);
-            for line in reader.lines() {
-                match line {
-                    Ok(json) => {
-                        log::info!("[Core →] {}", json);
-                        // Emit to frontend
-                        if let Err(e) = app_handle.emit("core-message", json) {
-                            log::error!("Failed to emit core-message: {}", e);
-                        }
-                    }
-                    Err(e) => {
-                        log::error!("Socket read error: {}", e);
-                        break;
-                    }
-                }
-            }
-            log::info!("Socket reader thread ended");
-        });
+        thread::spawn(move || run_core_reader(app_handle, reader_stream, framed));
     }
 
     #[cfg(windows)]
     {
         // On Windows, parse the socket_path as host:port
-        let stream = std::net::TcpStream::connect(&socket_path)
+        let mut stream = std::net::TcpStream::connect(&socket_path)
             .map_err(|e| format!("Failed to connect to Core socket: {}", e))?;
 
         // Clone for the reader thread
@@ -390,6 +507,8 @@ pub async fn spawn_core(app: AppHandle, state: State<'_, CoreState>) -> Result<S
             .try_clone()
             .map_err(|e| format!("Failed to clone stream: {}", e))?;
 
+        let framed = negotiate_core_framing(&mut stream);
+
         // Store the writer stream
         {
             let mut socket = state.socket.lock().map_err(|e| e.to_string())?;
@@ -398,24 +517,7 @@ pub async fn spawn_core(app: AppHandle, state: State<'_, CoreState>) -> Result<S
 
         // Spawn a thread to read from the socket and emit events
         let app_handle = app.clone();
-        thread::spawn(move || {
-            let reader = BufReader::new(reader_stream);
-            for line in reader.lines() {
-                match line {
-                    Ok(json) => {
-                        log::info!("[Core →] {}", json);
-                        if let Err(e) = app_handle.emit("core-message", json) {
-                            log::error!("Failed to emit core-message: {}", e);
-                        }
-                    }
-                    Err(e) => {
-                        log::error!("Socket read error: {}", e);
-                        break;
-                    }
-                }
-            }
-            log::info!("Socket reader thread ended");
-        });
+        thread::spawn(move || run_core_reader(app_handle, reader_stream, framed));
     }
 
     log::info!("Core spawned and connected successfully");
@@ -513,13 +615,62 @@ pub struct OverlayBounds {
     pub height: u32,
 }
 
+/// Event carrying the overlay's actual bounds whenever it moves or resizes
+const OVERLAY_BOUNDS_EVENT: &str = "overlay-bounds-changed";
+
+/// Move and resize the overlay in physical pixels
+fn apply_overlay_bounds(
+    window: &tauri::WebviewWindow,
+    bounds: &OverlayBounds,
+) -> Result<(), String> {
+    window
+        .set_position(tauri::Position::Physical(tauri::PhysicalPosition {
+            x: bounds.x,
+            y: bounds.y,
+        }))
+        .map_err(|e| format!("Failed to set position: {}", e))?;
+
+    window
+        .set_size(tauri::Size::Physical(tauri::PhysicalSize {
+            width: bounds.width,
+            height: bounds.height,
+        }))
+        .map_err(|e| format!("Failed to set size: {}", e))?;
+
+    Ok(())
+}
+
+/// Where the overlay actually is, in physical pixels
+///
+/// The window manager may not honor a requested size exactly (fractional
+/// scaling rounds it, some WMs enforce limits), so this can differ from the
+/// bounds last requested.
+fn actual_overlay_bounds(window: &tauri::WebviewWindow) -> Result<OverlayBounds, String> {
+    let position = window
+        .inner_position()
+        .map_err(|e| format!("Failed to get position: {}", e))?;
+    let size = window
+        .inner_size()
+        .map_err(|e| format!("Failed to get size: {}", e))?;
+    Ok(OverlayBounds {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+    })
+}
+
 /// Create a transparent annotation overlay window positioned over the shared content
 /// This window is click-through and always-on-top, serving as a canvas for annotations
+///
+/// Returns the bounds the overlay actually got; later changes (e.g. the
+/// window manager settling on another size) are emitted as
+/// `overlay-bounds-changed`.
 #[tauri::command]
 pub async fn create_annotation_overlay(
     app: AppHandle,
     bounds: OverlayBounds,
-) -> Result<(), String> {
+) -> Result<OverlayBounds, String> {
     const OVERLAY_LABEL: &str = "annotation-overlay";
 
     // Check if overlay already exists
@@ -580,6 +731,31 @@ pub async fn create_annotation_overlay(
         }
     };
 
+    // The builder takes logical units; re-apply the bounds in physical
+    // pixels so the overlay covers the display on scaled screens too
+    apply_overlay_bounds(&window, &bounds)?;
+
+    // Report the real size and position as the window manager settles them
+    let event_window = window.clone();
+    window.on_window_event(move |event| {
+        if !matches!(
+            event,
+            tauri::WindowEvent::Resized(_)
+                | tauri::WindowEvent::Moved(_)
+                | tauri::WindowEvent::ScaleFactorChanged { .. }
+        ) {
+            return;
+        }
+        match actual_overlay_bounds(&event_window) {
+            Ok(actual) => {
+                if let Err(e) = event_window.emit(OVERLAY_BOUNDS_EVENT, actual) {
+                    log::error!("Failed to emit overlay bounds: {}", e);
+                }
+            }
+            Err(e) => log::error!("{}", e),
+        }
+    });
+
     // Configure click-through behavior (platform-specific)
     log::info!("Configuring click-through...");
     if let Err(e) = configure_click_through(&window) {
@@ -587,8 +763,15 @@ pub async fn create_annotation_overlay(
         return Err(e);
     }
 
-    log::info!("Annotation overlay created successfully");
-    Ok(())
+    let actual = actual_overlay_bounds(&window)?;
+    log::info!(
+        "Annotation overlay created successfully at ({}, {}) with size {}x{}",
+        actual.x,
+        actual.y,
+        actual.width,
+        actual.height
+    );
+    Ok(actual)
 }
 
 /// Destroy the annotation overlay window
@@ -611,31 +794,22 @@ pub async fn destroy_annotation_overlay(app: AppHandle) -> Result<(), String> {
 
 /// Update the position and size of the annotation overlay
 /// Used for tracking window position when sharing a specific window
+///
+/// Returns the bounds the overlay actually got, which may differ from
+/// `bounds` (see `actual_overlay_bounds`).
 #[tauri::command]
-pub async fn update_overlay_bounds(app: AppHandle, bounds: OverlayBounds) -> Result<(), String> {
+pub async fn update_overlay_bounds(
+    app: AppHandle,
+    bounds: OverlayBounds,
+) -> Result<OverlayBounds, String> {
     const OVERLAY_LABEL: &str = "annotation-overlay";
 
     let window = app
         .get_webview_window(OVERLAY_LABEL)
         .ok_or_else(|| "Annotation overlay does not exist".to_string())?;
 
-    // Update position
-    window
-        .set_position(tauri::Position::Physical(tauri::PhysicalPosition {
-            x: bounds.x,
-            y: bounds.y,
-        }))
-        .map_err(|e| format!("Failed to set position: {}", e))?;
-
-    // Update size
-    window
-        .set_size(tauri::Size::Physical(tauri::PhysicalSize {
-            width: bounds.width,
-            height: bounds.height,
-        }))
-        .map_err(|e| format!("Failed to set size: {}", e))?;
-
-    Ok(())
+    apply_overlay_bounds(&window, &bounds)?;
+    actual_overlay_bounds(&window)
 }
 
 /// Check if the annotation overlay exists
@@ -682,9 +856,7 @@ fn disable_click_through(window: &tauri::WebviewWindow) -> Result<(), String> {
 
     #[cfg(target_os = "linux")]
     {
-        // Linux: click-through configuration is limited
-        let _ = window;
-        log::info!("Linux: Click-through toggle limited by window manager");
+        disable_click_through_linux(window)?;
     }
 
     Ok(())
@@ -900,20 +1072,94 @@ fn configure_click_through_windows(window: &tauri::WebviewWindow) -> Result<(),
     Ok(())
 }
 
-/// Linux: Basic transparency mode, full click-through varies by window manager
+/// Linux: Use the XShape extension to give the overlay an empty input region
+/// X11 only - Wayland compositors don't expose input regions to Tauri windows
 #[cfg(target_os = "linux")]
-fn configure_click_through_linux(_window: &tauri::WebviewWindow) -> Result<(), String> {
-    // Linux click-through is more complex and varies by window manager:
-    // - X11: Would use XShapeCombineRectangles to set input shape to empty
-    // - Wayland: Depends on compositor support for input regions
-    //
-    // For now, we rely on the transparent window + always_on_top settings
-    // The overlay will be visible but may intercept mouse events on some systems
-    //
-    // Future enhancement: Add x11rb or wayland-client for proper input passthrough
+fn configure_click_through_linux(window: &tauri::WebviewWindow) -> Result<(), String> {
+    let Some(xid) = x11_window_id(window)? else {
+        log::warn!("Linux: Click-through is not supported on Wayland - overlay will capture mouse input");
+        return Ok(());
+    };
+
+    // An empty input shape lets every pointer event fall through to the
+    // windows below (child windows are clipped to it as well)
+    set_x11_input_shape(xid, true)?;
+
+    log::info!("Linux (X11): Configured click-through with an empty XShape input region");
+    Ok(())
+}
+
+/// Linux: Restore the default input region so the overlay receives clicks
+#[cfg(target_os = "linux")]
+fn disable_click_through_linux(window: &tauri::WebviewWindow) -> Result<(), String> {
+    let Some(xid) = x11_window_id(window)? else {
+        log::warn!("Linux: Click-through toggle is not supported on Wayland");
+        return Ok(());
+    };
+
+    set_x11_input_shape(xid, false)?;
+
+    log::info!("Linux (X11): Disabled click-through for drawing mode");
+    Ok(())
+}
+
+/// Detect Wayland sessions (same check as Core's Linux permissions)
+#[cfg(target_os = "linux")]
+fn is_wayland() -> bool {
+    std::env::var("WAYLAND_DISPLAY").is_ok()
+        || std::env::var("XDG_SESSION_TYPE")
+            .map(|v| v.to_lowercase() == "wayland")
+            .unwrap_or(false)
+}
+
+/// Get the X11 window ID of the overlay, or None when not running on X11
+#[cfg(target_os = "linux")]
+fn x11_window_id(window: &tauri::WebviewWindow) -> Result<Option<u32>, String> {
+    use raw_window_handle::{HasWindowHandle, RawWindowHandle};
+
+    if is_wayland() {
+        return Ok(None);
+    }
+
+    let handle = window
+        .window_handle()
+        .map_err(|e| format!("Failed to get window handle: {}", e))?;
+
+    match handle.as_raw() {
+        RawWindowHandle::Xlib(xlib) => Ok(Some(xlib.window as u32)),
+        RawWindowHandle::Xcb(xcb) => Ok(Some(xcb.window.get())),
+        _ => Ok(None),
+    }
+}
+
+/// Set the input shape of an X11 window: empty (click-through) or default
+#[cfg(target_os = "linux")]
+fn set_x11_input_shape(xid: u32, click_through: bool) -> Result<(), String> {
+    use x11rb::connection::Connection;
+    use x11rb::protocol::shape::{ConnectionExt as _, SK, SO};
+    use x11rb::protocol::xproto::ClipOrdering;
+
+    let (conn, _) =
+        x11rb::connect(None).map_err(|e| format!("Failed to connect to X server: {}", e))?;
+
+    conn.shape_query_version()
+        .map_err(|e| format!("XShape query failed: {}", e))?
+        .reply()
+        .map_err(|e| format!("XShape extension not available: {}", e))?;
+
+    let cookie = if click_through {
+        conn.shape_rectangles(SO::SET, SK::INPUT, ClipOrdering::UNSORTED, xid, 0, 0, &[])
+    } else {
+        // No mask resets the input region to the window bounds
+        conn.shape_mask(SO::SET, SK::INPUT, xid, 0, 0, x11rb::NONE)
+    };
+    cookie
+        .map_err(|e| format!("Failed to set input shape: {}", e))?
+        .check()
+        .map_err(|e| format!("Failed to set input shape: {}", e))?;
 
-    log::info!("Linux: Click-through configured (basic mode - may vary by window manager)");
-    log::info!("Linux: For X11, full click-through would require XShape extension");
+    conn.flush()
+        .map_err(|e| format!("Failed to flush X connection: {}", e))?;
     Ok(())
 }
 
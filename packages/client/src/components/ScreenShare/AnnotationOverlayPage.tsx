@@ -202,6 +202,10 @@ export function AnnotationOverlayPage() {
     const width = window.innerWidth
     const height = window.innerHeight
 
+    // The window may not have its real bounds yet (or is collapsed to a
+    // pixel); strokes denormalized against that size would be garbage
+    if (width <= 1 || height <= 1) return
+
     // Clear canvas using physical dimensions
     ctx.clearRect(0, 0, canvas.width, canvas.height)
 
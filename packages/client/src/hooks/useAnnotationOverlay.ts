@@ -19,6 +19,12 @@ export interface OverlayBounds {
 export interface UseAnnotationOverlayReturn {
   /** Whether the overlay is currently active */
   isOverlayActive: boolean
+  /**
+   * Where the overlay actually is, in physical pixels. May differ from the
+   * requested bounds when the window manager adjusts the size (e.g. with
+   * fractional scaling).
+   */
+  overlayBounds: OverlayBounds | null
   /** Create the overlay window at the specified bounds */
   createOverlay: (bounds: OverlayBounds) => Promise<void>
   /** Destroy the overlay window */
@@ -56,6 +62,9 @@ export interface UseAnnotationOverlayReturn {
  */
 export function useAnnotationOverlay(): UseAnnotationOverlayReturn {
   const [isOverlayActive, setIsOverlayActive] = useState(false)
+  const [overlayBounds, setOverlayBounds] = useState<OverlayBounds | null>(
+    null
+  )
   const trackingIntervalRef = useRef<number | null>(null)
   const isOverlayActiveRef = useRef(false)
 
@@ -71,14 +80,22 @@ export function useAnnotationOverlay(): UseAnnotationOverlayReturn {
   // Create the overlay window
   const createOverlay = useCallback(async (bounds: OverlayBounds) => {
     try {
-      await invoke('create_annotation_overlay', { bounds })
+      const actual = await invoke<OverlayBounds | undefined>(
+        'create_annotation_overlay',
+        { bounds }
+      )
+      setOverlayBounds(actual ?? bounds)
       setIsOverlayActive(true)
-      console.log('[Overlay] Created at', bounds)
+      console.log('[Overlay] Created at', actual ?? bounds)
     } catch (error) {
       // If overlay already exists, just update position
       if (error instanceof Error && error.message.includes('already exists')) {
         console.log('[Overlay] Already exists, updating bounds')
-        await invoke('update_overlay_bounds', { bounds })
+        const actual = await invoke<OverlayBounds | undefined>(
+          'update_overlay_bounds',
+          { bounds }
+        )
+        setOverlayBounds(actual ?? bounds)
         setIsOverlayActive(true)
       } else {
         console.error('[Overlay] Failed to create:', error)
@@ -98,12 +115,14 @@ export function useAnnotationOverlay(): UseAnnotationOverlayReturn {
     try {
       await invoke('destroy_annotation_overlay')
       setIsOverlayActive(false)
+      setOverlayBounds(null)
       console.log('[Overlay] Destroyed')
     } catch (error) {
       // If overlay doesn't exist, that's fine
       if (error instanceof Error && error.message.includes('does not exist')) {
         console.log('[Overlay] Already destroyed')
         setIsOverlayActive(false)
+        setOverlayBounds(null)
       } else {
         console.error('[Overlay] Failed to destroy:', error)
         throw error
@@ -114,7 +133,11 @@ export function useAnnotationOverlay(): UseAnnotationOverlayReturn {
   // Update overlay bounds
   const updateBounds = useCallback(async (bounds: OverlayBounds) => {
     try {
-      await invoke('update_overlay_bounds', { bounds })
+      const actual = await invoke<OverlayBounds | undefined>(
+        'update_overlay_bounds',
+        { bounds }
+      )
+      setOverlayBounds(actual ?? bounds)
     } catch (error) {
       console.error('[Overlay] Failed to update bounds:', error)
       throw error
@@ -140,7 +163,11 @@ export function useAnnotationOverlay(): UseAnnotationOverlayReturn {
           { title: windowTitle }
         )
         if (bounds) {
-          await invoke('update_overlay_bounds', { bounds })
+          const actual = await invoke<OverlayBounds | undefined>(
+            'update_overlay_bounds',
+            { bounds }
+          )
+          setOverlayBounds(actual ?? bounds)
         }
       } catch (error) {
         // Silently ignore tracking errors to avoid console spam
@@ -177,8 +204,17 @@ export function useAnnotationOverlay(): UseAnnotationOverlayReturn {
   useEffect(() => {
     let unlistenCaptureError: (() => void) | undefined
     let unlistenTerminated: (() => void) | undefined
+    let unlistenBounds: (() => void) | undefined
 
     const setupListeners = async () => {
+      // The window manager may settle on a different size than requested
+      unlistenBounds = await listen<OverlayBounds>(
+        'overlay-bounds-changed',
+        (event) => {
+          setOverlayBounds(event.payload)
+        }
+      )
+
       // Listen for capture errors
       unlistenCaptureError = await listen<string>(
         'core-capture-error',
@@ -231,6 +267,9 @@ export function useAnnotationOverlay(): UseAnnotationOverlayReturn {
       if (unlistenTerminated) {
         unlistenTerminated()
       }
+      if (unlistenBounds) {
+        unlistenBounds()
+      }
     }
     // eslint-disable-next-line react-hooks/exhaustive-deps
   }, []) // Only set up listeners once on mount
@@ -246,6 +285,7 @@ export function useAnnotationOverlay(): UseAnnotationOverlayReturn {
 
   return {
     isOverlayActive,
+    overlayBounds,
     createOverlay,
     destroyOverlay,
     updateBounds,
@@ -38,6 +38,7 @@ export interface ParticipantData {
   name: string
   is_local: boolean
   role: 'host' | 'participant'
+  metadata: string
 }
 
 export type ConnectionState =
@@ -46,6 +47,23 @@ export type ConnectionState =
   | 'connected'
   | 'reconnecting'
 
+export interface ConnectionStats {
+  /** Total outbound bitrate in bits/s */
+  outbound_bitrate: number
+  /** Fraction of packets lost (0-1) */
+  packet_loss: number
+  rtt_ms: number | null
+  fps: number
+  resolution: { width: number; height: number } | null
+  /** Backlog of messages from Core to the WebView */
+  outgoing_queue: {
+    control_queued: number
+    frames_queued: number
+    /** Video frames dropped because a reader fell behind (since start) */
+    frames_dropped: number
+  }
+}
+
 export type PermissionStatus =
   | 'granted'
   | 'denied'
@@ -65,18 +83,121 @@ export interface CaptureConfig {
   height: number
   framerate: number
   bitrate: number
+  /**
+   * Also capture and publish system audio (Windows and macOS 13+; on Linux
+   * the share goes ahead without it and `audio_capture_failed` is reported)
+   */
+  capture_audio?: boolean
+  /** Screen share codec, VP9 by default; falls back to VP8 if rejected */
+  codec?: VideoCodec
+  /** Add a half-resolution 3 fps layer for weak subscribers (default true) */
+  simulcast?: boolean
+  /** How hard capture tries to recover before the share is given up */
+  restart_policy?: RestartPolicy
+  /**
+   * Pixel layout handed to the encoder (default 'i420'); 'nv12' saves a
+   * conversion only on encoders that take it natively
+   */
+  pixel_format?: 'i420' | 'nv12'
+}
+
+/** Capture recovery tuning; omitted fields keep Core's defaults */
+export interface RestartPolicy {
+  /** Consecutive failed frames before the capturer is restarted (default 3) */
+  max_failures?: number
+  /** Restarts before giving up, and retries within each (default 5) */
+  max_restart_attempts?: number
+  /** Pause before a restart in ms (default 200) */
+  restart_delay_ms?: number
+  /** Pause between retries within a restart in ms (default 100) */
+  retry_delay_ms?: number
 }
 
+/** Media Core publishes as soon as it joins, without waiting for the WebView */
+export interface InitialMedia {
+  screen_source_id?: string
+  screen_config?: CaptureConfig
+  /**
+   * Not supported yet: Core has no microphone track to publish, so the join
+   * is refused with `room_join_failed`
+   */
+  microphone?: boolean
+  /**
+   * Not supported yet: Core has no camera track to publish, so the join is
+   * refused with `room_join_failed`
+   */
+  camera?: boolean
+}
+
+export type VideoCodec = 'vp8' | 'vp9' | 'h264' | 'av1'
+
 export type SourceType = 'screen' // Window capture not supported
 
+export type TrackSourceType =
+  | 'camera'
+  | 'microphone'
+  | 'screen_share'
+  | 'screen_share_audio'
+  | 'unknown'
+
 export type FrameFormat = 'jpeg' | 'rgba' | 'nv12'
 
+/** What this build of Core supports, sent in the ready message */
+export interface CoreCapabilities {
+  window_capture: boolean
+  system_audio: boolean
+  recording: boolean
+  e2ee: boolean
+  codecs: VideoCodec[]
+  relay_formats: FrameFormat[]
+}
+
+/** Error codes sent by Core; see ErrorCode in packages/core/src/lib.rs */
+export type CoreErrorCode =
+  | 'socket_init_failed'
+  | 'permission_denied'
+  | 'already_sharing'
+  | 'not_sharing'
+  | 'publish_failed'
+  | 'capture_failed'
+  | 'audio_capture_failed'
+  | 'source_not_found'
+  | 'restart_failed'
+  | 'invalid_capture_quality'
+  | 'capture_panicked'
+  | 'already_recording'
+  | 'recording_failed'
+  | 'room_join_failed'
+  | 'room_service_failed'
+  | 'e2ee_unavailable'
+  | 'not_host'
+  | 'invalid_log_filter'
+  | 'track_not_found'
+
+/** A remote cursor at its smoothed position, as the overlay draws it */
+export interface RemoteCursorState {
+  participant_id: string
+  x: number // normalized 0-1
+  y: number
+  visible: boolean
+  style: 'default' | 'pen' | 'highlighter' | 'eraser' | 'hidden' | 'laser'
+  /**
+   * Point of the cursor shape on (x, y), as fractions of its width and
+   * height: draw the shape at (x - hotspot[0] * w, y - hotspot[1] * h)
+   */
+  hotspot: [number, number]
+  color: { r: number; g: number; b: number; a: number }
+  /** Laser pointer trail, oldest first; absent for other styles */
+  trail?: { x: number; y: number; opacity: number }[]
+}
+
 export interface VideoFrame {
   participant_id: string
   track_id: string
   width: number
   height: number
-  timestamp: number
+  timestamp: number // Core's monotonic clock (ms), strictly increasing
+  wall_timestamp: number // ms since Unix epoch
   format: FrameFormat
   frame_data: string // Base64 encoded
 }
@@ -89,9 +210,59 @@ export type CoreMessage =
   | { type: 'available_content'; screens: ScreenInfo[] }
   | { type: 'participant_joined'; participant: ParticipantData }
   | { type: 'participant_left'; participant_id: string }
+  | { type: 'participant_metadata_changed'; participant: ParticipantData }
   | { type: 'connection_state_changed'; state: ConnectionState }
+  | {
+      /** Joined a room; video_frame payloads arrive as `relay_format` */
+      type: 'room_connected'
+      room_name: string
+      relay_format: FrameFormat
+    }
+  | { type: 'room_disconnected'; reason: string }
+  | { type: 'participants'; participants: ParticipantData[] }
+  | {
+      type: 'track_subscribed'
+      participant_id: string
+      track_id: string
+      source: TrackSourceType
+    }
+  | { type: 'track_unsubscribed'; participant_id: string; track_id: string }
+  | {
+      /** Every animation frame while a remote cursor moves */
+      type: 'remote_cursors'
+      cursors: RemoteCursorState[]
+    }
+  | ({ type: 'connection_stats' } & ConnectionStats)
+  | {
+      /** Remote share to spotlight; both null when nobody shares */
+      type: 'focused_share_changed'
+      participant_id: string | null
+      track_id: string | null
+    }
   | { type: 'screen_share_started'; sharer_id: string }
   | { type: 'screen_share_stopped' }
+  | {
+      /** Config the share actually runs with; null while not sharing */
+      type: 'capture_config'
+      config: CaptureConfig | null
+    }
+  | { type: 'screen_share_pause_changed'; paused: boolean }
+  | { type: 'capture_resolution_changed'; width: number; height: number }
+  | { type: 'published_resolution_changed'; width: number; height: number }
+  | { type: 'capture_restarting'; attempt: number; max: number }
+  | { type: 'capture_restored' }
+  | { type: 'capture_giving_up' }
+  | {
+      /** Sent every second while sharing, at 0 fps if capture stalls */
+      type: 'capture_stats'
+      fps: number
+      frames_sent: number
+      frames_dropped: number
+      restart_attempts: number
+    }
+  | { type: 'capture_source_changed'; source_id: string }
+  | { type: 'capture_source_lost'; source_id: string; switched_to: string | null }
+  | { type: 'recording_state_changed'; is_recording: boolean; path: string | null }
   | {
       type: 'video_frame'
       participant_id: string
@@ -99,38 +270,93 @@ export type CoreMessage =
       width: number
       height: number
       timestamp: number
+      wall_timestamp: number
       format: FrameFormat
       frame_data: string
     }
+  | { type: 'still'; width: number; height: number; format: FrameFormat; data: string }
   | { type: 'permission_state'; state: PermissionState }
-  | { type: 'pong' }
-  | { type: 'error'; code: string; message: string }
+  | {
+      type: 'ready'
+      version: string
+      platform: string
+      capabilities: CoreCapabilities
+      /** Format video_frame payloads arrive in unless join_room asks for another */
+      relay_format: FrameFormat
+    }
+  | { type: 'pong'; timestamp: number | null; core_timestamp: number }
+  | { type: 'heartbeat'; core_timestamp: number }
+  | { type: 'error'; code: CoreErrorCode; message: string }
 
 // ============================================================================
 // Incoming Messages (from WebView to Core)
 // ============================================================================
 
 type IncomingMessage =
-  | { type: 'join_room'; server_url: string; token: string }
+  | {
+      type: 'join_room'
+      server_url: string
+      token: string
+      e2ee_passphrase?: string
+      initial_media?: InitialMedia
+      /** Defaults to true; when false, use setTrackSubscribed to receive tracks */
+      auto_subscribe?: boolean
+      /**
+       * Defaults to 'jpeg' (smallest on the wire); 'rgba' costs Core the least
+       * CPU, 'nv12' suits GPU-side conversion. See FrameFormat in Core.
+       */
+      relay_format?: FrameFormat
+    }
   | { type: 'leave_room' }
-  | { type: 'get_available_content' }
+  | { type: 'get_participants' }
+  | {
+      type: 'set_track_subscribed'
+      participant_id: string
+      track_id: string
+      subscribed: boolean
+    }
+  | { type: 'get_available_content'; force_refresh?: boolean }
   | {
       type: 'start_screen_share'
       source_id: string
       source_type: SourceType
       config?: CaptureConfig
+      /** Stop a share in progress instead of failing with already_sharing */
+      replace?: boolean
     }
   | { type: 'stop_screen_share' }
+  | { type: 'pause_screen_share' }
+  | { type: 'resume_screen_share' }
+  | { type: 'switch_screen_share_source'; source_id: string }
+  | { type: 'capture_still' }
+  | { type: 'get_capture_config' }
+  | { type: 'set_capture_quality'; framerate?: number }
+  | { type: 'start_recording'; path: string }
+  | { type: 'stop_recording' }
   | {
       type: 'send_annotation'
       stroke_id: string
       tool: string
       color: { r: number; g: number; b: number; a: number }
+      /** Fraction of the shared screen's width; defaults per tool */
+      width?: number
       points: { x: number; y: number; pressure?: number }[]
     }
   | { type: 'delete_annotation'; stroke_id: string }
+  | {
+      type: 'set_tool'
+      tool: 'pen' | 'highlighter' | 'eraser'
+      color: { r: number; g: number; b: number; a: number }
+    }
   | { type: 'clear_annotations' }
-  | { type: 'cursor_move'; x: number; y: number }
+  | { type: 'clear_participant_annotations'; participant_id: string }
+  | {
+      type: 'set_annotation_permission'
+      participant_id: string
+      enabled: boolean
+      clear_strokes?: boolean
+    }
+  | { type: 'cursor_move'; x: number; y: number; laser?: boolean }
   | { type: 'cursor_hide' }
   | { type: 'set_mic_muted'; muted: boolean }
   | { type: 'set_camera_enabled'; enabled: boolean }
@@ -138,7 +364,21 @@ type IncomingMessage =
   | { type: 'set_video_input_device'; device_id: string }
   | { type: 'check_permissions' }
   | { type: 'request_screen_recording_permission' }
-  | { type: 'ping' }
+  | { type: 'request_microphone_permission' }
+  | { type: 'request_camera_permission' }
+  | {
+      type: 'hello'
+      framing: 'json' | 'binary'
+      compress?: boolean
+      /**
+       * Send something (a ping if idle) at least every interval_ms; Core
+       * then writes `heartbeat` when idle and drops the connection after
+       * three silent intervals
+       */
+      heartbeat?: { interval_ms: number; shutdown_on_timeout?: boolean }
+    }
+  | { type: 'set_log_level'; filter: string }
+  | { type: 'ping'; timestamp?: number }
   | { type: 'shutdown' }
   | { type: 'test_overlay' }
 
@@ -399,12 +639,24 @@ export class CoreClient {
 
   /**
    * Join a LiveKit room
+   *
+   * The token is only needed to connect: LiveKit renews it from the
+   * refreshed tokens the server pushes, so long sessions outlive its TTL.
    */
-  async joinRoom(serverUrl: string, token: string): Promise<void> {
+  async joinRoom(
+    serverUrl: string,
+    token: string,
+    initialMedia?: InitialMedia,
+    autoSubscribe?: boolean,
+    relayFormat?: FrameFormat
+  ): Promise<void> {
     await this.sendMessage({
       type: 'join_room',
       server_url: serverUrl,
       token,
+      initial_media: initialMedia,
+      auto_subscribe: autoSubscribe,
+      relay_format: relayFormat,
     })
   }
 
@@ -415,15 +667,42 @@ export class CoreClient {
     await this.sendMessage({ type: 'leave_room' })
   }
 
+  /**
+   * Ask for everyone currently in the room - answered with `participants`,
+   * e.g. to rebuild the roster after a reload
+   */
+  async getParticipants(): Promise<void> {
+    await this.sendMessage({ type: 'get_participants' })
+  }
+
+  /**
+   * Receive (or stop receiving) one remote track, e.g. only what's on screen
+   */
+  async setTrackSubscribed(
+    participantId: string,
+    trackId: string,
+    subscribed: boolean
+  ): Promise<void> {
+    await this.sendMessage({
+      type: 'set_track_subscribed',
+      participant_id: participantId,
+      track_id: trackId,
+      subscribed,
+    })
+  }
+
   // ========================================================================
   // Screen Share Operations
   // ========================================================================
 
   /**
-   * Get available screens and windows for capture
+   * Get available screens for capture
+   *
+   * Cached sources are sent right away when displays haven't changed,
+   * followed by a fresh scan. Pass forceRefresh to skip the cached reply.
    */
-  async getAvailableContent(): Promise<void> {
-    await this.sendMessage({ type: 'get_available_content' })
+  async getAvailableContent(forceRefresh = false): Promise<void> {
+    await this.sendMessage({ type: 'get_available_content', force_refresh: forceRefresh })
   }
 
   /**
@@ -432,7 +711,8 @@ export class CoreClient {
   async startScreenShare(
     sourceId: string,
     sourceType: SourceType,
-    config?: Partial<CaptureConfig>
+    config?: Partial<CaptureConfig>,
+    replace = false
   ): Promise<void> {
     const fullConfig: CaptureConfig = {
       width: 1920,
@@ -447,6 +727,7 @@ export class CoreClient {
       source_id: sourceId,
       source_type: sourceType,
       config: fullConfig,
+      replace,
     })
   }
 
@@ -457,6 +738,68 @@ export class CoreClient {
     await this.sendMessage({ type: 'stop_screen_share' })
   }
 
+  /**
+   * Freeze the share - viewers keep seeing the last frame
+   */
+  async pauseScreenShare(): Promise<void> {
+    await this.sendMessage({ type: 'pause_screen_share' })
+  }
+
+  /**
+   * Resume a paused share
+   */
+  async resumeScreenShare(): Promise<void> {
+    await this.sendMessage({ type: 'resume_screen_share' })
+  }
+
+  /**
+   * Share another screen on the same track - answered with `capture_source_changed`
+   */
+  async switchScreenShareSource(sourceId: string): Promise<void> {
+    await this.sendMessage({ type: 'switch_screen_share_source', source_id: sourceId })
+  }
+
+  /**
+   * Snapshot the shared screen at full resolution - answered with a `still` message
+   */
+  async captureStill(): Promise<void> {
+    await this.sendMessage({ type: 'capture_still' })
+  }
+
+  /**
+   * Ask for the config the share is actually published with (resolution
+   * after adaptation, codec after fallback) - answered with `capture_config`
+   */
+  async getCaptureConfig(): Promise<void> {
+    await this.sendMessage({ type: 'get_capture_config' })
+  }
+
+  /**
+   * Change the share's framerate (1-60) without stopping it - answered
+   * with the updated `capture_config`
+   */
+  async setCaptureQuality(quality: { framerate?: number }): Promise<void> {
+    await this.sendMessage({ type: 'set_capture_quality', ...quality })
+  }
+
+  // ========================================================================
+  // Recording Operations
+  // ========================================================================
+
+  /**
+   * Record the shared screen to an MP4 file (requires ffmpeg)
+   */
+  async startRecording(path: string): Promise<void> {
+    await this.sendMessage({ type: 'start_recording', path })
+  }
+
+  /**
+   * Stop recording and finalize the file
+   */
+  async stopRecording(): Promise<void> {
+    await this.sendMessage({ type: 'stop_recording' })
+  }
+
   // ========================================================================
   // Permission Operations
   // ========================================================================
@@ -475,25 +818,49 @@ export class CoreClient {
     await this.sendMessage({ type: 'request_screen_recording_permission' })
   }
 
+  /**
+   * Request microphone permission (prompts on macOS)
+   */
+  async requestMicrophonePermission(): Promise<void> {
+    await this.sendMessage({ type: 'request_microphone_permission' })
+  }
+
+  /**
+   * Request camera permission (prompts on macOS)
+   */
+  async requestCameraPermission(): Promise<void> {
+    await this.sendMessage({ type: 'request_camera_permission' })
+  }
+
   // ========================================================================
   // Annotation Operations
   // ========================================================================
 
   /**
-   * Send an annotation stroke
+   * Send a finished annotation stroke (published to the room)
+   *
+   * `color` may be any RGBA (0-255 per channel, `a` is opacity); peers draw
+   * it as sent rather than snapping it to the participant palette.
+   *
+   * `shareId` is the participant id of the sharer whose screen the stroke
+   * was drawn on; Core uses the share currently shown when it is omitted.
    */
   async sendAnnotation(
     strokeId: string,
     tool: 'pen' | 'highlighter' | 'eraser',
     color: { r: number; g: number; b: number; a: number },
-    points: { x: number; y: number; pressure?: number }[]
+    points: { x: number; y: number; pressure?: number }[],
+    width?: number,
+    shareId?: string
   ): Promise<void> {
     await this.sendMessage({
       type: 'send_annotation',
       stroke_id: strokeId,
       tool,
       color,
+      width,
       points,
+      share_id: shareId,
     })
   }
 
@@ -507,6 +874,16 @@ export class CoreClient {
     })
   }
 
+  /**
+   * Select the tool and color the local user draws with
+   */
+  async setTool(
+    tool: 'pen' | 'highlighter' | 'eraser',
+    color: { r: number; g: number; b: number; a: number }
+  ): Promise<void> {
+    await this.sendMessage({ type: 'set_tool', tool, color })
+  }
+
   /**
    * Clear all annotations
    */
@@ -514,15 +891,44 @@ export class CoreClient {
     await this.sendMessage({ type: 'clear_annotations' })
   }
 
+  /**
+   * Clear one participant's annotations for everyone (host only)
+   */
+  async clearParticipantAnnotations(participantId: string): Promise<void> {
+    await this.sendMessage({
+      type: 'clear_participant_annotations',
+      participant_id: participantId,
+    })
+  }
+
+  /**
+   * Allow or revoke annotating for a participant, optionally removing
+   * their existing strokes when revoking
+   */
+  async setAnnotationPermission(
+    participantId: string,
+    enabled: boolean,
+    clearStrokes = false
+  ): Promise<void> {
+    await this.sendMessage({
+      type: 'set_annotation_permission',
+      participant_id: participantId,
+      enabled,
+      clear_strokes: clearStrokes,
+    })
+  }
+
   // ========================================================================
   // Cursor Operations
   // ========================================================================
 
   /**
    * Send cursor position (normalized 0-1)
+   *
+   * With `laser`, peers see a presenter pointer instead of a cursor.
    */
-  async moveCursor(x: number, y: number): Promise<void> {
-    await this.sendMessage({ type: 'cursor_move', x, y })
+  async moveCursor(x: number, y: number, laser = false): Promise<void> {
+    await this.sendMessage({ type: 'cursor_move', x, y, laser })
   }
 
   /**
@@ -574,11 +980,18 @@ export class CoreClient {
   // Utility
   // ========================================================================
 
+  /**
+   * Change Core's log filter at runtime (RUST_LOG syntax, e.g. 'etch_core=debug')
+   */
+  async setLogLevel(filter: string): Promise<void> {
+    await this.sendMessage({ type: 'set_log_level', filter })
+  }
+
   /**
    * Ping Core (for health check)
    */
   async ping(): Promise<void> {
-    await this.sendMessage({ type: 'ping' })
+    await this.sendMessage({ type: 'ping', timestamp: Date.now() })
   }
 }
 
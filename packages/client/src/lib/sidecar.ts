@@ -182,7 +182,7 @@ export class SidecarClient {
   }
 
   /**
-   * Enumerate available screens and windows
+   * Enumerate available screens
    * Uses event-driven Promise resolution - no polling
    */
   async enumerateSources(): Promise<SourcesResponse> {
@@ -90,6 +90,24 @@ describe('useAnnotationOverlay', () => {
       expect(result.current.isOverlayActive).toBe(true)
     })
 
+    it('should report the bounds the overlay actually got', async () => {
+      const actual = { x: 0, y: 0, width: 1918, height: 1080 }
+      mockInvoke.mockImplementation(async (cmd) => {
+        if (cmd === 'create_annotation_overlay') {
+          return actual
+        }
+        return undefined
+      })
+
+      const { result } = renderHook(() => useAnnotationOverlay())
+
+      await act(async () => {
+        await result.current.createOverlay({ x: 0, y: 0, width: 1920, height: 1080 })
+      })
+
+      expect(result.current.overlayBounds).toEqual(actual)
+    })
+
     it('should update bounds if overlay already exists', async () => {
       mockInvoke.mockImplementation(async (cmd, _args) => {
         if (cmd === 'create_annotation_overlay') {
@@ -0,0 +1,91 @@
+// Annotation store benchmarks
+//
+// Run with: cargo bench --bench annotation_store
+//
+// These benchmarks measure the annotation hot path in real crate code:
+// building strokes from incremental point updates, and collecting the
+// strokes in render order as every redraw does.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use etch_core::annotation::AnnotationStore;
+use etch_core::{AnnotationTool, Color, Point};
+
+/// Points sent per stroke update, matching the WebView's batching
+const POINTS_PER_UPDATE: usize = 8;
+
+fn point(stroke: usize, i: usize) -> Point {
+    let t = i as f32 / 1000.0;
+    Point {
+        x: (0.1 + t + stroke as f32 * 0.001).fract(),
+        y: (0.5 + (t * 20.0).sin() * 0.2).clamp(0.0, 1.0),
+        pressure: 1.0,
+    }
+}
+
+/// A store with `strokes` completed strokes of `points` points each
+fn populated_store(strokes: usize, points: usize) -> AnnotationStore {
+    let mut store = AnnotationStore::new();
+    for s in 0..strokes {
+        let id = format!("stroke-{}", s);
+        store.start_stroke(
+            &id,
+            "participant-123",
+            AnnotationTool::Pen,
+            Color::RED,
+            point(s, 0),
+        );
+        let rest: Vec<Point> = (1..points).map(|i| point(s, i)).collect();
+        for chunk in rest.chunks(POINTS_PER_UPDATE) {
+            store.update_stroke(&id, chunk);
+        }
+        store.complete_stroke(&id);
+    }
+    store
+}
+
+fn bench_build_strokes(c: &mut Criterion) {
+    let mut group = c.benchmark_group("annotation_build");
+
+    for (strokes, points) in [(10, 100), (100, 100), (100, 1000)] {
+        let id = BenchmarkId::from_parameter(format!("{}x{}", strokes, points));
+        group.bench_with_input(id, &(strokes, points), |b, &(strokes, points)| {
+            b.iter(|| populated_store(black_box(strokes), black_box(points)));
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_strokes_in_render_order(c: &mut Criterion) {
+    let mut group = c.benchmark_group("annotation_strokes");
+
+    for strokes in [10, 100, 1000] {
+        let store = populated_store(strokes, 100);
+        group.bench_with_input(BenchmarkId::from_parameter(strokes), &store, |b, store| {
+            b.iter(|| black_box(store.strokes()).len());
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_strokes_iter(c: &mut Criterion) {
+    let mut group = c.benchmark_group("annotation_strokes_iter");
+
+    for strokes in [10, 100, 1000] {
+        let store = populated_store(strokes, 100);
+        group.bench_with_input(BenchmarkId::from_parameter(strokes), &store, |b, store| {
+            b.iter(|| black_box(store.strokes_iter()).count());
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_build_strokes,
+    bench_strokes_in_render_order,
+    bench_strokes_iter
+);
+criterion_main!(benches);
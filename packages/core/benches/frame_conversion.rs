@@ -0,0 +1,111 @@
+// Frame conversion benchmarks
+//
+// Run with: cargo bench --bench frame_conversion
+//
+// These benchmarks compare the per-frame conversion of captured ABGR pixels
+// for each `PixelFormat`: libyuv's I420 conversion, the direct NV12
+// conversion, and I420 followed by the repack to NV12 an encoder that takes
+// NV12 natively does with I420 input.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use etch_core::capture::convert::abgr_to_nv12;
+use livekit::webrtc::native::yuv_helper;
+use livekit::webrtc::prelude::{I420Buffer, NV12Buffer};
+
+/// Common capture sizes
+const SIZES: [(u32, u32); 3] = [(1280, 720), (1920, 1080), (3840, 2160)];
+
+/// A gradient, so no conversion sees a constant image
+fn abgr_frame(width: u32, height: u32) -> Vec<u8> {
+    (0..height)
+        .flat_map(|y| {
+            (0..width)
+                .flat_map(move |x| [(x % 256) as u8, (y % 256) as u8, ((x + y) % 256) as u8, 255])
+        })
+        .collect()
+}
+
+fn to_i420(src: &[u8], width: u32, height: u32, dst: &mut I420Buffer) {
+    let (stride_y, stride_u, stride_v) = dst.strides();
+    let (data_y, data_u, data_v) = dst.data_mut();
+    yuv_helper::abgr_to_i420(
+        src,
+        width * 4,
+        data_y,
+        stride_y,
+        data_u,
+        stride_u,
+        data_v,
+        stride_v,
+        width as i32,
+        height as i32,
+    );
+}
+
+fn bench_frame_conversion(c: &mut Criterion) {
+    let mut group = c.benchmark_group("frame_conversion");
+
+    for (width, height) in SIZES {
+        let src = abgr_frame(width, height);
+        let size = format!("{}x{}", width, height);
+        group.throughput(Throughput::Elements(u64::from(width * height)));
+
+        group.bench_with_input(BenchmarkId::new("i420", &size), &src, |b, src| {
+            let mut i420 = I420Buffer::new(width, height);
+            b.iter(|| to_i420(black_box(src), width, height, &mut i420));
+        });
+
+        group.bench_with_input(BenchmarkId::new("nv12", &size), &src, |b, src| {
+            let mut nv12 = NV12Buffer::new(width, height);
+            b.iter(|| {
+                let (stride_y, stride_uv) = nv12.strides();
+                let (data_y, data_uv) = nv12.data_mut();
+                abgr_to_nv12(
+                    black_box(src),
+                    width * 4,
+                    data_y,
+                    stride_y,
+                    data_uv,
+                    stride_uv,
+                    width,
+                    height,
+                );
+            });
+        });
+
+        group.bench_with_input(BenchmarkId::new("i420_then_nv12", &size), &src, |b, src| {
+            let mut i420 = I420Buffer::new(width, height);
+            let mut nv12 = NV12Buffer::new(width, height);
+            b.iter(|| {
+                to_i420(black_box(src), width, height, &mut i420);
+
+                let (src_y, src_u, src_v) = i420.data();
+                let (stride_y, stride_u, stride_v) = i420.strides();
+                let (dst_stride_y, dst_stride_uv) = nv12.strides();
+                let (dst_y, dst_uv) = nv12.data_mut();
+                for (src_row, dst_row) in src_y
+                    .chunks(stride_y as usize)
+                    .zip(dst_y.chunks_mut(dst_stride_y as usize))
+                {
+                    dst_row[..width as usize].copy_from_slice(&src_row[..width as usize]);
+                }
+                let chroma_width = width.div_ceil(2) as usize;
+                for ((u_row, v_row), dst_row) in src_u
+                    .chunks(stride_u as usize)
+                    .zip(src_v.chunks(stride_v as usize))
+                    .zip(dst_uv.chunks_mut(dst_stride_uv as usize))
+                {
+                    for (x, pair) in dst_row[..chroma_width * 2].chunks_exact_mut(2).enumerate() {
+                        pair[0] = u_row[x];
+                        pair[1] = v_row[x];
+                    }
+                }
+            });
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_frame_conversion);
+criterion_main!(benches);
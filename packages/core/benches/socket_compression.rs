@@ -0,0 +1,90 @@
+// Socket compression benchmarks
+//
+// Run with: cargo bench --bench socket_compression
+//
+// These benchmarks measure the CPU cost of encoding video frame messages
+// with and without deflate compression, and report the resulting sizes.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use etch_core::socket::framing::{encode_message, FramingMode, FramingOptions};
+use etch_core::socket::OutgoingMessage;
+use etch_core::FrameFormat;
+
+const WIDTH: u32 = 1280;
+const HEIGHT: u32 = 720;
+
+/// Synthetic screen content: flat regions with some horizontal structure
+fn synthetic_rgba_frame() -> Vec<u8> {
+    let mut data = Vec::with_capacity((WIDTH * HEIGHT * 4) as usize);
+    for y in 0..HEIGHT {
+        for x in 0..WIDTH {
+            let band = ((y / 24) % 4) as u8 * 40;
+            let text = if (x / 6 + y / 3) % 11 == 0 { 0 } else { 255 };
+            data.extend_from_slice(&[band, text, band.wrapping_add(60), 255]);
+        }
+    }
+    data
+}
+
+/// Stand-in for JPEG output: high-entropy bytes that do not deflate
+fn synthetic_jpeg_frame() -> Vec<u8> {
+    let mut state = 0x2545_f491u32;
+    (0..120 * 1024)
+        .map(|_| {
+            // xorshift32
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as u8
+        })
+        .collect()
+}
+
+fn video_frame(format: FrameFormat, frame_data: Vec<u8>) -> OutgoingMessage {
+    OutgoingMessage::VideoFrame {
+        participant_id: "participant-123".to_string(),
+        track_id: "track-456".to_string(),
+        width: WIDTH,
+        height: HEIGHT,
+        timestamp: 0,
+        wall_timestamp: 0,
+        format,
+        frame_data,
+    }
+}
+
+fn bench_frame_encoding(c: &mut Criterion) {
+    let mut group = c.benchmark_group("frame_encoding");
+
+    let frames = [
+        (
+            "rgba",
+            video_frame(FrameFormat::Rgba, synthetic_rgba_frame()),
+        ),
+        (
+            "jpeg",
+            video_frame(FrameFormat::Jpeg, synthetic_jpeg_frame()),
+        ),
+    ];
+
+    for (name, msg) in frames.iter() {
+        for compress in [false, true] {
+            let options = FramingOptions {
+                mode: FramingMode::Binary,
+                compress,
+            };
+            let size = encode_message(msg, options).unwrap().len();
+            println!("{} compress={}: {} bytes", name, compress, size);
+
+            let id = format!("{}_{}", name, if compress { "deflate" } else { "raw" });
+            group.bench_function(id, |b| {
+                b.iter(|| encode_message(black_box(msg), options).unwrap());
+            });
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_frame_encoding);
+criterion_main!(benches);
@@ -3,19 +3,42 @@
 //! Stores strokes in memory, handles stroke lifecycle,
 //! and provides data for rendering.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
 
-use crate::{AnnotationTool, Color, Point};
+use crate::{clock, AnnotationTool, Color, Point};
 
 /// A single stroke (pen, highlighter, or eraser path)
-#[derive(Debug, Clone)]
+///
+/// Times are milliseconds on Core's monotonic clock (`clock::elapsed`), the
+/// same clock relayed and recorded frames are stamped with, so a replay can
+/// line strokes up with video. They are local: a stroke received in a
+/// snapshot is stamped when it arrives.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Stroke {
     pub id: String,
     pub participant_id: String,
     pub tool: AnnotationTool,
     pub color: Color,
+    /// Fraction of the shared screen's width; the renderer scales it by
+    /// each point's pressure. Zero in snapshots from older peers.
+    #[serde(default)]
+    pub width: f32,
     pub points: Vec<Point>,
     pub completed: bool,
+    /// Screen share the stroke was drawn on, as the sharer's participant id;
+    /// `None` from older peers, drawn over whichever share is shown
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub share_id: Option<String>,
+    /// When the stroke was started
+    #[serde(skip)]
+    pub created_at: u64,
+    /// When each point was added, parallel to `points`
+    #[serde(skip)]
+    pub point_times: Vec<u64>,
+    /// When the stroke was completed
+    #[serde(skip)]
+    pub completed_at: Option<u64>,
 }
 
 impl Stroke {
@@ -24,34 +47,95 @@ impl Stroke {
         participant_id: String,
         tool: AnnotationTool,
         color: Color,
+        width: f32,
         start_point: Point,
     ) -> Self {
+        let now = now_ms();
         Self {
             id,
             participant_id,
             tool,
             color,
+            width,
             points: vec![start_point],
             completed: false,
+            share_id: None,
+            created_at: now,
+            point_times: vec![now],
+            completed_at: None,
         }
     }
 
     /// Add points to the stroke
     pub fn add_points(&mut self, points: &[Point]) {
+        let now = now_ms();
         self.points.extend_from_slice(points);
+        self.point_times.resize(self.points.len(), now);
     }
 
     /// Mark stroke as completed
     pub fn complete(&mut self) {
+        if !self.completed {
+            self.completed_at = Some(now_ms());
+        }
         self.completed = true;
     }
 }
 
+/// Core clock time in milliseconds
+fn now_ms() -> u64 {
+    clock::elapsed().as_millis() as u64
+}
+
+/// One step of drawing, for replaying annotations
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AnnotationEvent {
+    StrokeStart {
+        /// Core clock time (ms)
+        at: u64,
+        stroke_id: String,
+        participant_id: String,
+        tool: AnnotationTool,
+        color: Color,
+        width: f32,
+        point: Point,
+        /// Share the stroke was drawn on, as the sharer's participant id
+        #[serde(skip_serializing_if = "Option::is_none")]
+        share_id: Option<String>,
+    },
+    /// Points added together in one update
+    StrokePoints {
+        at: u64,
+        stroke_id: String,
+        points: Vec<Point>,
+    },
+    StrokeComplete {
+        at: u64,
+        stroke_id: String,
+    },
+}
+
+impl AnnotationEvent {
+    /// Core clock time (ms) the event happened at
+    pub fn at(&self) -> u64 {
+        match self {
+            Self::StrokeStart { at, .. }
+            | Self::StrokePoints { at, .. }
+            | Self::StrokeComplete { at, .. } => *at,
+        }
+    }
+}
+
 /// In-memory annotation store
 pub struct AnnotationStore {
     strokes: HashMap<String, Stroke>,
     /// Order of stroke IDs for rendering (oldest first)
     stroke_order: Vec<String>,
+    /// While false, stroke mutations are ignored; clearing still works
+    enabled: bool,
+    /// Per-participant overrides; participants not listed may annotate
+    participant_permissions: HashMap<String, bool>,
 }
 
 impl AnnotationStore {
@@ -59,38 +143,111 @@ impl AnnotationStore {
         Self {
             strokes: HashMap::new(),
             stroke_order: Vec::new(),
+            enabled: true,
+            participant_permissions: HashMap::new(),
         }
     }
 
+    /// Allow or block annotating
+    ///
+    /// Existing strokes are kept; the host can still clear them.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Whether strokes can currently be added or changed
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Allow or block annotating for one participant
+    ///
+    /// Revoking keeps the participant's strokes unless `clear_strokes` is set.
+    pub fn set_participant_enabled(
+        &mut self,
+        participant_id: &str,
+        enabled: bool,
+        clear_strokes: bool,
+    ) {
+        self.participant_permissions
+            .insert(participant_id.to_string(), enabled);
+        if !enabled && clear_strokes {
+            self.delete_by_participant(participant_id);
+        }
+    }
+
+    /// Whether a participant's strokes are currently accepted
+    pub fn can_annotate(&self, participant_id: &str) -> bool {
+        self.enabled
+            && self
+                .participant_permissions
+                .get(participant_id)
+                .copied()
+                .unwrap_or(true)
+    }
+
+    /// Whether the author of an existing stroke may still change it
+    fn can_modify(&self, stroke_id: &str) -> bool {
+        self.strokes
+            .get(stroke_id)
+            .is_some_and(|s| self.can_annotate(&s.participant_id))
+    }
+
     /// Start a new stroke
+    ///
+    /// Returns the stroke, e.g. to set its `share_id`, unless it was dropped.
     pub fn start_stroke(
         &mut self,
         stroke_id: &str,
         participant_id: &str,
         tool: AnnotationTool,
         color: Color,
+        width: f32,
         start_point: Point,
-    ) {
+    ) -> Option<&mut Stroke> {
+        if !self.can_annotate(participant_id) {
+            return None;
+        }
+        let Some(start_point) = start_point.sanitized() else {
+            tracing::warn!("Dropping stroke {} with an invalid start point", stroke_id);
+            return None;
+        };
         let stroke = Stroke::new(
             stroke_id.to_string(),
             participant_id.to_string(),
             tool,
             color,
+            tool.stroke_width(Some(width)),
             start_point,
         );
         self.strokes.insert(stroke_id.to_string(), stroke);
         self.stroke_order.push(stroke_id.to_string());
+        self.strokes.get_mut(stroke_id)
     }
 
     /// Add points to an existing stroke
     pub fn update_stroke(&mut self, stroke_id: &str, points: &[Point]) {
+        if !self.can_modify(stroke_id) {
+            return;
+        }
         if let Some(stroke) = self.strokes.get_mut(stroke_id) {
-            stroke.add_points(points);
+            let valid: Vec<Point> = points.iter().filter_map(|p| p.sanitized()).collect();
+            if valid.len() < points.len() {
+                tracing::warn!(
+                    "Dropped {} invalid points from stroke {}",
+                    points.len() - valid.len(),
+                    stroke_id
+                );
+            }
+            stroke.add_points(&valid);
         }
     }
 
     /// Mark a stroke as completed
     pub fn complete_stroke(&mut self, stroke_id: &str) {
+        if !self.can_modify(stroke_id) {
+            return;
+        }
         if let Some(stroke) = self.strokes.get_mut(stroke_id) {
             stroke.complete();
         }
@@ -98,6 +255,12 @@ impl AnnotationStore {
 
     /// Delete a stroke
     pub fn delete_stroke(&mut self, stroke_id: &str) {
+        if self.enabled {
+            self.remove(stroke_id);
+        }
+    }
+
+    fn remove(&mut self, stroke_id: &str) {
         self.strokes.remove(stroke_id);
         self.stroke_order.retain(|id| id != stroke_id);
     }
@@ -110,10 +273,29 @@ impl AnnotationStore {
 
     /// Get all strokes in render order
     pub fn strokes(&self) -> Vec<&Stroke> {
+        self.strokes_iter().collect()
+    }
+
+    /// All strokes in render order, without collecting them (e.g. per redraw)
+    pub fn strokes_iter(&self) -> impl Iterator<Item = &Stroke> + '_ {
         self.stroke_order
             .iter()
             .filter_map(|id| self.strokes.get(id))
-            .collect()
+    }
+
+    /// Strokes to draw over a share, in render order; all of them when no
+    /// share is shown
+    ///
+    /// Includes strokes without a `share_id`, which older peers send.
+    pub fn strokes_for_share<'a>(
+        &'a self,
+        share_id: Option<&'a str>,
+    ) -> impl Iterator<Item = &'a Stroke> + 'a {
+        self.strokes_iter()
+            .filter(move |s| match (share_id, &s.share_id) {
+                (Some(shown), Some(drawn_on)) => shown == drawn_on,
+                _ => true,
+            })
     }
 
     /// Get stroke count
@@ -131,6 +313,114 @@ impl AnnotationStore {
         self.strokes.get(stroke_id)
     }
 
+    /// The newest `max` strokes in render order, for a late joiner
+    pub fn snapshot(&self, max: usize) -> Vec<Stroke> {
+        let skip = self.stroke_order.len().saturating_sub(max);
+        self.stroke_order[skip..]
+            .iter()
+            .filter_map(|id| self.strokes.get(id))
+            .cloned()
+            .collect()
+    }
+
+    /// Add a whole stroke received in a snapshot
+    ///
+    /// Strokes already known are kept as they are, so overlapping snapshots
+    /// are harmless - unless ours is still incomplete and the snapshot's is
+    /// complete, e.g. because `StrokeComplete` or some points were lost;
+    /// then the snapshot's replaces it. Returns whether the stroke was added
+    /// or replaced.
+    pub fn insert_stroke(&mut self, mut stroke: Stroke) -> bool {
+        if !self.can_annotate(&stroke.participant_id) {
+            return false;
+        }
+        if let Some(known) = self.strokes.get(&stroke.id) {
+            if known.completed || !stroke.completed || known.participant_id != stroke.participant_id
+            {
+                return false;
+            }
+        }
+        stroke.points.retain_mut(|p| match p.sanitized() {
+            Some(valid) => {
+                *p = valid;
+                true
+            }
+            None => false,
+        });
+        if stroke.points.is_empty() {
+            tracing::warn!(
+                "Dropping snapshot stroke {} without valid points",
+                stroke.id
+            );
+            return false;
+        }
+        stroke.width = stroke.tool.stroke_width(Some(stroke.width));
+        let now = now_ms();
+        stroke.created_at = now;
+        stroke.point_times = vec![now; stroke.points.len()];
+        stroke.completed_at = stroke.completed.then_some(now);
+        match self.strokes.get(&stroke.id) {
+            // Keep when it was started, so replays still line up
+            Some(known) => stroke.created_at = known.created_at,
+            None => self.stroke_order.push(stroke.id.clone()),
+        }
+        self.strokes.insert(stroke.id.clone(), stroke);
+        true
+    }
+
+    /// Drawing of the strokes held, as events ordered by time
+    ///
+    /// Points added at the same time are merged into one `StrokePoints`.
+    /// Deleted and cleared strokes are not included.
+    pub fn event_log(&self) -> Vec<AnnotationEvent> {
+        let mut events = Vec::new();
+        for stroke in self.strokes_iter() {
+            let Some((first, rest)) = stroke.points.split_first() else {
+                continue;
+            };
+            events.push(AnnotationEvent::StrokeStart {
+                at: stroke.created_at,
+                stroke_id: stroke.id.clone(),
+                participant_id: stroke.participant_id.clone(),
+                tool: stroke.tool,
+                color: stroke.color,
+                width: stroke.width,
+                point: *first,
+                share_id: stroke.share_id.clone(),
+            });
+
+            let times = stroke.point_times.get(1..).unwrap_or_default();
+            let mut i = 0;
+            while i < rest.len() {
+                let at = times.get(i).copied().unwrap_or(stroke.created_at);
+                let len = times
+                    .get(i..)
+                    .unwrap_or_default()
+                    .iter()
+                    .take_while(|&&t| t == at)
+                    .count()
+                    .max(1);
+                let end = (i + len).min(rest.len());
+                events.push(AnnotationEvent::StrokePoints {
+                    at,
+                    stroke_id: stroke.id.clone(),
+                    points: rest[i..end].to_vec(),
+                });
+                i = end;
+            }
+
+            if let Some(at) = stroke.completed_at {
+                events.push(AnnotationEvent::StrokeComplete {
+                    at,
+                    stroke_id: stroke.id.clone(),
+                });
+            }
+        }
+        // Stable, so events at the same time keep stroke order
+        events.sort_by_key(AnnotationEvent::at);
+        events
+    }
+
     /// Delete all strokes by a specific participant
     pub fn delete_by_participant(&mut self, participant_id: &str) {
         let to_delete: Vec<String> = self
@@ -141,7 +431,7 @@ impl AnnotationStore {
             .collect();
 
         for id in to_delete {
-            self.delete_stroke(&id);
+            self.remove(&id);
         }
     }
 }
@@ -151,3 +441,237 @@ impl Default for AnnotationStore {
         Self::new()
     }
 }
+
+/// Most strokes sent to a late joiner; older ones are left out
+pub const SNAPSHOT_MAX_STROKES: usize = 500;
+
+/// Target payload size of one snapshot message
+///
+/// Kept under LiveKit's ~15 KiB limit for reliable data packets; larger
+/// snapshots are split across several messages.
+pub const SNAPSHOT_CHUNK_BYTES: usize = 14 * 1024;
+
+/// Most points one stroke message carries
+///
+/// A point takes up to ~50 bytes of JSON, so a message stays well under
+/// `SNAPSHOT_CHUNK_BYTES`; a finished stroke with more points sends the rest
+/// in follow-up messages.
+pub const STROKE_MAX_POINTS_PER_MESSAGE: usize = 200;
+
+/// Split strokes into consecutive groups whose JSON stays under `max_bytes`
+///
+/// A stroke larger than `max_bytes` on its own has every other point dropped
+/// until it fits (its first and last points are kept), and goes in a group
+/// by itself: a snapshot can't split one stroke across messages.
+pub fn chunk_strokes(strokes: Vec<Stroke>, max_bytes: usize) -> Vec<Vec<Stroke>> {
+    let json_len = |stroke: &Stroke| serde_json::to_vec(stroke).map_or(0, |json| json.len());
+    let mut chunks = Vec::new();
+    let mut chunk = Vec::new();
+    let mut chunk_bytes = 0;
+
+    for mut stroke in strokes {
+        let mut bytes = json_len(&stroke);
+        while bytes > max_bytes && stroke.points.len() > 2 {
+            halve_points(&mut stroke);
+            bytes = json_len(&stroke);
+        }
+        if !chunk.is_empty() && chunk_bytes + bytes > max_bytes {
+            chunks.push(std::mem::take(&mut chunk));
+            chunk_bytes = 0;
+        }
+        chunk_bytes += bytes;
+        chunk.push(stroke);
+    }
+    if !chunk.is_empty() {
+        chunks.push(chunk);
+    }
+    chunks
+}
+
+/// Drop every other point of a stroke, keeping its first and last
+fn halve_points(stroke: &mut Stroke) {
+    let last = stroke.points.len().saturating_sub(1);
+    let keep = |i: usize| i % 2 == 0 || i == last;
+    stroke.points = stroke
+        .points
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| keep(*i))
+        .map(|(_, point)| *point)
+        .collect();
+    stroke.point_times = stroke
+        .point_times
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| keep(*i))
+        .map(|(_, time)| *time)
+        .collect();
+}
+
+/// How long the author waits for peers to acknowledge a completed stroke
+/// before sending it again
+pub const STROKE_ACK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Times a completed stroke is sent again before the author gives up on a peer
+pub const STROKE_RESEND_LIMIT: u32 = 5;
+
+/// Completed local strokes some peers haven't acknowledged yet
+///
+/// `StrokeComplete` is sent reliably, but a peer can still miss it - it
+/// joined mid-stroke, or reconnected. Peers acknowledge every completed
+/// stroke they receive; strokes still missing an ack after
+/// `STROKE_ACK_TIMEOUT` are sent again as a whole (in a `Snapshot`) to the
+/// peers that didn't answer, up to `STROKE_RESEND_LIMIT` times.
+#[derive(Debug, Default)]
+pub struct StrokeAcks {
+    pending: HashMap<String, PendingAck>,
+}
+
+#[derive(Debug)]
+struct PendingAck {
+    /// Peers yet to acknowledge
+    peers: HashSet<String>,
+    /// When the stroke was last sent
+    sent_at: Instant,
+    resends: u32,
+}
+
+impl StrokeAcks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Expect `peers` to acknowledge a stroke just completed
+    ///
+    /// Returns true when nothing was pending before, i.e. a resend check
+    /// needs scheduling.
+    pub fn track(
+        &mut self,
+        stroke_id: &str,
+        peers: impl IntoIterator<Item = String>,
+        now: Instant,
+    ) -> bool {
+        let was_empty = self.pending.is_empty();
+        let peers: HashSet<String> = peers.into_iter().collect();
+        if peers.is_empty() {
+            return false;
+        }
+        self.pending.insert(
+            stroke_id.to_string(),
+            PendingAck {
+                peers,
+                sent_at: now,
+                resends: 0,
+            },
+        );
+        was_empty
+    }
+
+    /// A peer received a completed stroke
+    pub fn ack(&mut self, stroke_id: &str, peer: &str) {
+        if let Some(pending) = self.pending.get_mut(stroke_id) {
+            pending.peers.remove(peer);
+            if pending.peers.is_empty() {
+                self.pending.remove(stroke_id);
+            }
+        }
+    }
+
+    /// Stop waiting on a peer that left the room
+    pub fn peer_left(&mut self, peer: &str) {
+        self.pending.retain(|_, pending| {
+            pending.peers.remove(peer);
+            !pending.peers.is_empty()
+        });
+    }
+
+    /// Stop waiting on a stroke, e.g. one that was deleted
+    pub fn forget(&mut self, stroke_id: &str) {
+        self.pending.remove(stroke_id);
+    }
+
+    /// Stop waiting on anything, e.g. after leaving the room
+    pub fn clear(&mut self) {
+        self.pending.clear();
+    }
+
+    /// Strokes to send again now, with the peers still missing each
+    ///
+    /// Counts the resend; strokes past `STROKE_RESEND_LIMIT` are given up on
+    /// with a warning.
+    pub fn due(&mut self, now: Instant) -> Vec<(String, Vec<String>)> {
+        let mut due = Vec::new();
+        self.pending.retain(|stroke_id, pending| {
+            if now.duration_since(pending.sent_at) < STROKE_ACK_TIMEOUT {
+                return true;
+            }
+            if pending.resends >= STROKE_RESEND_LIMIT {
+                tracing::warn!(
+                    "Giving up on stroke {}: {} peers never acknowledged it",
+                    stroke_id,
+                    pending.peers.len()
+                );
+                return false;
+            }
+            pending.resends += 1;
+            pending.sent_at = now;
+            let mut peers: Vec<String> = pending.peers.iter().cloned().collect();
+            peers.sort();
+            due.push((stroke_id.clone(), peers));
+            true
+        });
+        due.sort();
+        due
+    }
+
+    /// Check if no stroke is waiting for acks
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+/// Window over which incoming stroke points are merged before dispatch
+///
+/// About one frame at 60 Hz. A remote peer drawing fast sends a DataTrack
+/// packet every few milliseconds; merging them within this window turns a
+/// burst into a single update. Longer windows save more events but add up to
+/// the window's length of latency and make a live stroke grow in visible
+/// chunks instead of smoothly.
+pub const STROKE_BATCH_WINDOW: Duration = Duration::from_millis(16);
+
+/// Accumulates stroke points between flushes, merged per stroke
+#[derive(Debug, Default)]
+pub struct StrokeUpdateBatcher {
+    /// Pending points per stroke, in order of first update
+    pending: Vec<(String, Vec<Point>)>,
+}
+
+impl StrokeUpdateBatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue points for a stroke
+    ///
+    /// Returns true when the batch was empty, i.e. a flush needs scheduling.
+    pub fn push(&mut self, stroke_id: &str, points: Vec<Point>) -> bool {
+        let was_empty = self.pending.is_empty();
+
+        match self.pending.iter_mut().find(|(id, _)| id == stroke_id) {
+            Some((_, pending)) => pending.extend(points),
+            None => self.pending.push((stroke_id.to_string(), points)),
+        }
+
+        was_empty
+    }
+
+    /// Take all pending updates, one per stroke
+    pub fn take(&mut self) -> Vec<(String, Vec<Point>)> {
+        std::mem::take(&mut self.pending)
+    }
+
+    /// Check if no updates are pending
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
@@ -0,0 +1,111 @@
+//! Programmatic control of Core from Rust
+//!
+//! `CoreHandle` drives an `Application` with the same commands the WebView
+//! sends over the socket, as typed `IncomingMessage`s instead of JSON. Another
+//! Rust host can embed Core this way, and tests can exercise the real
+//! handlers:
+//!
+//! ```ignore
+//! let (sender, events) = std::sync::mpsc::channel();
+//! let mut app = Application::new(EventProxy::Channel(sender), Default::default());
+//! let core = app.handle();
+//! core.join_room("wss://example.livekit.cloud", "token")?;
+//! while let Ok(event) = events.try_recv() {
+//!     app.handle_user_event(event);
+//! }
+//! ```
+//!
+//! Commands are queued like socket messages; results are reported as
+//! `OutgoingMessage`s to the socket client, if any, and are observable
+//! through `Application`'s accessors.
+
+use crate::socket::{self, IncomingMessage};
+use crate::{AnnotationTool, CaptureConfig, Color, EventProxy, Point, SourceType};
+
+/// Cloneable handle for sending commands to an `Application`
+#[derive(Debug, Clone)]
+pub struct CoreHandle {
+    proxy: EventProxy,
+}
+
+impl CoreHandle {
+    pub fn new(proxy: EventProxy) -> Self {
+        Self { proxy }
+    }
+
+    /// Send any command the socket accepts
+    ///
+    /// Fails only once the application's event loop has exited.
+    pub fn send(&self, command: IncomingMessage) -> anyhow::Result<()> {
+        socket::dispatch(&self.proxy, command)
+    }
+
+    pub fn join_room(&self, server_url: &str, token: &str) -> anyhow::Result<()> {
+        self.send(IncomingMessage::JoinRoom {
+            server_url: server_url.to_string(),
+            token: token.into(),
+            e2ee_passphrase: None,
+            initial_media: None,
+            auto_subscribe: true,
+            relay_format: crate::relay::DEFAULT_RELAY_FORMAT,
+        })
+    }
+
+    pub fn leave_room(&self) -> anyhow::Result<()> {
+        self.send(IncomingMessage::LeaveRoom)
+    }
+
+    /// Ask for the current roster, reported as `OutgoingMessage::Participants`
+    pub fn get_participants(&self) -> anyhow::Result<()> {
+        self.send(IncomingMessage::GetParticipants)
+    }
+
+    pub fn start_screen_share(&self, source_id: &str, config: CaptureConfig) -> anyhow::Result<()> {
+        self.send(IncomingMessage::StartScreenShare {
+            source_id: source_id.to_string(),
+            source_type: SourceType::Screen,
+            config: Some(config),
+            replace: false,
+        })
+    }
+
+    pub fn stop_screen_share(&self) -> anyhow::Result<()> {
+        self.send(IncomingMessage::StopScreenShare)
+    }
+
+    /// Draw a finished stroke as the local participant and publish it
+    ///
+    /// `width` is a fraction of the shared screen's width; `None` draws at
+    /// the tool's default.
+    pub fn send_annotation(
+        &self,
+        stroke_id: &str,
+        tool: AnnotationTool,
+        color: Color,
+        width: Option<f32>,
+        points: Vec<Point>,
+    ) -> anyhow::Result<()> {
+        self.send(IncomingMessage::SendAnnotation {
+            stroke_id: stroke_id.to_string(),
+            tool,
+            color,
+            width,
+            points,
+            share_id: None,
+        })
+    }
+
+    /// Select the tool and color the local user draws with
+    pub fn set_tool(&self, tool: AnnotationTool, color: Color) -> anyhow::Result<()> {
+        self.send(IncomingMessage::SetTool { tool, color })
+    }
+
+    pub fn clear_annotations(&self) -> anyhow::Result<()> {
+        self.send(IncomingMessage::ClearAnnotations)
+    }
+
+    /// Leave the room, stop capture and exit the event loop
+    pub fn shutdown(&self) -> anyhow::Result<()> {
+        self.send(IncomingMessage::Shutdown)
+    }
+}
@@ -0,0 +1,100 @@
+//! Adaptive published resolution
+//!
+//! Steps the screen share down a resolution ladder when connection stats
+//! show sustained congestion, and back up once the network recovers.
+//! Congestion is packet loss reported by the receiver, or a high round-trip
+//! time - a growing send queue shows up as RTT before it turns into loss.
+//!
+//! Decisions need several consecutive samples (one per second), with a
+//! longer window for stepping up than down, so a single bad report does
+//! not make the resolution oscillate.
+
+use crate::ConnectionStats;
+
+/// Height caps to step through, highest first
+pub const RESOLUTION_LADDER: [u32; 3] = [1080, 720, 540];
+
+/// Packet loss fraction treated as congestion
+const CONGESTED_PACKET_LOSS: f64 = 0.05;
+
+/// Round-trip time (ms) treated as congestion
+const CONGESTED_RTT_MS: f64 = 400.0;
+
+/// Packet loss fraction below which the connection counts as healthy
+const HEALTHY_PACKET_LOSS: f64 = 0.01;
+
+/// Round-trip time (ms) below which the connection counts as healthy
+const HEALTHY_RTT_MS: f64 = 200.0;
+
+/// Consecutive congested samples before stepping down
+const STEP_DOWN_AFTER: u32 = 3;
+
+/// Consecutive healthy samples before stepping up
+const STEP_UP_AFTER: u32 = 10;
+
+/// Resolution controller for one screen share
+#[derive(Debug, Default)]
+pub struct AdaptiveResolution {
+    /// Current height cap (None = capture resolution)
+    max_height: Option<u32>,
+    congested_samples: u32,
+    healthy_samples: u32,
+}
+
+impl AdaptiveResolution {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Current height cap
+    pub fn max_height(&self) -> Option<u32> {
+        self.max_height
+    }
+
+    /// Feed one stats sample; returns the new height cap if it changed
+    pub fn observe(&mut self, stats: &ConnectionStats) -> Option<Option<u32>> {
+        let rtt = stats.rtt_ms.unwrap_or(0.0);
+
+        if stats.packet_loss >= CONGESTED_PACKET_LOSS || rtt >= CONGESTED_RTT_MS {
+            self.healthy_samples = 0;
+            self.congested_samples += 1;
+            if self.congested_samples >= STEP_DOWN_AFTER {
+                self.congested_samples = 0;
+                let current = stats.resolution.map(|r| r.height);
+                return self.step_down(current);
+            }
+        } else if stats.packet_loss < HEALTHY_PACKET_LOSS && rtt < HEALTHY_RTT_MS {
+            self.congested_samples = 0;
+            self.healthy_samples += 1;
+            if self.healthy_samples >= STEP_UP_AFTER {
+                self.healthy_samples = 0;
+                return self.step_up();
+            }
+        } else {
+            // In between - neither degrade nor recover
+            self.congested_samples = 0;
+            self.healthy_samples = 0;
+        }
+
+        None
+    }
+
+    /// Cap below the currently published height, if the ladder goes lower
+    fn step_down(&mut self, current_height: Option<u32>) -> Option<Option<u32>> {
+        let current = current_height.or(self.max_height).unwrap_or(u32::MAX);
+        let next = RESOLUTION_LADDER.iter().copied().find(|&h| h < current)?;
+        self.max_height = Some(next);
+        Some(self.max_height)
+    }
+
+    /// Next cap up the ladder, or no cap above the top step
+    fn step_up(&mut self) -> Option<Option<u32>> {
+        let current = self.max_height?;
+        self.max_height = RESOLUTION_LADDER
+            .iter()
+            .rev()
+            .copied()
+            .find(|&h| h > current);
+        Some(self.max_height)
+    }
+}
@@ -0,0 +1,356 @@
+//! System audio capture for screen share
+//!
+//! Captures what the machine is playing so shared videos and music are
+//! audible to viewers. Samples are delivered as interleaved i16 chunks to a
+//! channel consumed by the room's screen share audio track.
+//!
+//! Backends:
+//! - Windows: WASAPI loopback on the default output device (via cpal)
+//! - macOS: ScreenCaptureKit audio of the main display (macOS 13+)
+//! - Linux: not implemented yet, `probe` returns `Unsupported`
+//!
+//! The capture runs on its own thread, independent of the video capture
+//! loop, so video capturer restarts do not interrupt audio.
+
+use std::sync::mpsc;
+use std::thread::JoinHandle;
+
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Format of the captured system audio
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AudioFormat {
+    pub sample_rate: u32,
+    pub num_channels: u32,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AudioCaptureError {
+    #[error("System audio capture is not supported on this platform")]
+    Unsupported,
+
+    #[error("No audio output device available")]
+    NoDevice,
+
+    #[error("Audio stream error: {0}")]
+    Stream(String),
+}
+
+/// Running system audio capture. Stops when dropped.
+pub struct SystemAudioCapture {
+    format: AudioFormat,
+    stop_tx: Option<mpsc::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl SystemAudioCapture {
+    /// Query the format system audio will be captured in
+    ///
+    /// Used to create the matching LiveKit audio source before capture starts.
+    pub fn probe() -> Result<AudioFormat, AudioCaptureError> {
+        backend::probe()
+    }
+
+    /// Start capturing system audio into `sink`
+    pub fn start(
+        format: AudioFormat,
+        sink: UnboundedSender<Vec<i16>>,
+    ) -> Result<Self, AudioCaptureError> {
+        let (stop_tx, stop_rx) = mpsc::channel();
+        let (ready_tx, ready_rx) = mpsc::channel();
+
+        // The stream is created and dropped on this thread - audio streams
+        // are not Send on every platform
+        let thread = std::thread::Builder::new()
+            .name("system-audio".to_string())
+            .spawn(move || backend::run(format, sink, stop_rx, ready_tx))
+            .map_err(|e| AudioCaptureError::Stream(e.to_string()))?;
+
+        match ready_rx.recv() {
+            Ok(Ok(())) => {
+                tracing::info!(
+                    "System audio capture started ({} Hz, {} ch)",
+                    format.sample_rate,
+                    format.num_channels
+                );
+                Ok(Self {
+                    format,
+                    stop_tx: Some(stop_tx),
+                    thread: Some(thread),
+                })
+            }
+            Ok(Err(e)) => {
+                let _ = thread.join();
+                Err(e)
+            }
+            Err(_) => {
+                let _ = thread.join();
+                Err(AudioCaptureError::Stream(
+                    "Audio thread exited during startup".to_string(),
+                ))
+            }
+        }
+    }
+
+    /// Format of the captured audio
+    pub fn format(&self) -> AudioFormat {
+        self.format
+    }
+
+    /// Stop capturing and wait for the capture thread to exit
+    pub fn stop(&mut self) {
+        if let Some(tx) = self.stop_tx.take() {
+            let _ = tx.send(());
+        }
+        if let Some(handle) = self.thread.take() {
+            let _ = handle.join();
+            tracing::info!("System audio capture stopped");
+        }
+    }
+}
+
+impl Drop for SystemAudioCapture {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod backend {
+    use super::*;
+    use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+
+    fn output_device() -> Result<(cpal::Device, cpal::SupportedStreamConfig), AudioCaptureError> {
+        let device = cpal::default_host()
+            .default_output_device()
+            .ok_or(AudioCaptureError::NoDevice)?;
+        let config = device
+            .default_output_config()
+            .map_err(|e| AudioCaptureError::Stream(e.to_string()))?;
+        Ok((device, config))
+    }
+
+    pub fn probe() -> Result<AudioFormat, AudioCaptureError> {
+        let (_, config) = output_device()?;
+        Ok(AudioFormat {
+            sample_rate: config.sample_rate().0,
+            num_channels: config.channels() as u32,
+        })
+    }
+
+    pub fn run(
+        format: AudioFormat,
+        sink: UnboundedSender<Vec<i16>>,
+        stop_rx: mpsc::Receiver<()>,
+        ready_tx: mpsc::Sender<Result<(), AudioCaptureError>>,
+    ) {
+        let stream = match build_loopback_stream(format, sink) {
+            Ok(stream) => stream,
+            Err(e) => {
+                let _ = ready_tx.send(Err(e));
+                return;
+            }
+        };
+
+        if let Err(e) = stream.play() {
+            let _ = ready_tx.send(Err(AudioCaptureError::Stream(e.to_string())));
+            return;
+        }
+        let _ = ready_tx.send(Ok(()));
+
+        // Keep the stream alive until stopped
+        let _ = stop_rx.recv();
+        drop(stream);
+    }
+
+    /// Build an input stream on the output device - WASAPI treats this as loopback
+    fn build_loopback_stream(
+        format: AudioFormat,
+        sink: UnboundedSender<Vec<i16>>,
+    ) -> Result<cpal::Stream, AudioCaptureError> {
+        let (device, config) = output_device()?;
+        if config.sample_rate().0 != format.sample_rate
+            || config.channels() as u32 != format.num_channels
+        {
+            return Err(AudioCaptureError::Stream(
+                "Output device format changed".to_string(),
+            ));
+        }
+
+        let err_fn = |e: cpal::StreamError| tracing::warn!("System audio stream error: {}", e);
+        let stream_config = config.config();
+
+        let stream = match config.sample_format() {
+            cpal::SampleFormat::F32 => device.build_input_stream(
+                &stream_config,
+                move |data: &[f32], _: &cpal::InputCallbackInfo| {
+                    let _ = sink.send(data.iter().copied().map(f32_to_i16).collect());
+                },
+                err_fn,
+                None,
+            ),
+            cpal::SampleFormat::I16 => device.build_input_stream(
+                &stream_config,
+                move |data: &[i16], _: &cpal::InputCallbackInfo| {
+                    let _ = sink.send(data.to_vec());
+                },
+                err_fn,
+                None,
+            ),
+            other => {
+                return Err(AudioCaptureError::Stream(format!(
+                    "Unsupported sample format: {:?}",
+                    other
+                )))
+            }
+        };
+
+        stream.map_err(|e| AudioCaptureError::Stream(e.to_string()))
+    }
+}
+
+/// Convert a float sample (-1.0..1.0) to i16
+fn f32_to_i16(sample: f32) -> i16 {
+    (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16
+}
+
+/// Interleave planar f32 audio (one byte buffer per channel) into i16
+///
+/// A single buffer is taken as already interleaved. Channels of unequal
+/// length are cut to the shortest.
+pub fn interleave_f32_planes(planes: &[&[u8]]) -> Vec<i16> {
+    let samples = |plane: &[u8]| -> Vec<f32> {
+        plane
+            .chunks_exact(4)
+            .map(|bytes| f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            .collect()
+    };
+
+    let channels: Vec<Vec<f32>> = planes.iter().map(|plane| samples(plane)).collect();
+    let frames = channels.iter().map(Vec::len).min().unwrap_or(0);
+    let mut interleaved = Vec::with_capacity(frames * channels.len());
+    for frame in 0..frames {
+        interleaved.extend(channels.iter().map(|channel| f32_to_i16(channel[frame])));
+    }
+    interleaved
+}
+
+#[cfg(target_os = "macos")]
+mod backend {
+    use super::*;
+    use core_media_rs::cm_sample_buffer::CMSampleBuffer;
+    use screencapturekit::{
+        shareable_content::SCShareableContent,
+        stream::{
+            configuration::SCStreamConfiguration, content_filter::SCContentFilter,
+            output_trait::SCStreamOutputTrait, output_type::SCStreamOutputType, SCStream,
+        },
+    };
+
+    /// ScreenCaptureKit resamples to whatever the stream asks for
+    const FORMAT: AudioFormat = AudioFormat {
+        sample_rate: 48_000,
+        num_channels: 2,
+    };
+
+    pub fn probe() -> Result<AudioFormat, AudioCaptureError> {
+        // Fails without the screen recording permission, which covers audio
+        main_display()?;
+        Ok(FORMAT)
+    }
+
+    fn main_display() -> Result<screencapturekit::shareable_content::SCDisplay, AudioCaptureError> {
+        let content =
+            SCShareableContent::get().map_err(|e| AudioCaptureError::Stream(format!("{:?}", e)))?;
+        content
+            .displays()
+            .into_iter()
+            .next()
+            .ok_or(AudioCaptureError::NoDevice)
+    }
+
+    /// Forwards each audio sample buffer to the sink
+    struct AudioOutput {
+        sink: UnboundedSender<Vec<i16>>,
+    }
+
+    impl SCStreamOutputTrait for AudioOutput {
+        fn did_output_sample_buffer(&self, sample: CMSampleBuffer, of_type: SCStreamOutputType) {
+            if !matches!(of_type, SCStreamOutputType::Audio) {
+                return;
+            }
+            let Ok(buffers) = sample.get_audio_buffer_list() else {
+                return;
+            };
+            // Float32, one buffer per channel
+            let planes: Vec<&[u8]> = (0..buffers.num_buffers())
+                .filter_map(|index| buffers.get(index))
+                .map(|buffer| buffer.data())
+                .collect();
+            let _ = self.sink.send(interleave_f32_planes(&planes));
+        }
+    }
+
+    fn build_stream(
+        format: AudioFormat,
+        sink: UnboundedSender<Vec<i16>>,
+    ) -> Result<SCStream, AudioCaptureError> {
+        let stream_error = |e| AudioCaptureError::Stream(format!("{:?}", e));
+        let display = main_display()?;
+        let config = SCStreamConfiguration::new()
+            .set_captures_audio(true)
+            .and_then(|config| config.set_sample_rate(format.sample_rate))
+            .and_then(|config| config.set_channel_count(format.num_channels as u8))
+            .map_err(stream_error)?;
+        let filter = SCContentFilter::new().with_display_excluding_windows(&display, &[]);
+
+        let mut stream = SCStream::new(&filter, &config);
+        stream.add_output_handler(AudioOutput { sink }, SCStreamOutputType::Audio);
+        Ok(stream)
+    }
+
+    pub fn run(
+        format: AudioFormat,
+        sink: UnboundedSender<Vec<i16>>,
+        stop_rx: mpsc::Receiver<()>,
+        ready_tx: mpsc::Sender<Result<(), AudioCaptureError>>,
+    ) {
+        let stream = match build_stream(format, sink) {
+            Ok(stream) => stream,
+            Err(e) => {
+                let _ = ready_tx.send(Err(e));
+                return;
+            }
+        };
+
+        if let Err(e) = stream.start_capture() {
+            let _ = ready_tx.send(Err(AudioCaptureError::Stream(format!("{:?}", e))));
+            return;
+        }
+        let _ = ready_tx.send(Ok(()));
+
+        // Keep the stream alive until stopped
+        let _ = stop_rx.recv();
+        if let Err(e) = stream.stop_capture() {
+            tracing::warn!("Failed to stop system audio stream: {:?}", e);
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+mod backend {
+    use super::*;
+
+    pub fn probe() -> Result<AudioFormat, AudioCaptureError> {
+        Err(AudioCaptureError::Unsupported)
+    }
+
+    pub fn run(
+        _format: AudioFormat,
+        _sink: UnboundedSender<Vec<i16>>,
+        _stop_rx: mpsc::Receiver<()>,
+        ready_tx: mpsc::Sender<Result<(), AudioCaptureError>>,
+    ) {
+        let _ = ready_tx.send(Err(AudioCaptureError::Unsupported));
+    }
+}
@@ -0,0 +1,66 @@
+//! Pixel conversions for the capture path that libyuv doesn't cover here
+//!
+//! The I420 path goes through `yuv_helper::abgr_to_i420`. NV12 is produced
+//! here in a single pass over the captured pixels, with the same BT.601
+//! limited-range coefficients libyuv uses, so both paths publish the same
+//! colors.
+
+/// Convert ABGR pixels (bytes in R, G, B, A order) to NV12
+///
+/// `dst_y` receives `height` rows of luma and `dst_uv` `ceil(height / 2)`
+/// rows of interleaved U/V, each pair averaged over a 2x2 block. Odd widths
+/// and heights repeat the last column/row, as libyuv does.
+#[allow(clippy::too_many_arguments)]
+pub fn abgr_to_nv12(
+    src: &[u8],
+    src_stride: u32,
+    dst_y: &mut [u8],
+    stride_y: u32,
+    dst_uv: &mut [u8],
+    stride_uv: u32,
+    width: u32,
+    height: u32,
+) {
+    let (width, height) = (width as usize, height as usize);
+    let (src_stride, stride_y, stride_uv) =
+        (src_stride as usize, stride_y as usize, stride_uv as usize);
+
+    for row in 0..height.div_ceil(2) {
+        let top = &src[row * 2 * src_stride..];
+        // Last row of an odd height is paired with itself
+        let bottom = if row * 2 + 1 < height {
+            &src[(row * 2 + 1) * src_stride..]
+        } else {
+            top
+        };
+
+        luma_row(top, &mut dst_y[row * 2 * stride_y..], width);
+        if row * 2 + 1 < height {
+            luma_row(bottom, &mut dst_y[(row * 2 + 1) * stride_y..], width);
+        }
+
+        let uv = &mut dst_uv[row * stride_uv..];
+        for x in 0..width.div_ceil(2) {
+            let left = x * 8;
+            let right = if x * 2 + 1 < width { left + 4 } else { left };
+            let [r, g, b] = [0, 1, 2].map(|c| {
+                (u32::from(top[left + c])
+                    + u32::from(top[right + c])
+                    + u32::from(bottom[left + c])
+                    + u32::from(bottom[right + c])
+                    + 2)
+                    / 4
+            });
+            let (r, g, b) = (r as i32, g as i32, b as i32);
+            uv[x * 2] = (((112 * b - 74 * g - 38 * r + 128) >> 8) + 128) as u8;
+            uv[x * 2 + 1] = (((112 * r - 94 * g - 18 * b + 128) >> 8) + 128) as u8;
+        }
+    }
+}
+
+fn luma_row(src: &[u8], dst: &mut [u8], width: usize) {
+    for (y, px) in dst[..width].iter_mut().zip(src.chunks_exact(4)) {
+        let (r, g, b) = (u32::from(px[0]), u32::from(px[1]), u32::from(px[2]));
+        *y = (((66 * r + 129 * g + 25 * b + 128) >> 8) + 16) as u8;
+    }
+}
@@ -0,0 +1,149 @@
+//! Display configuration change detection
+//!
+//! Keeps a generation counter that is bumped whenever displays are added,
+//! removed or reconfigured (resolution, arrangement), so cached enumeration
+//! results can be recognized as stale.
+//!
+//! Backends:
+//! - macOS: `CGDisplayRegisterReconfigurationCallback`
+//! - Windows: `WM_DISPLAYCHANGE` delivered to a hidden top-level window
+//! - Linux: no notifications - the generation never changes and callers rely
+//!   on background refreshes
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Once;
+
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+static START: Once = Once::new();
+
+/// Start listening for display changes (idempotent)
+///
+/// Call from the main thread: on macOS the reconfiguration callbacks are
+/// delivered through the main run loop.
+pub fn start() {
+    START.call_once(backend::start);
+}
+
+/// Current display configuration generation
+pub fn generation() -> u64 {
+    GENERATION.load(Ordering::SeqCst)
+}
+
+#[cfg_attr(not(any(target_os = "macos", target_os = "windows")), allow(dead_code))]
+fn invalidate() {
+    let generation = GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    tracing::info!("Display configuration changed (generation {})", generation);
+}
+
+#[cfg(target_os = "macos")]
+mod backend {
+    use std::ffi::c_void;
+
+    /// `kCGDisplayBeginConfigurationFlag`
+    const BEGIN_CONFIGURATION_FLAG: u32 = 1 << 0;
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGDisplayRegisterReconfigurationCallback(
+            callback: extern "C" fn(display: u32, flags: u32, user_info: *mut c_void),
+            user_info: *mut c_void,
+        ) -> i32;
+    }
+
+    extern "C" fn on_reconfiguration(_display: u32, flags: u32, _user_info: *mut c_void) {
+        // Called once before and once after each change - count the latter
+        if flags & BEGIN_CONFIGURATION_FLAG == 0 {
+            super::invalidate();
+        }
+    }
+
+    pub fn start() {
+        let error = unsafe {
+            CGDisplayRegisterReconfigurationCallback(on_reconfiguration, std::ptr::null_mut())
+        };
+        if error != 0 {
+            tracing::warn!(
+                "Failed to register display reconfiguration callback: {}",
+                error
+            );
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod backend {
+    use windows::core::w;
+    use windows::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        CreateWindowExW, DefWindowProcW, DispatchMessageW, GetMessageW, RegisterClassW, MSG,
+        WINDOW_EX_STYLE, WM_DISPLAYCHANGE, WNDCLASSW, WS_OVERLAPPED,
+    };
+
+    unsafe extern "system" fn window_proc(
+        hwnd: HWND,
+        msg: u32,
+        wparam: WPARAM,
+        lparam: LPARAM,
+    ) -> LRESULT {
+        if msg == WM_DISPLAYCHANGE {
+            super::invalidate();
+        }
+        DefWindowProcW(hwnd, msg, wparam, lparam)
+    }
+
+    /// Pump messages for a hidden window - WM_DISPLAYCHANGE is broadcast to
+    /// top-level windows only, so a message-only window would miss it
+    fn run() {
+        unsafe {
+            let class_name = w!("EtchDisplayWatch");
+            let class = WNDCLASSW {
+                lpfnWndProc: Some(window_proc),
+                lpszClassName: class_name,
+                ..Default::default()
+            };
+            if RegisterClassW(&class) == 0 {
+                tracing::warn!("Failed to register display watch window class");
+                return;
+            }
+
+            if let Err(e) = CreateWindowExW(
+                WINDOW_EX_STYLE::default(),
+                class_name,
+                w!("Etch display watch"),
+                WS_OVERLAPPED,
+                0,
+                0,
+                0,
+                0,
+                None,
+                None,
+                None,
+                None,
+            ) {
+                tracing::warn!("Failed to create display watch window: {}", e);
+                return;
+            }
+
+            let mut msg = MSG::default();
+            while GetMessageW(&mut msg, None, 0, 0).as_bool() {
+                DispatchMessageW(&msg);
+            }
+        }
+    }
+
+    pub fn start() {
+        if let Err(e) = std::thread::Builder::new()
+            .name("display-watch".to_string())
+            .spawn(run)
+        {
+            tracing::warn!("Failed to start display watch thread: {}", e);
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+mod backend {
+    pub fn start() {
+        tracing::debug!("Display change notifications not available on this platform");
+    }
+}
@@ -5,31 +5,50 @@
 //!
 //! Architecture based on Hopp's capture system:
 //! - DesktopCapturer for native screen capture (60fps)
-//! - I420 color space for WebRTC compatibility (same as Hopp)
+//! - I420 color space for WebRTC compatibility (same as Hopp), or NV12 for
+//!   encoders that take it directly (`PixelFormat`)
 //! - NativeVideoSource for publishing to LiveKit
-//! - Reusable VideoFrame wrapped in Arc<Mutex> to avoid per-frame allocation
+//! - Frames handed to a publisher thread through a drop-oldest mailbox, with
+//!   buffers recycled to avoid per-frame allocation
 
+pub mod adaptive;
+pub mod audio;
+pub mod convert;
+pub mod display_watch;
+pub mod pipeline;
+mod publisher;
+
+use std::collections::VecDeque;
 use std::io::Cursor;
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{mpsc, Arc, Mutex as StdMutex};
 
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
 use livekit::webrtc::{
     desktop_capturer::{
-        CaptureError, DesktopCaptureSourceType, DesktopCapturer, DesktopCapturerOptions,
-        DesktopFrame,
+        CaptureError, CaptureSource, DesktopCaptureSourceType, DesktopCapturer,
+        DesktopCapturerOptions, DesktopFrame,
     },
-    native::yuv_helper,
-    prelude::{I420Buffer, VideoFrame, VideoRotation},
+    prelude::{I420Buffer, VideoBuffer},
     video_source::native::NativeVideoSource,
 };
 use parking_lot::Mutex;
-use winit::event_loop::EventLoopProxy;
 
-use crate::{CaptureConfig, ScreenInfo, SourceType, UserEvent};
+use crate::recording::{Recorder, RecordingError};
+use crate::{CaptureConfig, ErrorCode, EventProxy, ScreenInfo, SourceType, UserEvent};
+use pipeline::FramePipeline;
 
 /// Frame capture interval in milliseconds (~45fps)
 const FRAME_CAPTURE_INTERVAL_MS: u64 = 22;
 
+/// Lowest framerate a share can be set to
+pub const MIN_FRAMERATE: u32 = 1;
+
+/// Highest framerate a share can be set to; capture itself tops out at
+/// ~45 fps (`FRAME_CAPTURE_INTERVAL_MS`)
+pub const MAX_FRAMERATE: u32 = 60;
+
 /// Get display dimensions and position by display ID
 /// Returns (x, y, width, height) or None if not found
 #[cfg(target_os = "macos")]
@@ -73,6 +92,48 @@ const RESTART_DELAY_MS: u64 = 200;
 /// Delay between retry attempts within a restart (ms)
 const RETRY_DELAY_MS: u64 = 100;
 
+/// How persistently the capture loop recovers from failing frames
+///
+/// The defaults suit a desktop; a laptop that sleeps often may want more
+/// restarts and longer delays before the share is given up. Fields left
+/// out of the JSON keep their defaults.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct RestartPolicy {
+    /// Consecutive failed frames before the capturer is restarted
+    pub max_failures: u64,
+    /// Restarts before giving up, and retries within each restart
+    pub max_restart_attempts: u64,
+    /// Pause before a restart to let the system stabilize (ms)
+    pub restart_delay_ms: u64,
+    /// Pause between retries within a restart (ms)
+    pub retry_delay_ms: u64,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            max_failures: MAX_FAILURES,
+            max_restart_attempts: MAX_RESTART_ATTEMPTS,
+            restart_delay_ms: RESTART_DELAY_MS,
+            retry_delay_ms: RETRY_DELAY_MS,
+        }
+    }
+}
+
+/// Pixel layout captured frames are handed to the encoder in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PixelFormat {
+    /// Planar Y, U, V, converted by libyuv; what every WebRTC encoder takes
+    #[default]
+    I420,
+    /// Y plane then interleaved UV. Encoders that accept it natively (e.g.
+    /// libvpx, most hardware encoders) skip repacking each frame; others
+    /// convert it back to I420, so it only pays off on those encoders.
+    Nv12,
+}
+
 /// Target thumbnail width
 const THUMBNAIL_WIDTH: u32 = 320;
 
@@ -88,6 +149,15 @@ const THUMBNAIL_POLL_INTERVAL_MS: u64 = 16;
 /// Total timeout for all thumbnail captures (seconds)
 const THUMBNAIL_TOTAL_TIMEOUT_SECS: u64 = 10;
 
+/// Maximum number of thumbnail workers, including the enumerating thread
+const THUMBNAIL_WORKERS: usize = 2;
+
+/// Interval for re-publishing the frozen frame while paused (ms)
+const PAUSED_FRAME_INTERVAL_MS: u64 = 1000;
+
+/// JPEG quality for stills (0-100)
+const STILL_JPEG_QUALITY: u8 = 90;
+
 /// Errors that can occur during screen capture setup
 #[derive(Debug, thiserror::Error)]
 pub enum StreamCaptureError {
@@ -107,10 +177,111 @@ pub enum StreamCaptureError {
 /// Messages for stream runtime control
 #[derive(Debug, Clone, Copy)]
 #[allow(dead_code)]
-enum StreamMessage {
+pub enum StreamMessage {
     Stop,
     /// Sent when capture encounters permanent errors and needs restart
     Failed,
+    /// Cap the published frame height (None = capture resolution)
+    SetMaxHeight(Option<u32>),
+    /// Freeze (true) or resume (false) capture without ending the stream
+    SetPaused(bool),
+    /// Encode the newest frame as a still image
+    CaptureStill,
+    /// Capture another screen, keeping the video source and publication
+    SwitchSource(u64),
+    /// Capture at most this many frames per second
+    SetFramerate(u32),
+}
+
+/// What the capture loop does next
+#[derive(Debug)]
+pub enum LoopEvent {
+    /// Act on a control message
+    Message(StreamMessage),
+    /// No message within the frame interval: grab a frame
+    Tick,
+    /// Stopped, failed, or the controlling `Capturer` is gone: end the loop
+    Exit(&'static str),
+}
+
+/// Wait up to `timeout` for the capture loop's next control message
+pub fn next_loop_event(
+    rx: &mpsc::Receiver<StreamMessage>,
+    timeout: std::time::Duration,
+) -> LoopEvent {
+    match rx.recv_timeout(timeout) {
+        Ok(StreamMessage::Stop) => LoopEvent::Exit("Received stop signal"),
+        Ok(StreamMessage::Failed) => LoopEvent::Exit("Stream failed"),
+        Ok(msg) => LoopEvent::Message(msg),
+        Err(mpsc::RecvTimeoutError::Timeout) => LoopEvent::Tick,
+        Err(mpsc::RecvTimeoutError::Disconnected) => LoopEvent::Exit("Channel disconnected"),
+    }
+}
+
+/// Capture lifecycle as seen by the rest of Core
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureState {
+    /// No capture thread running
+    Idle,
+    /// Capturing and publishing new frames
+    Capturing,
+    /// Capture thread alive, publishing the last frame only
+    Paused,
+}
+
+/// Frame counts behind the once-a-second `CaptureStats` report
+struct FrameStats {
+    /// Frames handed to the publisher since the last report
+    since_report: u64,
+    /// Frames handed to the publisher since the share started
+    total: u64,
+    last_report: std::time::Instant,
+}
+
+impl FrameStats {
+    fn new() -> Self {
+        Self {
+            since_report: 0,
+            total: 0,
+            last_report: std::time::Instant::now(),
+        }
+    }
+
+    /// Count a frame handed to the publisher
+    fn frame(&mut self) {
+        self.since_report += 1;
+        self.total += 1;
+    }
+
+    /// Log and report the stats once a second has passed since the last time
+    fn report_if_due(
+        &mut self,
+        publisher: &publisher::FramePublisher,
+        restart_attempts: u64,
+        event_proxy: Option<&EventProxy>,
+    ) {
+        let elapsed = self.last_report.elapsed();
+        if elapsed < std::time::Duration::from_secs(1) {
+            return;
+        }
+        let fps = self.since_report as f64 / elapsed.as_secs_f64();
+        let dropped = publisher.take_dropped();
+        tracing::info!(
+            "Screen capture FPS: {:.1} ({} dropped before publish)",
+            fps,
+            dropped
+        );
+        if let Some(proxy) = event_proxy {
+            let _ = proxy.send_event(UserEvent::CaptureStats {
+                fps,
+                frames_sent: self.total,
+                frames_dropped: dropped,
+                restart_attempts: restart_attempts as u32,
+            });
+        }
+        self.since_report = 0;
+        self.last_report = std::time::Instant::now();
+    }
 }
 
 /// State for capture restart operations
@@ -120,30 +291,87 @@ struct CaptureRestartState {
     temp_error_count: Arc<Mutex<u64>>,
 }
 
+/// Enumerated screens and the display generation they were taken at
+type CachedSources = (u64, Vec<ScreenInfo>);
+
+/// Last enumeration result, shared outside the `Capturer` lock
+///
+/// Entries are tagged with the display generation they were taken at and
+/// ignored once displays change. Cloning shares the same cache.
+#[derive(Clone, Default)]
+pub struct SourceCache {
+    entry: Arc<Mutex<Option<CachedSources>>>,
+    /// Whether an enumeration is currently running
+    refreshing: Arc<AtomicBool>,
+}
+
+impl SourceCache {
+    /// Cached screens, if displays haven't changed since they were taken
+    pub fn get(&self) -> Option<Vec<ScreenInfo>> {
+        match &*self.entry.lock() {
+            Some((generation, screens)) if *generation == display_watch::generation() => {
+                Some(screens.clone())
+            }
+            _ => None,
+        }
+    }
+
+    fn store(&self, generation: u64, screens: Vec<ScreenInfo>) {
+        *self.entry.lock() = Some((generation, screens));
+    }
+
+    /// Mark an enumeration as started; false if one is already running
+    pub fn begin_refresh(&self) -> bool {
+        !self.refreshing.swap(true, Ordering::SeqCst)
+    }
+
+    /// Mark the running enumeration as finished
+    pub fn end_refresh(&self) {
+        self.refreshing.store(false, Ordering::SeqCst);
+    }
+
+    /// Drop the cached screens, e.g. after a display change the platform
+    /// didn't report
+    pub fn invalidate(&self) {
+        *self.entry.lock() = None;
+    }
+}
+
 /// Screen capturer using LiveKit DesktopCapturer
 pub struct Capturer {
-    event_loop_proxy: Option<EventLoopProxy<UserEvent>>,
-    is_capturing: bool,
+    event_loop_proxy: Option<EventProxy>,
+    state: CaptureState,
     current_source: Option<String>,
     video_source: Option<NativeVideoSource>,
     stream_tx: Option<mpsc::Sender<StreamMessage>>,
     capture_thread: Option<std::thread::JoinHandle<()>>,
+    /// Active local recording, fed by the capture loop
+    recorder: Arc<Mutex<Option<Recorder>>>,
+    /// Result of the last `enumerate_sources`
+    source_cache: SourceCache,
 }
 
 impl Capturer {
     pub fn new() -> Self {
         Self {
             event_loop_proxy: None,
-            is_capturing: false,
+            state: CaptureState::Idle,
             current_source: None,
             video_source: None,
             stream_tx: None,
             capture_thread: None,
+            recorder: Arc::new(Mutex::new(None)),
+            source_cache: SourceCache::default(),
         }
     }
 
+    /// Cache of enumerated sources, usable without locking the capturer
+    pub fn source_cache(&self) -> SourceCache {
+        self.source_cache.clone()
+    }
+
     /// Set the event loop proxy for sending events
-    pub fn set_event_loop_proxy(&mut self, proxy: EventLoopProxy<UserEvent>) {
+    pub fn set_event_loop_proxy(&mut self, proxy: EventProxy) {
         self.event_loop_proxy = Some(proxy);
     }
 
@@ -156,27 +384,20 @@ impl Capturer {
     ///
     /// Uses parallel thumbnail capture (like Hopp) for fast enumeration.
     /// Note: Window capture is not supported - only screens are returned.
-    pub fn enumerate_sources(&self) -> Vec<ScreenInfo> {
-        // Create capturer with options (following Hopp's pattern)
-        #[cfg(target_os = "macos")]
-        let mut options = DesktopCapturerOptions::new(DesktopCaptureSourceType::Screen);
-        #[cfg(not(target_os = "macos"))]
-        let options = DesktopCapturerOptions::new(DesktopCaptureSourceType::Screen);
-
-        // Disable system picker on macOS to enumerate sources programmatically
-        #[cfg(target_os = "macos")]
-        {
-            options.set_sck_system_picker(false);
-        }
-
-        let capturer = DesktopCapturer::new(options);
-
-        if capturer.is_none() {
+    ///
+    /// The result is stored in the source cache.
+    ///
+    /// Fails when no capturer can be created, which usually means screen
+    /// recording permission is missing.
+    pub fn enumerate_sources(&self) -> Result<Vec<ScreenInfo>, StreamCaptureError> {
+        // Read before enumerating so a change during the scan marks it stale
+        let generation = display_watch::generation();
+
+        // Also captures thumbnails below, as the first worker
+        let Some(mut capturer) = new_screen_capturer() else {
             tracing::error!("Failed to create DesktopCapturer for enumeration");
-            return vec![];
-        }
-
-        let capturer = capturer.unwrap();
+            return Err(StreamCaptureError::CapturerCreationFailed);
+        };
         let sources = capturer.get_source_list();
         let source_count = sources.len();
 
@@ -185,14 +406,11 @@ impl Capturer {
             source_count
         );
 
-        // Shared storage for thumbnail results: (source_id, index, thumbnail_base64)
-        let results: Arc<StdMutex<Vec<(u64, usize, String)>>> = Arc::new(StdMutex::new(Vec::new()));
-
-        // Collect source metadata and spawn parallel capture threads
+        // Collect source metadata and queue one thumbnail job per source
         let mut screens = Vec::new();
-        let mut handles = Vec::new();
+        let mut jobs = VecDeque::new();
 
-        for source in sources {
+        for source in &sources {
             let id = source.id();
             let title = source.title();
 
@@ -218,53 +436,54 @@ impl Capturer {
                 thumbnail: None,
             });
 
-            // Spawn thread for parallel thumbnail capture
-            let results_clone = results.clone();
-            let (stop_tx, stop_rx) = std::sync::mpsc::channel::<()>();
-
-            let handle = std::thread::spawn(move || {
-                capture_thumbnail_thread(id, screen_idx, name, results_clone, stop_rx);
+            jobs.push_back(ThumbnailJob {
+                source_id: id,
+                idx: screen_idx,
+                display_name: name,
             });
-
-            handles.push((handle, stop_tx));
         }
 
-        // Wait for all thumbnails to be captured (or timeout)
+        // Capture thumbnails on a bounded pool of workers (or until timeout)
         let start_time = std::time::Instant::now();
-        let timeout = std::time::Duration::from_secs(THUMBNAIL_TOTAL_TIMEOUT_SECS);
+        let deadline = start_time + std::time::Duration::from_secs(THUMBNAIL_TOTAL_TIMEOUT_SECS);
+        let jobs = Arc::new(StdMutex::new(jobs));
+        let results: Arc<StdMutex<Vec<(usize, String)>>> = Arc::new(StdMutex::new(Vec::new()));
+
+        let workers: Vec<_> = (1..THUMBNAIL_WORKERS.min(source_count))
+            .filter_map(|n| {
+                let jobs = jobs.clone();
+                let results = results.clone();
+                std::thread::Builder::new()
+                    .name(format!("thumbnail-{}", n))
+                    .spawn(move || thumbnail_worker(jobs, results, deadline))
+                    .map_err(|e| tracing::error!("Failed to spawn thumbnail worker: {}", e))
+                    .ok()
+            })
+            .collect();
 
-        loop {
-            {
-                let res = results.lock().unwrap();
-                if res.len() >= source_count {
-                    tracing::info!("All {} thumbnails captured", res.len());
-                    break;
-                }
-            }
+        capture_thumbnails(&mut capturer, &sources, &jobs, &results, deadline);
+        for worker in workers {
+            let _ = worker.join();
+        }
 
-            if start_time.elapsed() > timeout {
+        // Apply thumbnails to results
+        {
+            let res = results.lock().unwrap();
+            if res.len() >= source_count {
+                tracing::info!("All {} thumbnails captured", res.len());
+            } else {
                 tracing::warn!(
                     "Thumbnail capture timeout after {:?}, got {}/{} thumbnails",
                     start_time.elapsed(),
-                    results.lock().unwrap().len(),
+                    res.len(),
                     source_count
                 );
-                break;
             }
 
-            std::thread::sleep(std::time::Duration::from_millis(33));
-        }
-
-        // Stop all capture threads
-        for (handle, stop_tx) in handles {
-            let _ = stop_tx.send(());
-            let _ = handle.join();
-        }
-
-        // Apply thumbnails to results
-        {
-            let res = results.lock().unwrap();
-            for (_, idx, thumbnail) in res.iter() {
+            for (idx, thumbnail) in res.iter() {
+                if thumbnail.is_empty() {
+                    continue;
+                }
                 if let Some(screen) = screens.get_mut(*idx) {
                     screen.thumbnail = Some(thumbnail.clone());
                 }
@@ -277,7 +496,9 @@ impl Capturer {
             screens.len()
         );
 
-        screens
+        self.source_cache.store(generation, screens.clone());
+
+        Ok(screens)
     }
 
     /// Start capturing the specified source
@@ -287,18 +508,13 @@ impl Capturer {
         _source_type: SourceType,
         config: &CaptureConfig,
     ) -> Result<(), StreamCaptureError> {
-        if self.is_capturing {
+        if self.state != CaptureState::Idle {
             self.stop_capture();
         }
 
         tracing::info!("Starting capture of source: {}", source_id);
 
-        // Parse the numeric ID from source_id (format: "screen:123" or "window:456")
-        let id: u64 = source_id
-            .split(':')
-            .nth(1)
-            .and_then(|s| s.parse().ok())
-            .ok_or_else(|| StreamCaptureError::SourceNotFound(source_id.to_string()))?;
+        let id = parse_source_id(source_id)?;
 
         // Create channel for stream control
         let (tx, rx) = mpsc::channel();
@@ -308,15 +524,36 @@ impl Capturer {
         let video_source = self.video_source.clone();
         let width = config.width;
         let height = config.height;
+        let framerate = config.framerate;
+        let pixel_format = config.pixel_format;
         let event_proxy = self.event_loop_proxy.clone();
+        let recorder = self.recorder.clone();
+        let policy = config.restart_policy;
 
-        // Spawn capture thread
+        // Spawn capture thread, reporting a panic instead of dying silently
         let handle = std::thread::spawn(move || {
-            run_capture_loop(id, width, height, rx, video_source, event_proxy);
+            let panic_proxy = event_proxy.clone();
+            let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                run_capture_loop(
+                    id,
+                    width,
+                    height,
+                    framerate,
+                    pixel_format,
+                    rx,
+                    video_source,
+                    recorder,
+                    event_proxy,
+                    policy,
+                );
+            }));
+            if let Err(panic) = result {
+                report_capture_panic(id, panic.as_ref(), panic_proxy);
+            }
         });
 
         self.capture_thread = Some(handle);
-        self.is_capturing = true;
+        self.state = CaptureState::Capturing;
         self.current_source = Some(source_id.to_string());
 
         Ok(())
@@ -324,7 +561,7 @@ impl Capturer {
 
     /// Stop capturing
     pub fn stop_capture(&mut self) {
-        if !self.is_capturing {
+        if self.state == CaptureState::Idle {
             return;
         }
 
@@ -340,19 +577,153 @@ impl Capturer {
             let _ = handle.join();
         }
 
-        self.is_capturing = false;
+        self.state = CaptureState::Idle;
         self.current_source = None;
     }
 
-    /// Check if currently capturing
+    /// Freeze the share: stop grabbing the screen but keep the thread and
+    /// track alive, re-publishing the last frame so viewers see a still image
+    pub fn pause_capture(&mut self) -> bool {
+        if self.state() != CaptureState::Capturing {
+            return false;
+        }
+        if let Some(tx) = &self.stream_tx {
+            let _ = tx.send(StreamMessage::SetPaused(true));
+        }
+        tracing::info!("Capture paused");
+        self.state = CaptureState::Paused;
+        true
+    }
+
+    /// Resume capturing after `pause_capture`
+    pub fn resume_capture(&mut self) -> bool {
+        if self.state() != CaptureState::Paused {
+            return false;
+        }
+        if let Some(tx) = &self.stream_tx {
+            let _ = tx.send(StreamMessage::SetPaused(false));
+        }
+        tracing::info!("Capture resumed");
+        self.state = CaptureState::Capturing;
+        true
+    }
+
+    /// Snapshot the newest frame as a JPEG
+    ///
+    /// The still is encoded off the capture thread and delivered as
+    /// `UserEvent::StillCaptured` (or an `Error`). Returns false if nothing is
+    /// being captured. Works while paused, returning the frozen frame.
+    pub fn capture_still(&self) -> bool {
+        match &self.stream_tx {
+            Some(tx) if self.state != CaptureState::Idle => {
+                tx.send(StreamMessage::CaptureStill).is_ok()
+            }
+            _ => false,
+        }
+    }
+
+    /// Current capture state
+    ///
+    /// `Idle` once the capture thread has exited, even if `stop_capture`
+    /// hasn't been called yet.
+    pub fn state(&self) -> CaptureState {
+        if self.capture_thread_exited() {
+            CaptureState::Idle
+        } else {
+            self.state
+        }
+    }
+
+    /// Check if currently capturing new frames (false while paused)
     pub fn is_capturing(&self) -> bool {
-        self.is_capturing
+        self.state() == CaptureState::Capturing
+    }
+
+    /// Check if capture is paused
+    pub fn is_paused(&self) -> bool {
+        self.state() == CaptureState::Paused
+    }
+
+    /// Whether the capture thread ended on its own (gave up, lost its
+    /// source, lost its control channel or panicked) and `stop_capture`
+    /// hasn't cleaned up after it yet
+    pub fn capture_thread_exited(&self) -> bool {
+        self.capture_thread
+            .as_ref()
+            .is_some_and(|handle| handle.is_finished())
     }
 
     /// Get current source ID
     pub fn current_source(&self) -> Option<&str> {
         self.current_source.as_deref()
     }
+
+    /// Capture another screen without ending the share
+    ///
+    /// The capture thread re-selects the source and reports
+    /// `UserEvent::CaptureSourceChanged` (or a `source_not_found` error);
+    /// the published track stays the same throughout.
+    pub fn switch_source(&self, source_id: &str) -> Result<(), StreamCaptureError> {
+        let id = parse_source_id(source_id)?;
+        match &self.stream_tx {
+            Some(tx) if self.state != CaptureState::Idle => tx
+                .send(StreamMessage::SwitchSource(id))
+                .map_err(|_| StreamCaptureError::CaptureFailed("Capture has ended".to_string())),
+            _ => Err(StreamCaptureError::CaptureFailed(
+                "No capture in progress".to_string(),
+            )),
+        }
+    }
+
+    /// The capture thread moved on to another source
+    pub(crate) fn source_switched(&mut self, source_id: String) {
+        if self.state != CaptureState::Idle {
+            self.current_source = Some(source_id);
+        }
+    }
+
+    /// Start recording captured frames to an MP4 file
+    ///
+    /// Frames are recorded whenever capture is running, so a recording may
+    /// be started before or during a screen share.
+    pub fn start_recording(&self, path: &str) -> Result<(), RecordingError> {
+        let recorder = Recorder::new(path)?;
+        tracing::info!("Recording started: {}", path);
+        *self.recorder.lock() = Some(recorder);
+        Ok(())
+    }
+
+    /// Detach the active recording, if any
+    ///
+    /// The caller finalizes it with `Recorder::finish`, which may block
+    /// while the encoder flushes.
+    pub fn take_recorder(&self) -> Option<Recorder> {
+        self.recorder.lock().take()
+    }
+
+    /// Cap the height of published frames while capturing
+    ///
+    /// Frames taller than `max_height` are downscaled (keeping aspect ratio)
+    /// before conversion. `None` publishes at the capture resolution.
+    pub fn set_max_height(&self, max_height: Option<u32>) {
+        if let Some(tx) = &self.stream_tx {
+            let _ = tx.send(StreamMessage::SetMaxHeight(max_height));
+        }
+    }
+
+    /// Change the capture framerate while capturing
+    ///
+    /// Takes effect on the next frame; the published track is untouched.
+    pub fn set_framerate(&self, framerate: u32) {
+        if let Some(tx) = &self.stream_tx {
+            let _ = tx.send(StreamMessage::SetFramerate(framerate));
+        }
+    }
+
+    /// Check if a recording is active
+    pub fn is_recording(&self) -> bool {
+        self.recorder.lock().is_some()
+    }
 }
 
 impl Default for Capturer {
@@ -367,44 +738,104 @@ impl Drop for Capturer {
     }
 }
 
-/// Capture thumbnail in a dedicated thread (Hopp-style parallel capture)
-///
-/// This function runs in its own thread and captures a thumbnail for a single screen.
-/// It creates its own DesktopCapturer, finds the source by ID, and captures a thumbnail.
-/// Results are written to the shared results vector.
-fn capture_thumbnail_thread(
+/// A source waiting for its thumbnail
+struct ThumbnailJob {
     source_id: u64,
+    /// Index into the enumerated screens
     idx: usize,
     display_name: String,
-    results: Arc<StdMutex<Vec<(u64, usize, String)>>>,
-    stop_rx: mpsc::Receiver<()>,
+}
+
+/// Capture thumbnails for queued sources until the queue is empty
+///
+/// Each worker creates a single DesktopCapturer and moves it from source to
+/// source (`start_capture` stops the previous capture), so the capturer and
+/// source list are set up once per worker rather than once per display.
+/// Failed sources store an empty result so the caller can tell them apart
+/// from timeouts.
+fn thumbnail_worker(
+    jobs: Arc<StdMutex<VecDeque<ThumbnailJob>>>,
+    results: Arc<StdMutex<Vec<(usize, String)>>>,
+    deadline: std::time::Instant,
 ) {
-    tracing::debug!(
-        "Starting thumbnail capture thread for screen {} ({})",
-        source_id,
-        display_name
-    );
+    let Some(mut capturer) = new_screen_capturer() else {
+        tracing::error!("Failed to create DesktopCapturer for thumbnails");
+        return;
+    };
+    let sources = capturer.get_source_list();
+
+    capture_thumbnails(&mut capturer, &sources, &jobs, &results, deadline);
+}
 
-    // Check if we already have a result for this source (avoid duplicates)
+/// Create a screen capturer for enumeration and thumbnails (following Hopp's pattern)
+fn new_screen_capturer() -> Option<DesktopCapturer> {
+    #[cfg(target_os = "macos")]
+    let mut options = DesktopCapturerOptions::new(DesktopCaptureSourceType::Screen);
+    #[cfg(not(target_os = "macos"))]
+    let options = DesktopCapturerOptions::new(DesktopCaptureSourceType::Screen);
+
+    // Disable system picker on macOS to enumerate sources programmatically
+    #[cfg(target_os = "macos")]
     {
-        let res = results.lock().unwrap();
-        for (id, _, _) in res.iter() {
-            if *id == source_id {
-                tracing::debug!("Thumbnail already captured for screen {}", source_id);
-                return;
-            }
-        }
+        options.set_sck_system_picker(false);
     }
 
-    // Shared state for the callback
-    let results_cb = results.clone();
-    let captured = Arc::new(std::sync::atomic::AtomicBool::new(false));
-    let captured_cb = captured.clone();
+    DesktopCapturer::new(options)
+}
+
+/// Take jobs off the queue and capture their thumbnails with one capturer
+fn capture_thumbnails(
+    capturer: &mut DesktopCapturer,
+    sources: &[CaptureSource],
+    jobs: &StdMutex<VecDeque<ThumbnailJob>>,
+    results: &Arc<StdMutex<Vec<(usize, String)>>>,
+    deadline: std::time::Instant,
+) {
+    loop {
+        if std::time::Instant::now() >= deadline {
+            break;
+        }
+        let Some(job) = jobs.lock().unwrap().pop_front() else {
+            break;
+        };
+
+        tracing::debug!(
+            "Capturing thumbnail for screen {} ({})",
+            job.source_id,
+            job.display_name
+        );
 
-    // Create callback that processes frame and stores result (new API)
-    let callback = move |result: Result<DesktopFrame, CaptureError>| {
+        let Some(source) = sources.iter().find(|s| s.id() == job.source_id) else {
+            tracing::warn!("Screen {} not found in worker capturer", job.source_id);
+            results.lock().unwrap().push((job.idx, String::new()));
+            continue;
+        };
+
+        let captured = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        capturer.start_capture(
+            Some(source.clone()),
+            thumbnail_callback(job, Arc::clone(results), captured.clone()),
+        );
+
+        // Poll until captured or out of time
+        while !captured.load(std::sync::atomic::Ordering::SeqCst)
+            && std::time::Instant::now() < deadline
+        {
+            std::thread::sleep(std::time::Duration::from_millis(THUMBNAIL_POLL_INTERVAL_MS));
+            capturer.capture_frame();
+        }
+    }
+}
+
+/// Frame callback storing the first usable frame of a source as its thumbnail
+fn thumbnail_callback(
+    job: ThumbnailJob,
+    results: Arc<StdMutex<Vec<(usize, String)>>>,
+    captured: Arc<std::sync::atomic::AtomicBool>,
+) -> impl FnMut(Result<DesktopFrame, CaptureError>) + Send + 'static {
+    move |result: Result<DesktopFrame, CaptureError>| {
         // Skip if already captured
-        if captured_cb.load(std::sync::atomic::Ordering::SeqCst) {
+        if captured.load(std::sync::atomic::Ordering::SeqCst) {
             return;
         }
 
@@ -413,10 +844,9 @@ fn capture_thumbnail_thread(
             Ok(frame) => frame,
             Err(_) => {
                 // Any error - store empty result
-                tracing::debug!("Thumbnail capture error for screen {}", source_id);
-                let mut res = results_cb.lock().unwrap();
-                res.push((source_id, idx, String::new()));
-                captured_cb.store(true, std::sync::atomic::Ordering::SeqCst);
+                tracing::debug!("Thumbnail capture error for screen {}", job.source_id);
+                results.lock().unwrap().push((job.idx, String::new()));
+                captured.store(true, std::sync::atomic::Ordering::SeqCst);
                 return;
             }
         };
@@ -449,73 +879,11 @@ fn capture_thumbnail_thread(
         if let Some(thumbnail) = create_thumbnail_from_rgb(&raw_rgb, width as u32, height as u32) {
             tracing::info!(
                 "Thumbnail captured for screen {} ({})",
-                source_id,
-                display_name
+                job.source_id,
+                job.display_name
             );
-            let mut res = results_cb.lock().unwrap();
-            res.push((source_id, idx, thumbnail));
-            captured_cb.store(true, std::sync::atomic::Ordering::SeqCst);
-        }
-    };
-
-    // Create capturer for this thread (following Hopp's pattern)
-    #[cfg(target_os = "macos")]
-    let mut options = DesktopCapturerOptions::new(DesktopCaptureSourceType::Screen);
-    #[cfg(not(target_os = "macos"))]
-    let options = DesktopCapturerOptions::new(DesktopCaptureSourceType::Screen);
-
-    #[cfg(target_os = "macos")]
-    {
-        options.set_sck_system_picker(false);
-    }
-
-    let capturer = DesktopCapturer::new(options);
-    if capturer.is_none() {
-        tracing::error!("Failed to create DesktopCapturer for screen {}", source_id);
-        // Store empty result so main thread knows we're done
-        let mut res = results.lock().unwrap();
-        res.push((source_id, idx, String::new()));
-        return;
-    }
-    let mut capturer = capturer.unwrap();
-
-    // Find the source by ID in this thread's capturer
-    let sources = capturer.get_source_list();
-    let source = sources.iter().find(|s| s.id() == source_id);
-
-    if source.is_none() {
-        tracing::warn!("Screen {} not found in thread capturer", source_id);
-        // Store empty result so main thread knows we're done
-        let mut res = results.lock().unwrap();
-        res.push((source_id, idx, String::new()));
-        return;
-    }
-
-    // Start capture for this source (new API: callback passed here)
-    capturer.start_capture(Some(source.unwrap().clone()), callback);
-
-    // Poll until captured or stopped
-    loop {
-        // Check for stop signal (non-blocking)
-        match stop_rx.recv_timeout(std::time::Duration::from_millis(THUMBNAIL_POLL_INTERVAL_MS)) {
-            Ok(()) => {
-                // Stop signal received
-                tracing::debug!("Stop signal received for screen {}", source_id);
-                break;
-            }
-            Err(mpsc::RecvTimeoutError::Timeout) => {
-                // Timeout - request another frame
-                capturer.capture_frame();
-            }
-            Err(mpsc::RecvTimeoutError::Disconnected) => {
-                // Channel closed
-                break;
-            }
-        }
-
-        // Check if we've captured the thumbnail
-        if captured.load(std::sync::atomic::Ordering::SeqCst) {
-            break;
+            results.lock().unwrap().push((job.idx, thumbnail));
+            captured.store(true, std::sync::atomic::Ordering::SeqCst);
         }
     }
 }
@@ -565,6 +933,40 @@ fn create_thumbnail_from_rgb(rgb_data: &[u8], width: u32, height: u32) -> Option
     Some(BASE64.encode(&jpeg_bytes))
 }
 
+/// Encode a still on its own thread, so capture doesn't stall on the JPEG
+fn encode_still(frame: Option<I420Buffer>, event_proxy: Option<EventProxy>) {
+    let Some(proxy) = event_proxy else {
+        return;
+    };
+    let Some(frame) = frame else {
+        let _ = proxy.send_event(UserEvent::Error {
+            code: ErrorCode::CaptureFailed,
+            message: "No frame captured yet".to_string(),
+        });
+        return;
+    };
+
+    let spawned = std::thread::Builder::new()
+        .name("still-encoder".to_string())
+        .spawn(move || {
+            let event = match crate::relay::encode_i420_jpeg(&frame, STILL_JPEG_QUALITY) {
+                Some(data) => UserEvent::StillCaptured {
+                    width: frame.width(),
+                    height: frame.height(),
+                    data,
+                },
+                None => UserEvent::Error {
+                    code: ErrorCode::CaptureFailed,
+                    message: "Failed to encode still".to_string(),
+                },
+            };
+            let _ = proxy.send_event(event);
+        });
+    if let Err(e) = spawned {
+        tracing::error!("Failed to spawn still encoder: {}", e);
+    }
+}
+
 /// Restart capture after permanent errors
 ///
 /// Based on Hopp's restart_stream approach:
@@ -576,10 +978,11 @@ fn create_thumbnail_from_rgb(rgb_data: &[u8], width: u32, height: u32) -> Option
 fn restart_capture<F>(
     source_id: u64,
     capturer: &Arc<Mutex<DesktopCapturer>>,
-    event_proxy: &Option<EventLoopProxy<UserEvent>>,
+    event_proxy: &Option<EventProxy>,
     restart_state: &CaptureRestartState,
+    policy: &RestartPolicy,
     create_callback: F,
-) -> Result<(), StreamCaptureError>
+) -> Result<(), RestartFailure>
 where
     F: Fn() -> Box<dyn FnMut(Result<DesktopFrame, CaptureError>) + Send + 'static>,
 {
@@ -592,19 +995,29 @@ where
     tracing::warn!(
         source_id = source_id,
         restart_attempt = current_restart,
-        max_restarts = MAX_RESTART_ATTEMPTS,
+        max_restarts = policy.max_restart_attempts,
         "=== STARTING CAPTURE RESTART PROCEDURE ==="
     );
 
+    if let Some(proxy) = event_proxy {
+        let _ = proxy.send_event(UserEvent::CaptureRestarting {
+            attempt: current_restart as u32,
+            max: policy.max_restart_attempts as u32,
+        });
+    }
+
     // Sleep to let system stabilize (following Hopp's pattern)
-    std::thread::sleep(std::time::Duration::from_millis(RESTART_DELAY_MS));
+    std::thread::sleep(std::time::Duration::from_millis(policy.restart_delay_ms));
 
     // Reset failure counters for new attempt
     *restart_state.failures.lock() = 0;
     *restart_state.temp_error_count.lock() = 0;
 
-    // Retry start_capture up to MAX_RESTART_ATTEMPTS times (following Hopp's pattern)
-    for retry_num in 0..MAX_RESTART_ATTEMPTS {
+    // Sources seen on the last enumeration, if ours wasn't among them
+    let mut remaining = Vec::new();
+
+    // Retry start_capture up to max_restart_attempts times (following Hopp's pattern)
+    for retry_num in 0..policy.max_restart_attempts {
         tracing::warn!(
             source_id = source_id,
             restart_attempt = current_restart,
@@ -650,7 +1063,7 @@ where
             drop(cap);
 
             // Give it a moment to start
-            std::thread::sleep(std::time::Duration::from_millis(RETRY_DELAY_MS));
+            std::thread::sleep(std::time::Duration::from_millis(policy.retry_delay_ms));
 
             tracing::warn!(
                 source_id = source_id,
@@ -665,15 +1078,27 @@ where
                 retry_num = retry_num,
                 "Source not found, will retry"
             );
+            remaining = sources.iter().map(|s| s.id()).collect();
             drop(cap);
 
             // Sleep before retry
-            if retry_num < MAX_RESTART_ATTEMPTS - 1 {
-                std::thread::sleep(std::time::Duration::from_millis(RETRY_DELAY_MS));
+            if retry_num + 1 < policy.max_restart_attempts {
+                std::thread::sleep(std::time::Duration::from_millis(policy.retry_delay_ms));
             }
         }
     }
 
+    // Other sources were listed every time, so ours is gone rather than
+    // the whole capture backend being unavailable
+    if !remaining.is_empty() {
+        tracing::error!(
+            source_id = source_id,
+            remaining = ?remaining,
+            "Source disappeared during restart"
+        );
+        return Err(RestartFailure::SourceLost { remaining });
+    }
+
     // All retries failed
     tracing::error!(
         source_id = source_id,
@@ -683,24 +1108,97 @@ where
 
     if let Some(proxy) = event_proxy {
         let _ = proxy.send_event(UserEvent::Error {
-            code: "restart_failed".to_string(),
+            code: ErrorCode::RestartFailed,
             message: format!("Failed to restart capture for source {}", source_id),
         });
     }
 
-    Err(StreamCaptureError::CaptureFailed(
-        "Failed to restart after all retries".to_string(),
-    ))
+    Err(RestartFailure::Failed)
+}
+
+/// Log a capture thread panic and have `Application` end the share
+///
+/// Sent from the dying thread itself, so `Application` can't yet see it as
+/// exited; `CapturePanicked` tears the share down regardless.
+fn report_capture_panic(
+    source_id: u64,
+    panic: &(dyn std::any::Any + Send),
+    event_proxy: Option<EventProxy>,
+) {
+    let reason = panic
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string());
+    tracing::error!(source_id = source_id, "Capture thread panicked: {}", reason);
+
+    if let Some(proxy) = event_proxy {
+        let _ = proxy.send_event(UserEvent::CapturePanicked {
+            message: format!("Screen capture crashed: {}", reason),
+        });
+    }
+}
+
+/// Numeric id of a source id like "screen:123" or "window:456"
+fn parse_source_id(source_id: &str) -> Result<u64, StreamCaptureError> {
+    source_id
+        .split(':')
+        .nth(1)
+        .and_then(|s| s.parse().ok())
+        .ok_or_else(|| StreamCaptureError::SourceNotFound(source_id.to_string()))
+}
+
+/// Why `restart_capture` couldn't bring capture back
+#[derive(Debug)]
+enum RestartFailure {
+    /// The source is no longer listed (e.g. its display was unplugged);
+    /// `remaining` are the source ids still available
+    SourceLost { remaining: Vec<u64> },
+    /// Capture couldn't be restarted for another reason
+    Failed,
+}
+
+/// Capture a different source with the running capturer
+///
+/// The video source and publication are untouched, so viewers see the new
+/// source on the same track. Returns false if the source isn't listed.
+fn switch_source<F>(
+    capturer: &Arc<Mutex<DesktopCapturer>>,
+    source_id: u64,
+    create_callback: F,
+) -> bool
+where
+    F: Fn() -> Box<dyn FnMut(Result<DesktopFrame, CaptureError>) + Send + 'static>,
+{
+    let mut cap = capturer.lock();
+    let sources = cap.get_source_list();
+    let Some(source) = sources.iter().find(|s| s.id() == source_id) else {
+        tracing::warn!(source_id = source_id, "Source to switch to not found");
+        return false;
+    };
+
+    tracing::info!(
+        source_id = source_id,
+        source_title = source.title(),
+        "Switching capture source"
+    );
+    cap.start_capture(Some(source.clone()), create_callback());
+    true
 }
 
 /// Run the capture loop in a separate thread
+#[allow(clippy::too_many_arguments)]
 fn run_capture_loop(
     source_id: u64,
     target_width: u32,
     target_height: u32,
+    framerate: u32,
+    pixel_format: PixelFormat,
     rx: mpsc::Receiver<StreamMessage>,
     video_source: Option<NativeVideoSource>,
-    event_proxy: Option<EventLoopProxy<UserEvent>>,
+    recorder: Arc<Mutex<Option<Recorder>>>,
+    event_proxy: Option<EventProxy>,
+    policy: RestartPolicy,
 ) {
     tracing::info!(
         "Capture loop started for source {} at {}x{}",
@@ -717,37 +1215,52 @@ fn run_capture_loop(
     let restart_attempts = Arc::new(Mutex::new(0u64));
 
     // FPS counter state
-    let frame_count = Arc::new(Mutex::new(0u64));
-    let last_fps_log = Arc::new(Mutex::new(std::time::Instant::now()));
-
-    // Create reusable VideoFrame with I420Buffer (Hopp pattern)
-    // This avoids allocating a new buffer for each frame
-    // Note: We'll resize on first frame if dimensions don't match
-    let video_frame = Arc::new(StdMutex::new(VideoFrame {
-        rotation: VideoRotation::VideoRotation0,
-        buffer: I420Buffer::new(target_width, target_height),
-        timestamp_us: 0,
-    }));
+    let frame_stats = Arc::new(Mutex::new(FrameStats::new()));
+
+    // Frames are handed to a publisher thread, dropping stale ones when
+    // LiveKit cannot keep up
+    let pipeline = match FramePipeline::new(video_source, pixel_format, recorder) {
+        Ok(pipeline) => Arc::new(Mutex::new(pipeline)),
+        Err(e) => {
+            tracing::error!("Failed to start frame publisher: {}", e);
+            if let Some(proxy) = &event_proxy {
+                let _ = proxy.send_event(UserEvent::Error {
+                    code: ErrorCode::CaptureFailed,
+                    message: format!("Failed to start frame publisher: {}", e),
+                });
+            }
+            return;
+        }
+    };
+    let publisher = pipeline.lock().publisher().clone();
 
     // Track current buffer dimensions to detect when resize is needed
     let buffer_dims = Arc::new(StdMutex::new((target_width, target_height)));
 
+    // Native size of the captured display, unknown until the first frame
+    let capture_dims: Arc<StdMutex<Option<(u32, u32)>>> = Arc::new(StdMutex::new(None));
+
     // Track consecutive temporary errors for debugging
     let temp_error_count = Arc::new(Mutex::new(0u64));
 
+    // Set after a restart until the first good frame confirms recovery
+    let restoring = Arc::new(AtomicBool::new(false));
+
     // Create callback factory - generates callbacks for initial and restart captures
     let create_callback =
         || -> Box<dyn FnMut(Result<DesktopFrame, CaptureError>) + Send + 'static> {
-            let video_source_cb = video_source.clone();
-            let video_frame_cb = video_frame.clone();
+            let pipeline_cb = pipeline.clone();
+            let publisher_cb = publisher.clone();
             let buffer_dims_cb = buffer_dims.clone();
+            let capture_dims_cb = capture_dims.clone();
             let failures_cb = failures.clone();
             let should_stop_cb = should_stop.clone();
             let needs_restart_cb = needs_restart.clone();
             let restart_attempts_cb = restart_attempts.clone();
-            let frame_count_cb = frame_count.clone();
-            let last_fps_log_cb = last_fps_log.clone();
+            let frame_stats_cb = frame_stats.clone();
             let temp_error_count_cb = temp_error_count.clone();
+            let event_proxy_cb = event_proxy.clone();
+            let restoring_cb = restoring.clone();
 
             Box::new(move |result: Result<DesktopFrame, CaptureError>| {
                 if *should_stop_cb.lock() {
@@ -760,6 +1273,13 @@ fn run_capture_loop(
                         // Reset failure counts on success
                         *failures_cb.lock() = 0;
                         *temp_error_count_cb.lock() = 0;
+
+                        if restoring_cb.swap(false, Ordering::SeqCst) {
+                            tracing::info!(source_id = source_id, "Capture restored after restart");
+                            if let Some(proxy) = &event_proxy_cb {
+                                let _ = proxy.send_event(UserEvent::CaptureRestored);
+                            }
+                        }
                         frame
                     }
                     Err(_) => {
@@ -771,11 +1291,11 @@ fn run_capture_loop(
                         tracing::error!(
                             source_id = source_id,
                             failure_count = current_fails,
-                            max_failures = MAX_FAILURES,
+                            max_failures = policy.max_failures,
                             "Capture error - display may be unavailable or went to sleep"
                         );
 
-                        if current_fails >= MAX_FAILURES {
+                        if current_fails >= policy.max_failures {
                             let restart_count = *restart_attempts_cb.lock();
                             tracing::error!(
                                 source_id = source_id,
@@ -785,11 +1305,11 @@ fn run_capture_loop(
                             );
 
                             // Check if we've exhausted restart attempts
-                            if restart_count >= MAX_RESTART_ATTEMPTS {
+                            if restart_count >= policy.max_restart_attempts {
                                 tracing::error!(
                                     source_id = source_id,
                                     restart_attempts = restart_count,
-                                    max_restarts = MAX_RESTART_ATTEMPTS,
+                                    max_restarts = policy.max_restart_attempts,
                                     "Exhausted all restart attempts - stopping capture permanently"
                                 );
                                 *should_stop_cb.lock() = true;
@@ -818,74 +1338,60 @@ fn run_capture_loop(
                     frame_stride
                 );
 
-                // Lock the reusable frame buffer and convert ABGR to I420 in-place
-                // This follows the Hopp pattern for zero-allocation frame capture
-                let mut framebuffer = video_frame_cb.lock().unwrap();
-
-                // Check if we need to resize the buffer (first frame or resolution change)
+                // Check if we need to resize the buffer (first frame, resolution
+                // change, or a new height cap)
                 // Note: frame_width/height are i32 from libwebrtc, convert to u32
                 let frame_w = frame_width as u32;
                 let frame_h = frame_height as u32;
+                {
+                    // The display itself changed size (e.g. a projector was
+                    // plugged in), so normalized coordinates map differently
+                    let mut dims = capture_dims_cb.lock().unwrap();
+                    if *dims != Some((frame_w, frame_h)) {
+                        *dims = Some((frame_w, frame_h));
+                        if let Some(proxy) = &event_proxy_cb {
+                            let _ = proxy.send_event(UserEvent::CaptureResolutionChanged {
+                                width: frame_w,
+                                height: frame_h,
+                            });
+                        }
+                    }
+                }
+                let (out_w, out_h) = pipeline_cb.lock().output_size(frame_w, frame_h);
                 {
                     let mut dims = buffer_dims_cb.lock().unwrap();
-                    if dims.0 != frame_w || dims.1 != frame_h {
+                    if dims.0 != out_w || dims.1 != out_h {
                         tracing::info!(
                             "Resizing buffer from {}x{} to {}x{}",
                             dims.0,
                             dims.1,
-                            frame_w,
-                            frame_h
+                            out_w,
+                            out_h
                         );
-                        framebuffer.buffer = I420Buffer::new(frame_w, frame_h);
-                        *dims = (frame_w, frame_h);
+                        *dims = (out_w, out_h);
+
+                        if let Some(proxy) = &event_proxy_cb {
+                            let _ = proxy.send_event(UserEvent::PublishedResolutionChanged {
+                                width: out_w,
+                                height: out_h,
+                            });
+                        }
                     }
                 }
 
-                let buffer = &mut framebuffer.buffer;
-
-                // Get mutable access to Y, U, V planes
-                let (stride_y, stride_u, stride_v) = buffer.strides();
-                let (data_y, data_u, data_v) = buffer.data_mut();
-
-                // Convert ABGR to I420 (same as Hopp)
-                // Note: DesktopCapturer provides ABGR format on most platforms
-                yuv_helper::abgr_to_i420(
-                    frame_data,
-                    frame_stride,
-                    data_y,
-                    stride_y,
-                    data_u,
-                    stride_u,
-                    data_v,
-                    stride_v,
-                    frame_width,
-                    frame_height,
+                // Convert, record and queue the frame for LiveKit
+                pipeline_cb
+                    .lock()
+                    .push(frame_data, frame_stride, frame_w, frame_h);
+
+                // FPS counter - log and report every second
+                let mut stats = frame_stats_cb.lock();
+                stats.frame();
+                stats.report_if_due(
+                    &publisher_cb,
+                    *restart_attempts_cb.lock(),
+                    event_proxy_cb.as_ref(),
                 );
-
-                // Update timestamp
-                framebuffer.timestamp_us = std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_micros() as i64;
-
-                // Publish frame to LiveKit (pass reference, not ownership)
-                if let Some(source) = video_source_cb.lock().as_ref() {
-                    source.capture_frame(&*framebuffer);
-                }
-
-                // FPS counter - log every second
-                {
-                    let mut count = frame_count_cb.lock();
-                    *count += 1;
-                    let mut last_log = last_fps_log_cb.lock();
-                    let elapsed = last_log.elapsed();
-                    if elapsed >= std::time::Duration::from_secs(1) {
-                        let fps = *count as f64 / elapsed.as_secs_f64();
-                        tracing::info!("Screen capture FPS: {:.1}", fps);
-                        *count = 0;
-                        *last_log = std::time::Instant::now();
-                    }
-                }
             })
         };
 
@@ -914,7 +1420,7 @@ fn run_capture_loop(
         );
         if let Some(proxy) = &event_proxy {
             let _ = proxy.send_event(UserEvent::Error {
-                code: "capture_failed".to_string(),
+                code: ErrorCode::CaptureFailed,
                 message: "Failed to create DesktopCapturer".to_string(),
             });
         }
@@ -970,7 +1476,7 @@ fn run_capture_loop(
             );
             if let Some(proxy) = &event_proxy {
                 let _ = proxy.send_event(UserEvent::Error {
-                    code: "source_not_found".to_string(),
+                    code: ErrorCode::SourceNotFound,
                     message: format!("Source {} not found", source_id),
                 });
             }
@@ -979,32 +1485,81 @@ fn run_capture_loop(
     }
 
     // Capture loop
+    // Source being captured; changes if it's lost and another takes its place
+    let mut current_source = source_id;
     let loop_start = std::time::Instant::now();
     let mut frame_requests: u64 = 0;
+    let mut paused = false;
+    let mut last_paused_publish: Option<std::time::Instant> = None;
+    let mut capture_interval = frame_interval(framerate);
     tracing::info!(source_id = source_id, "Entering capture loop");
 
     loop {
         // Check for stop signal
-        match rx.recv_timeout(std::time::Duration::from_millis(FRAME_CAPTURE_INTERVAL_MS)) {
-            Ok(StreamMessage::Stop) => {
+        match next_loop_event(&rx, capture_interval) {
+            LoopEvent::Exit(reason) => {
                 tracing::info!(
                     source_id = source_id,
                     frame_requests = frame_requests,
                     loop_duration_secs = loop_start.elapsed().as_secs_f64(),
-                    "Received stop signal"
+                    "{}",
+                    reason
                 );
                 break;
             }
-            Ok(StreamMessage::Failed) => {
-                tracing::error!(
+            LoopEvent::Message(StreamMessage::Stop | StreamMessage::Failed) => {
+                unreachable!("next_loop_event turns these into Exit")
+            }
+            LoopEvent::Message(StreamMessage::SetPaused(value)) => {
+                paused = value;
+                last_paused_publish = None;
+            }
+            LoopEvent::Message(StreamMessage::CaptureStill) => {
+                encode_still(pipeline.lock().still(), event_proxy.clone());
+            }
+            LoopEvent::Message(StreamMessage::SwitchSource(new_source)) => {
+                let event = if switch_source(&capturer, new_source, create_callback) {
+                    current_source = new_source;
+                    *failures.lock() = 0;
+                    *temp_error_count.lock() = 0;
+                    UserEvent::CaptureSourceChanged {
+                        source_id: format!("screen:{}", new_source),
+                    }
+                } else {
+                    UserEvent::Error {
+                        code: ErrorCode::SourceNotFound,
+                        message: format!("Source screen:{} not found", new_source),
+                    }
+                };
+                if let Some(proxy) = &event_proxy {
+                    let _ = proxy.send_event(event);
+                }
+            }
+            LoopEvent::Message(StreamMessage::SetMaxHeight(height)) => {
+                tracing::info!(
                     source_id = source_id,
-                    frame_requests = frame_requests,
-                    loop_duration_secs = loop_start.elapsed().as_secs_f64(),
-                    "Stream failed"
+                    max_height = ?height,
+                    "Published resolution cap changed"
                 );
-                break;
+                pipeline.lock().set_max_height(height);
             }
-            Err(mpsc::RecvTimeoutError::Timeout) => {
+            LoopEvent::Message(StreamMessage::SetFramerate(framerate)) => {
+                tracing::info!(
+                    source_id = source_id,
+                    framerate = framerate,
+                    "Capture framerate changed"
+                );
+                capture_interval = frame_interval(framerate);
+            }
+            LoopEvent::Tick => {
+                // Also reported from here, so stats keep coming (at 0 fps)
+                // while capture delivers no frames
+                frame_stats.lock().report_if_due(
+                    &publisher,
+                    *restart_attempts.lock(),
+                    event_proxy.as_ref(),
+                );
+
                 // Check if restart is needed
                 if *needs_restart.lock() {
                     tracing::warn!(
@@ -1022,10 +1577,11 @@ fn run_capture_loop(
                     };
 
                     match restart_capture(
-                        source_id,
+                        current_source,
                         &capturer,
                         &event_proxy,
                         &restart_state,
+                        &policy,
                         create_callback,
                     ) {
                         Ok(_) => {
@@ -1033,15 +1589,42 @@ fn run_capture_loop(
                             // Note: capturer is already updated in restart_capture
                             // Clear restart flag
                             *needs_restart.lock() = false;
+                            restoring.store(true, Ordering::SeqCst);
                             // Reset frame counter for this session
                             frame_requests = 0;
                         }
-                        Err(e) => {
+                        Err(RestartFailure::SourceLost { remaining }) => {
+                            // With one display left there is nothing to choose
+                            let switched_to = match remaining.as_slice() {
+                                [only] if switch_source(&capturer, *only, create_callback) => {
+                                    Some(*only)
+                                }
+                                _ => None,
+                            };
+                            if let Some(proxy) = &event_proxy {
+                                let _ = proxy.send_event(UserEvent::CaptureSourceLost {
+                                    source_id: format!("screen:{}", current_source),
+                                    switched_to: switched_to.map(|id| format!("screen:{}", id)),
+                                });
+                            }
+                            let Some(new_source) = switched_to else {
+                                break;
+                            };
+
+                            current_source = new_source;
+                            *needs_restart.lock() = false;
+                            *restart_attempts.lock() = 0;
+                            restoring.store(true, Ordering::SeqCst);
+                            frame_requests = 0;
+                        }
+                        Err(RestartFailure::Failed) => {
                             tracing::error!(
-                                source_id = source_id,
-                                error = ?e,
+                                source_id = current_source,
                                 "Restart failed - stopping capture"
                             );
+                            if let Some(proxy) = &event_proxy {
+                                let _ = proxy.send_event(UserEvent::CaptureGivingUp);
+                            }
                             break;
                         }
                     }
@@ -1056,20 +1639,29 @@ fn run_capture_loop(
                         failure_count = *failures.lock(),
                         "Breaking capture loop due to should_stop flag"
                     );
+                    if let Some(proxy) = &event_proxy {
+                        let _ = proxy.send_event(UserEvent::CaptureGivingUp);
+                    }
                     break;
                 }
+
+                // While paused, nothing new is grabbed from the screen. The last
+                // frame is re-sent now and then so the track keeps producing
+                // (late subscribers get a picture, viewers see a still image).
+                if paused {
+                    let due = last_paused_publish.is_none_or(|at| {
+                        at.elapsed() >= std::time::Duration::from_millis(PAUSED_FRAME_INTERVAL_MS)
+                    });
+                    if due {
+                        publisher.republish_last();
+                        last_paused_publish = Some(std::time::Instant::now());
+                    }
+                    continue;
+                }
+
                 frame_requests += 1;
                 capturer.lock().capture_frame();
             }
-            Err(mpsc::RecvTimeoutError::Disconnected) => {
-                tracing::info!(
-                    source_id = source_id,
-                    frame_requests = frame_requests,
-                    loop_duration_secs = loop_start.elapsed().as_secs_f64(),
-                    "Channel disconnected"
-                );
-                break;
-            }
         }
     }
 
@@ -1080,3 +1672,54 @@ fn run_capture_loop(
         "Capture loop ended"
     );
 }
+
+/// Time between frame grabs for `framerate`, never faster than
+/// `FRAME_CAPTURE_INTERVAL_MS`
+fn frame_interval(framerate: u32) -> std::time::Duration {
+    let requested = std::time::Duration::from_secs(1) / framerate.max(MIN_FRAMERATE);
+    requested.max(std::time::Duration::from_millis(FRAME_CAPTURE_INTERVAL_MS))
+}
+
+/// Output size for a frame under an optional height cap
+///
+/// Keeps the aspect ratio and rounds to even dimensions (I420 subsamples
+/// chroma 2x2). Frames already within the cap are left unscaled.
+pub fn scaled_size(width: u32, height: u32, max_height: Option<u32>) -> (u32, u32) {
+    match max_height {
+        Some(max) if height > max && max > 0 => {
+            let scaled_width = (width as u64 * max as u64 / height as u64) as u32;
+            ((scaled_width & !1).max(2), (max & !1).max(2))
+        }
+        _ => (width, height),
+    }
+}
+
+/// Nearest-neighbor downscale of ABGR pixels into a tightly packed buffer
+///
+/// Cheap enough to run per frame on the capture thread; only used while the
+/// published resolution is reduced, where sharpness matters less than latency.
+fn downscale_abgr(
+    src: &[u8],
+    src_stride: u32,
+    src_width: u32,
+    src_height: u32,
+    dst: &mut Vec<u8>,
+    dst_width: u32,
+    dst_height: u32,
+) {
+    let dst_stride = dst_width as usize * 4;
+    dst.resize(dst_stride * dst_height as usize, 0);
+
+    // Source byte offset of each destination column
+    let columns: Vec<usize> = (0..dst_width as usize)
+        .map(|x| x * src_width as usize / dst_width as usize * 4)
+        .collect();
+
+    for (y, dst_row) in dst.chunks_exact_mut(dst_stride).enumerate() {
+        let src_y = y * src_height as usize / dst_height as usize;
+        let src_row = &src[src_y * src_stride as usize..];
+        for (px, &offset) in dst_row.chunks_exact_mut(4).zip(&columns) {
+            px.copy_from_slice(&src_row[offset..offset + 4]);
+        }
+    }
+}
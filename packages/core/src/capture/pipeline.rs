@@ -0,0 +1,168 @@
+//! From captured pixels to published, recorded and still frames
+//!
+//! Each captured ABGR frame is downscaled when the published resolution is
+//! capped, converted into a recycled publisher buffer and handed to the
+//! publisher. The recorder and stills always get the capture resolution:
+//! while the published frame is not a full-resolution I420 frame, an I420
+//! copy at capture resolution is kept next to it. A height cap that changes
+//! mid-share therefore neither freezes the recording, whose size is fixed
+//! when it starts, nor shrinks stills.
+
+use std::sync::Arc;
+
+use livekit::webrtc::{
+    native::yuv_helper,
+    prelude::{I420Buffer, VideoBuffer},
+    video_source::native::NativeVideoSource,
+};
+use parking_lot::Mutex;
+
+use super::publisher::{copy_i420, FrameBuffer, FramePublisher};
+use super::{convert, downscale_abgr, scaled_size, PixelFormat};
+use crate::clock::FrameClock;
+use crate::recording::Recorder;
+
+/// Converts and distributes the frames of one capture session
+pub struct FramePipeline {
+    publisher: Arc<FramePublisher>,
+    clock: Arc<FrameClock>,
+    pixel_format: PixelFormat,
+    recorder: Arc<Mutex<Option<Recorder>>>,
+    /// Published height cap, adjusted at runtime under network pressure
+    max_height: Option<u32>,
+    /// Downscaled ABGR pixels, reused across frames
+    scaled: Vec<u8>,
+    /// Newest frame at capture resolution, while the published one isn't
+    full_frame: Option<I420Buffer>,
+}
+
+impl FramePipeline {
+    /// Start publishing to `video_source` (nothing is published while it is
+    /// None) and recording into whatever `recorder` holds
+    pub fn new(
+        video_source: Arc<Mutex<Option<NativeVideoSource>>>,
+        pixel_format: PixelFormat,
+        recorder: Arc<Mutex<Option<Recorder>>>,
+    ) -> std::io::Result<Self> {
+        let clock = Arc::new(FrameClock::micros());
+        let publisher = FramePublisher::start(video_source, clock.clone())?;
+        Ok(Self {
+            publisher: Arc::new(publisher),
+            clock,
+            pixel_format,
+            recorder,
+            max_height: None,
+            scaled: Vec::new(),
+            full_frame: None,
+        })
+    }
+
+    pub(super) fn publisher(&self) -> &Arc<FramePublisher> {
+        &self.publisher
+    }
+
+    /// Cap the published height; frames taller than this are downscaled
+    pub fn set_max_height(&mut self, max_height: Option<u32>) {
+        self.max_height = max_height;
+    }
+
+    /// Size a frame captured at `width`x`height` is published at
+    pub fn output_size(&self, width: u32, height: u32) -> (u32, u32) {
+        scaled_size(width, height, self.max_height)
+    }
+
+    /// Publish one captured frame, and record it while recording
+    pub fn push(&mut self, src: &[u8], src_stride: u32, width: u32, height: u32) {
+        let (out_w, out_h) = self.output_size(width, height);
+        let downscaled = (out_w, out_h) != (width, height);
+        let recorder = self.recorder.clone();
+        let mut recorder = recorder.lock();
+
+        // An unscaled I420 published frame doubles as the full-resolution one
+        let keep_full_frame =
+            downscaled || (recorder.is_some() && self.pixel_format == PixelFormat::Nv12);
+        self.full_frame = if keep_full_frame {
+            let mut buffer = match self.full_frame.take() {
+                Some(buffer) if (buffer.width(), buffer.height()) == (width, height) => buffer,
+                _ => I420Buffer::new(width, height),
+            };
+            abgr_to_i420(src, src_stride, &mut buffer);
+            Some(buffer)
+        } else {
+            None
+        };
+
+        // Downscale first when publishing below the capture resolution
+        let (src, src_stride) = if downscaled {
+            downscale_abgr(
+                src,
+                src_stride,
+                width,
+                height,
+                &mut self.scaled,
+                out_w,
+                out_h,
+            );
+            (&self.scaled[..], out_w * 4)
+        } else {
+            (src, src_stride)
+        };
+
+        // Convert into a recycled buffer (Hopp pattern, no per-frame allocation)
+        let mut frame = self.publisher.frame(out_w, out_h, self.pixel_format);
+        match &mut frame.buffer {
+            FrameBuffer::I420(buffer) => abgr_to_i420(src, src_stride, buffer),
+            FrameBuffer::Nv12(buffer) => {
+                let (stride_y, stride_uv) = buffer.strides();
+                let (data_y, data_uv) = buffer.data_mut();
+                convert::abgr_to_nv12(
+                    src, src_stride, data_y, stride_y, data_uv, stride_uv, out_w, out_h,
+                );
+            }
+        }
+        frame.timestamp_us = self.clock.next() as i64;
+
+        // Tap the frame for local recording, then queue it for LiveKit
+        if let Some(recorder) = recorder.as_mut() {
+            match (&self.full_frame, &frame.buffer) {
+                (Some(full_frame), _) => recorder.push_frame(full_frame),
+                (None, FrameBuffer::I420(buffer)) => recorder.push_frame(buffer),
+                // Not reached: NV12 frames always keep a full-resolution copy
+                // while recording
+                (None, FrameBuffer::Nv12(_)) => {}
+            }
+        }
+        self.publisher.submit(frame);
+    }
+
+    /// Copy of the newest frame at capture resolution, for stills
+    ///
+    /// None before the first frame.
+    pub fn still(&self) -> Option<I420Buffer> {
+        match &self.full_frame {
+            Some(frame) => Some(copy_i420(frame)),
+            None => self.publisher.latest(),
+        }
+    }
+}
+
+/// Convert ABGR pixels covering the whole of `dst`
+///
+/// DesktopCapturer provides ABGR on most platforms (same as Hopp).
+fn abgr_to_i420(src: &[u8], src_stride: u32, dst: &mut I420Buffer) {
+    let (width, height) = (dst.width(), dst.height());
+    let (stride_y, stride_u, stride_v) = dst.strides();
+    let (data_y, data_u, data_v) = dst.data_mut();
+    yuv_helper::abgr_to_i420(
+        src,
+        src_stride,
+        data_y,
+        stride_y,
+        data_u,
+        stride_u,
+        data_v,
+        stride_v,
+        width as i32,
+        height as i32,
+    );
+}
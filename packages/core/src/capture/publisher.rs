@@ -0,0 +1,232 @@
+//! Frame hand-off between capture and publishing
+//!
+//! The capture callback converts each frame and drops it into a single-slot
+//! mailbox; a publisher thread feeds the newest frame to the LiveKit video
+//! source. When the encoder or network falls behind, a frame still waiting
+//! in the slot is replaced instead of queued, so viewers always get the
+//! freshest picture and capture never waits on `capture_frame`.
+//!
+//! Buffers are recycled between the two sides, so steady state allocates
+//! nothing. Frames carry I420 or NV12 pixels, per the share's `PixelFormat`.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex as StdMutex};
+use std::thread::JoinHandle;
+
+use livekit::webrtc::{
+    prelude::{I420Buffer, NV12Buffer, VideoBuffer, VideoFrame, VideoRotation},
+    video_source::native::NativeVideoSource,
+};
+use parking_lot::Mutex;
+
+use super::PixelFormat;
+use crate::clock::FrameClock;
+
+/// Pixels of one converted frame
+pub(super) enum FrameBuffer {
+    I420(I420Buffer),
+    Nv12(NV12Buffer),
+}
+
+impl FrameBuffer {
+    fn new(format: PixelFormat, width: u32, height: u32) -> Self {
+        match format {
+            PixelFormat::I420 => Self::I420(I420Buffer::new(width, height)),
+            PixelFormat::Nv12 => Self::Nv12(NV12Buffer::new(width, height)),
+        }
+    }
+
+    fn format(&self) -> PixelFormat {
+        match self {
+            Self::I420(_) => PixelFormat::I420,
+            Self::Nv12(_) => PixelFormat::Nv12,
+        }
+    }
+
+    fn size(&self) -> (u32, u32) {
+        let buffer: &dyn VideoBuffer = self.as_ref();
+        (buffer.width(), buffer.height())
+    }
+
+    /// Copy as I420, for the recorder and stills
+    pub(super) fn to_i420(&self) -> I420Buffer {
+        match self {
+            Self::I420(buffer) => copy_i420(buffer),
+            Self::Nv12(buffer) => buffer.to_i420(),
+        }
+    }
+}
+
+impl AsRef<dyn VideoBuffer> for FrameBuffer {
+    fn as_ref(&self) -> &(dyn VideoBuffer + 'static) {
+        match self {
+            Self::I420(buffer) => buffer,
+            Self::Nv12(buffer) => buffer,
+        }
+    }
+}
+
+/// Publishes captured frames on its own thread. Stops when dropped.
+pub(super) struct FramePublisher {
+    shared: Arc<Shared>,
+    thread: Option<JoinHandle<()>>,
+}
+
+struct Shared {
+    state: StdMutex<State>,
+    wake: Condvar,
+    /// Frames replaced in the slot before they were published
+    dropped: AtomicU64,
+}
+
+#[derive(Default)]
+struct State {
+    /// Newest frame not yet published
+    pending: Option<VideoFrame<FrameBuffer>>,
+    /// Last published frame, re-sent on `republish_last`
+    last: Option<VideoFrame<FrameBuffer>>,
+    /// Buffer free for the next capture
+    spare: Option<VideoFrame<FrameBuffer>>,
+    republish: bool,
+    closed: bool,
+}
+
+impl FramePublisher {
+    pub(super) fn start(
+        video_source: Arc<Mutex<Option<NativeVideoSource>>>,
+        clock: Arc<FrameClock>,
+    ) -> std::io::Result<Self> {
+        let shared = Arc::new(Shared {
+            state: StdMutex::new(State::default()),
+            wake: Condvar::new(),
+            dropped: AtomicU64::new(0),
+        });
+
+        let thread = {
+            let shared = shared.clone();
+            std::thread::Builder::new()
+                .name("frame-publisher".to_string())
+                .spawn(move || publish_frames(&shared, &video_source, &clock))?
+        };
+
+        Ok(Self {
+            shared,
+            thread: Some(thread),
+        })
+    }
+
+    /// A frame to convert the next capture into, recycled when the size and
+    /// format match
+    pub(super) fn frame(
+        &self,
+        width: u32,
+        height: u32,
+        format: PixelFormat,
+    ) -> VideoFrame<FrameBuffer> {
+        let spare = self.shared.state.lock().unwrap().spare.take();
+        match spare {
+            Some(frame)
+                if frame.buffer.format() == format && frame.buffer.size() == (width, height) =>
+            {
+                frame
+            }
+            _ => VideoFrame {
+                rotation: VideoRotation::VideoRotation0,
+                buffer: FrameBuffer::new(format, width, height),
+                timestamp_us: 0,
+            },
+        }
+    }
+
+    /// Hand a frame to the publisher, replacing one still waiting
+    pub(super) fn submit(&self, frame: VideoFrame<FrameBuffer>) {
+        let mut state = self.shared.state.lock().unwrap();
+        if let Some(stale) = state.pending.replace(frame) {
+            self.shared.dropped.fetch_add(1, Ordering::Relaxed);
+            state.spare = Some(stale);
+        }
+        self.shared.wake.notify_one();
+    }
+
+    /// Send the last published frame again, with a fresh timestamp
+    pub(super) fn republish_last(&self) {
+        self.shared.state.lock().unwrap().republish = true;
+        self.shared.wake.notify_one();
+    }
+
+    /// Copy of the newest frame, queued or already published
+    ///
+    /// None before the first capture, or for the moment a frozen frame is
+    /// being re-sent.
+    pub(super) fn latest(&self) -> Option<I420Buffer> {
+        let state = self.shared.state.lock().unwrap();
+        let frame = state.pending.as_ref().or(state.last.as_ref())?;
+        Some(frame.buffer.to_i420())
+    }
+
+    /// Frames dropped since the previous call
+    pub(super) fn take_dropped(&self) -> u64 {
+        self.shared.dropped.swap(0, Ordering::Relaxed)
+    }
+}
+
+impl Drop for FramePublisher {
+    fn drop(&mut self) {
+        self.shared.state.lock().unwrap().closed = true;
+        self.shared.wake.notify_one();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Deep copy, so the original can keep being recycled
+pub(super) fn copy_i420(src: &I420Buffer) -> I420Buffer {
+    let mut dst = I420Buffer::new(src.width(), src.height());
+    let (src_y, src_u, src_v) = src.data();
+    let (dst_y, dst_u, dst_v) = dst.data_mut();
+    dst_y.copy_from_slice(src_y);
+    dst_u.copy_from_slice(src_u);
+    dst_v.copy_from_slice(src_v);
+    dst
+}
+
+fn publish_frames(
+    shared: &Shared,
+    video_source: &Mutex<Option<NativeVideoSource>>,
+    clock: &FrameClock,
+) {
+    loop {
+        let (frame, republish) = {
+            let mut state = shared.state.lock().unwrap();
+            while state.pending.is_none() && !state.republish && !state.closed {
+                state = shared.wake.wait(state).unwrap();
+            }
+            if state.closed {
+                return;
+            }
+            let republish = std::mem::take(&mut state.republish);
+            match state.pending.take() {
+                Some(frame) => (Some(frame), false),
+                // Nothing captured yet - there is no frame to freeze on
+                None => (state.last.take(), republish),
+            }
+        };
+
+        // Published without holding the lock so capture can keep submitting
+        let Some(mut frame) = frame else {
+            continue;
+        };
+        if republish {
+            frame.timestamp_us = clock.next() as i64;
+        }
+        if let Some(source) = video_source.lock().as_ref() {
+            source.capture_frame(&frame);
+        }
+
+        let mut state = shared.state.lock().unwrap();
+        if let Some(previous) = state.last.replace(frame) {
+            state.spare = Some(previous);
+        }
+    }
+}
@@ -0,0 +1,63 @@
+//! Monotonic timestamps
+//!
+//! Frame timestamps count from an `Instant` taken when Core starts, so NTP
+//! adjustments or manual clock changes never make them jump backward. Wall
+//! clock time is only used where the protocol carries an absolute time
+//! (`Pong.core_timestamp`, `VideoFrame.wall_timestamp`).
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+static EPOCH: OnceLock<Instant> = OnceLock::new();
+
+/// Fix the clock's epoch (idempotent; otherwise set on first use)
+pub fn start() {
+    EPOCH.get_or_init(Instant::now);
+}
+
+/// Time elapsed since the epoch
+pub fn elapsed() -> Duration {
+    EPOCH.get_or_init(Instant::now).elapsed()
+}
+
+/// Strictly increasing timestamps for one stream of frames
+///
+/// Two frames read within the same clock tick still get distinct,
+/// ordered timestamps.
+#[derive(Debug)]
+pub struct FrameClock {
+    /// Length of one timestamp unit in microseconds
+    unit_us: u64,
+    last: AtomicU64,
+}
+
+impl FrameClock {
+    /// Timestamps in microseconds
+    pub const fn micros() -> Self {
+        Self {
+            unit_us: 1,
+            last: AtomicU64::new(0),
+        }
+    }
+
+    /// Timestamps in milliseconds
+    pub const fn millis() -> Self {
+        Self {
+            unit_us: 1000,
+            last: AtomicU64::new(0),
+        }
+    }
+
+    /// Timestamp for the next frame, later than all previous ones
+    pub fn next(&self) -> u64 {
+        let now = elapsed().as_micros() as u64 / self.unit_us;
+        let previous = self
+            .last
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |last| {
+                Some(now.max(last + 1))
+            })
+            .unwrap_or_default();
+        now.max(previous + 1)
+    }
+}
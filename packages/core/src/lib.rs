@@ -9,24 +9,35 @@
 //! - Application struct holds all components
 //! - Socket IPC between Core and Tauri WebView
 
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use parking_lot::Mutex;
 use tokio::task::JoinHandle;
-use winit::event_loop::EventLoopProxy;
+use winit::event_loop::{EventLoopClosed, EventLoopProxy};
 
 pub mod annotation;
+pub mod api;
 pub mod capture;
+pub mod clock;
+pub mod logging;
 pub mod permissions;
+pub mod recording;
+pub mod relay;
 pub mod room;
 pub mod socket;
+pub mod watchdog;
 
 // Re-export key types
 pub use annotation::{AnnotationStore, Stroke};
-pub use permissions::{PermissionState, PermissionStatus};
+pub use api::CoreHandle;
+pub use permissions::{NeededPermissions, PermissionState, PermissionStatus};
 pub use socket::{CoreSocket, IncomingMessage, OutgoingMessage};
 
+/// How long shutdown waits for the room to close and the socket to flush
+const SHUTDOWN_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(3);
+
 /// All possible events that can be dispatched through the event loop.
 /// This is the central command vocabulary for the Core process.
 ///
@@ -40,8 +51,11 @@ pub enum UserEvent {
     // ═══════════════════════════════════════════════════════════════════════
     // SCREEN CAPTURE
     // ═══════════════════════════════════════════════════════════════════════
-    /// Request list of available screens and windows for capture
-    GetAvailableContent,
+    /// Request list of available screens for capture (window capture is not supported)
+    ///
+    /// Served from the cache when displays haven't changed, unless
+    /// `force_refresh` is set; a rescan always runs in the background.
+    GetAvailableContent { force_refresh: bool },
 
     /// Start screen sharing with the specified source
     StartScreenShare(ScreenShareMessage),
@@ -49,6 +63,37 @@ pub enum UserEvent {
     /// Stop current screen share
     StopScreenShare,
 
+    /// Freeze the screen share without unpublishing the track
+    PauseScreenShare,
+
+    /// Resume a paused screen share
+    ResumeScreenShare,
+
+    /// Share another screen on the same track
+    SwitchScreenShareSource { source_id: String },
+
+    /// The capture thread now captures `source_id` (internal notification)
+    CaptureSourceChanged { source_id: String },
+
+    /// Snapshot the frame currently being shared
+    CaptureStill,
+
+    /// Report the configuration the share is actually running with
+    GetCaptureConfig,
+
+    /// Change the share's framerate without stopping it
+    SetCaptureQuality { framerate: Option<u32> },
+
+    /// A still was encoded (internal notification)
+    StillCaptured {
+        width: u32,
+        height: u32,
+        data: Vec<u8>,
+    },
+
+    /// The capture thread panicked; the share is torn down
+    CapturePanicked { message: String },
+
     /// Screen share state changed (internal notification)
     ScreenShareStateChanged {
         is_sharing: bool,
@@ -59,16 +104,71 @@ pub enum UserEvent {
     /// Note: Window capture is not supported - only screen capture is available.
     AvailableContentReady { screens: Vec<ScreenInfo> },
 
+    /// Native resolution of the captured display changed, or is known for
+    /// the first time once capture starts (internal notification)
+    CaptureResolutionChanged { width: u32, height: u32 },
+
+    /// Resolution of published screen share frames changed (internal notification)
+    PublishedResolutionChanged { width: u32, height: u32 },
+
+    /// Capture stalled (e.g. display asleep) and is being restarted
+    CaptureRestarting { attempt: u32, max: u32 },
+
+    /// Frames are flowing again after a restart
+    CaptureRestored,
+
+    /// Capture could not be restarted - the share has ended
+    CaptureGivingUp,
+
+    /// Capture health, reported about once a second while sharing, also
+    /// when no frames arrive
+    CaptureStats {
+        fps: f64,
+        /// Frames handed to the publisher since the share started
+        frames_sent: u64,
+        /// Frames dropped before publishing since the last report
+        frames_dropped: u64,
+        /// Restarts since the share started (or since its source changed)
+        restart_attempts: u32,
+    },
+
+    /// The captured source disappeared (e.g. monitor unplugged). Capture
+    /// moved to `switched_to` if that was the only display left; otherwise
+    /// the share has ended.
+    CaptureSourceLost {
+        source_id: String,
+        switched_to: Option<String>,
+    },
+
+    // ═══════════════════════════════════════════════════════════════════════
+    // RECORDING
+    // ═══════════════════════════════════════════════════════════════════════
+    /// Start recording captured frames to an MP4 file at `path`
+    StartRecording { path: String },
+
+    /// Stop recording and finalize the file
+    StopRecording,
+
+    /// Recording file finalized (internal notification)
+    RecordingFinished { path: String, error: Option<String> },
+
     // ═══════════════════════════════════════════════════════════════════════
     // ANNOTATIONS (Core Feature)
     // ═══════════════════════════════════════════════════════════════════════
+    /// Local user selected a drawing tool and color
+    SetTool { tool: AnnotationTool, color: Color },
+
     /// Remote participant started drawing a stroke
     StrokeStart {
         stroke_id: String,
         participant_id: String,
         tool: AnnotationTool,
         color: Color,
+        /// Validated width (see `AnnotationTool::stroke_width`)
+        width: f32,
         start_point: Point,
+        /// Share drawn on (the sharer's participant id), if known
+        share_id: Option<String>,
     },
 
     /// Remote participant added points to their stroke
@@ -80,15 +180,34 @@ pub enum UserEvent {
     /// Remote participant completed their stroke
     StrokeComplete { stroke_id: String },
 
+    /// Dispatch remote stroke points batched since the last flush
+    FlushStrokeUpdates,
+
+    /// Send completed local strokes again to peers that didn't acknowledge them
+    ResendUnackedStrokes,
+
     /// Delete a specific stroke (eraser or moderation)
     StrokeDelete { stroke_id: String },
 
     /// Clear all annotations (host/sharer action)
     ClearAllAnnotations,
 
+    /// Strokes drawn before we joined, from the sharer or host
+    AnnotationSnapshot { strokes: Vec<annotation::Stroke> },
+
+    /// Clear one participant's annotations (host moderation)
+    ClearParticipantAnnotations { participant_id: String },
+
     /// Annotation permissions changed
     AnnotationPermissionChanged { enabled: bool },
 
+    /// Annotation permission changed for one participant
+    ParticipantAnnotationPermissionChanged {
+        participant_id: String,
+        enabled: bool,
+        clear_strokes: bool,
+    },
+
     // ═══════════════════════════════════════════════════════════════════════
     // REMOTE CURSORS (Visual feedback only, no input simulation)
     // ═══════════════════════════════════════════════════════════════════════
@@ -109,17 +228,47 @@ pub enum UserEvent {
     // ═══════════════════════════════════════════════════════════════════════
     // LIVEKIT / ROOM EVENTS
     // ═══════════════════════════════════════════════════════════════════════
-    /// Connect to LiveKit room
-    JoinRoom { server_url: String, token: String },
+    /// Connect to LiveKit room. A passphrase enables DataTrack E2EE.
+    JoinRoom {
+        server_url: String,
+        token: Secret,
+        e2ee_passphrase: Option<Secret>,
+        /// Boxed to keep `UserEvent` small
+        initial_media: Box<InitialMedia>,
+        /// Subscribe to remote tracks as they are published
+        auto_subscribe: bool,
+        /// Format remote video is relayed to the WebView in
+        relay_format: FrameFormat,
+    },
 
     /// Leave the current room
     LeaveRoom,
 
+    /// Report everyone currently in the room
+    GetParticipants,
+
+    /// Subscribe to or unsubscribe from one remote track
+    SetTrackSubscribed {
+        participant_id: String,
+        track_id: String,
+        subscribed: bool,
+    },
+
     /// Room connected successfully
-    RoomConnected { room_name: String },
+    RoomConnected {
+        room_name: String,
+        relay_format: FrameFormat,
+    },
+
+    /// Identity of the local participant, known once the room is connected
+    LocalParticipant(ParticipantData),
+
+    /// Room disconnected; `reason` is LiveKit's disconnect reason in
+    /// snake_case (e.g. `participant_removed`, `duplicate_identity`)
+    RoomDisconnected { reason: String },
 
-    /// Room disconnected
-    RoomDisconnected,
+    /// Room connection restored after `ConnectionState::Reconnecting`
+    RoomReconnected,
 
     /// Participant connected to the room
     ParticipantConnected(ParticipantData),
@@ -127,6 +276,9 @@ pub enum UserEvent {
     /// Participant disconnected from the room
     ParticipantDisconnected(ParticipantData),
 
+    /// Participant metadata or attributes (and so possibly role) changed
+    ParticipantMetadataChanged(ParticipantData),
+
     /// Connection state changed
     ConnectionStateChanged(ConnectionState),
 
@@ -136,6 +288,49 @@ pub enum UserEvent {
         payload: Vec<u8>,
     },
 
+    /// Local annotation or cursor action: applied as the local participant
+    /// and published to the room over DataTrack
+    PublishData(socket::DataTrackMessage),
+
+    /// Remote track subscribed
+    TrackSubscribed {
+        participant_id: String,
+        track_id: String,
+        source: TrackSourceType,
+    },
+
+    /// Remote track unsubscribed
+    TrackUnsubscribed {
+        participant_id: String,
+        track_id: String,
+    },
+
+    /// Remote track published, whether or not it is subscribed
+    RemoteTrackPublished {
+        participant_id: String,
+        track_id: String,
+        source: TrackSourceType,
+    },
+
+    /// Remote track unpublished
+    RemoteTrackUnpublished {
+        participant_id: String,
+        track_id: String,
+    },
+
+    /// Active speakers changed (participant_id, audio level 0.0-1.0), loudest first
+    ActiveSpeakersChanged { speakers: Vec<(String, f32)> },
+
+    /// Remote screen share to spotlight changed; None when nobody shares
+    /// (internal notification)
+    FocusedShareChanged {
+        participant_id: Option<String>,
+        track_id: Option<String>,
+    },
+
+    /// Periodic connection statistics while connected
+    ConnectionStatsUpdated(ConnectionStats),
+
     /// Screen share track published
     ScreenSharePublished,
 
@@ -179,8 +374,15 @@ pub enum UserEvent {
     /// Socket client disconnected
     SocketDisconnected,
 
+    /// Health check from the client; answered with a Pong.
+    /// `timestamp` is the client's send time, echoed back for RTT measurement.
+    Ping { timestamp: Option<u64> },
+
+    /// Replace the log filter (`RUST_LOG` syntax) without restarting
+    SetLogLevel { filter: String },
+
     /// Error occurred
-    Error { code: String, message: String },
+    Error { code: ErrorCode, message: String },
 
     // ═══════════════════════════════════════════════════════════════════════
     // PERMISSIONS
@@ -191,6 +393,12 @@ pub enum UserEvent {
     /// Request screen recording permission
     RequestScreenRecordingPermission,
 
+    /// Request microphone permission (prompts on macOS)
+    RequestMicrophonePermission,
+
+    /// Request camera permission (prompts on macOS)
+    RequestCameraPermission,
+
     /// Permission state changed (response to CheckPermissions or RequestScreenRecordingPermission)
     PermissionStateChanged(PermissionState),
 
@@ -201,6 +409,32 @@ pub enum UserEvent {
     Terminate,
 }
 
+/// Dispatches `UserEvent`s to whatever loop drives the `Application`
+///
+/// Normally the winit event loop; headless runs have no display to open one
+/// on, so they drain a plain channel instead (see `Application::run_headless`).
+#[derive(Debug, Clone)]
+pub enum EventProxy {
+    EventLoop(EventLoopProxy<UserEvent>),
+    Channel(std::sync::mpsc::Sender<UserEvent>),
+}
+
+impl EventProxy {
+    /// Queue an event; fails once the receiving loop has exited
+    pub fn send_event(&self, event: UserEvent) -> Result<(), EventLoopClosed<UserEvent>> {
+        match self {
+            EventProxy::EventLoop(proxy) => proxy.send_event(event),
+            EventProxy::Channel(sender) => sender.send(event).map_err(|e| EventLoopClosed(e.0)),
+        }
+    }
+}
+
+impl From<EventLoopProxy<UserEvent>> for EventProxy {
+    fn from(proxy: EventLoopProxy<UserEvent>) -> Self {
+        EventProxy::EventLoop(proxy)
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // SUPPORTING TYPES
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -211,6 +445,9 @@ pub struct ScreenShareMessage {
     pub source_id: String,
     pub source_type: SourceType,
     pub config: CaptureConfig,
+    /// Stop a share in progress first; otherwise starting while sharing
+    /// fails with `already_sharing`
+    pub replace: bool,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
@@ -226,6 +463,29 @@ pub struct CaptureConfig {
     pub height: u32,
     pub framerate: u32,
     pub bitrate: u32,
+    /// Also capture and publish system audio with the screen share
+    /// (Windows and macOS 13+; on Linux the share goes ahead without it and
+    /// `AudioCaptureFailed` is reported)
+    #[serde(default)]
+    pub capture_audio: bool,
+    /// Codec the screen share is encoded with
+    #[serde(default)]
+    pub codec: VideoCodecPreference,
+    /// Also publish a half-resolution, low-framerate layer so the SFU can
+    /// downshift subscribers on weak connections (width/height must be at
+    /// least 480 on the longer side for the layer to be added)
+    #[serde(default = "default_simulcast")]
+    pub simulcast: bool,
+    /// How hard capture tries to recover before the share is given up
+    #[serde(default)]
+    pub restart_policy: capture::RestartPolicy,
+    /// Pixel layout frames are handed to the encoder in
+    #[serde(default)]
+    pub pixel_format: capture::PixelFormat,
+}
+
+fn default_simulcast() -> bool {
+    true
 }
 
 impl Default for CaptureConfig {
@@ -235,6 +495,144 @@ impl Default for CaptureConfig {
             height: 1080,
             framerate: 60,
             bitrate: 6_000_000, // 6 Mbps
+            capture_audio: false,
+            codec: VideoCodecPreference::default(),
+            simulcast: true,
+            restart_policy: capture::RestartPolicy::default(),
+            pixel_format: capture::PixelFormat::default(),
+        }
+    }
+}
+
+/// Media to publish right after joining a room, in the same flow as the
+/// connection, so the client doesn't have to wait for `connected` first
+///
+/// Only the screen share is published so far. Core has no microphone or
+/// camera tracks yet, so a join asking for either is refused with
+/// `RoomJoinFailed` rather than connecting without them.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct InitialMedia {
+    /// Screen to share
+    #[serde(default)]
+    pub screen_source_id: Option<String>,
+    /// Capture settings for the screen share (defaults if omitted)
+    #[serde(default)]
+    pub screen_config: Option<CaptureConfig>,
+    /// Publish the microphone unmuted (refused for now, see above)
+    #[serde(default)]
+    pub microphone: bool,
+    /// Publish the camera (refused for now, see above)
+    #[serde(default)]
+    pub camera: bool,
+}
+
+impl InitialMedia {
+    /// Events that bring up the requested tracks, in publishing order
+    fn events(self) -> Vec<UserEvent> {
+        let mut events = Vec::new();
+        if let Some(source_id) = self.screen_source_id {
+            events.push(UserEvent::StartScreenShare(ScreenShareMessage {
+                source_id,
+                source_type: SourceType::Screen,
+                config: self.screen_config.unwrap_or_default(),
+                replace: false,
+            }));
+        }
+        events
+    }
+}
+
+/// A credential (access token, E2EE passphrase) that must not reach the logs
+///
+/// Derefs to the value; `Debug` prints a placeholder, so messages carrying
+/// one can still be logged with `{:?}`.
+#[derive(Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(transparent)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+}
+
+impl std::fmt::Debug for Secret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("<redacted>")
+    }
+}
+
+impl std::ops::Deref for Secret {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for Secret {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl From<&str> for Secret {
+    fn from(value: &str) -> Self {
+        Self(value.to_string())
+    }
+}
+
+impl PartialEq<&str> for Secret {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+/// Screen share codec requested by the client
+///
+/// H264 decodes in hardware almost everywhere; AV1 holds up best at low
+/// bitrates. Falls back to VP8 if the server rejects the choice.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VideoCodecPreference {
+    Vp8,
+    #[default]
+    Vp9,
+    H264,
+    Av1,
+}
+
+/// What this build of Core supports, announced in `Ready`
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Capabilities {
+    /// Sharing a single window rather than a whole screen
+    pub window_capture: bool,
+    /// Publishing system audio alongside the screen share
+    pub system_audio: bool,
+    /// Recording the shared screen to a local file (needs ffmpeg at runtime)
+    pub recording: bool,
+    /// End-to-end encryption of DataTrack messages
+    pub e2ee: bool,
+    /// Codecs the screen share can be published with
+    pub codecs: Vec<VideoCodecPreference>,
+    /// Formats remote video can be relayed in (`JoinRoom.relay_format`)
+    pub relay_formats: Vec<FrameFormat>,
+}
+
+impl Capabilities {
+    pub fn current() -> Self {
+        Self {
+            window_capture: false,
+            system_audio: cfg!(any(target_os = "windows", target_os = "macos")),
+            recording: true,
+            e2ee: cfg!(feature = "e2ee"),
+            codecs: vec![
+                VideoCodecPreference::Vp8,
+                VideoCodecPreference::Vp9,
+                VideoCodecPreference::H264,
+                VideoCodecPreference::Av1,
+            ],
+            relay_formats: relay::RELAY_FORMATS.to_vec(),
         }
     }
 }
@@ -267,6 +665,37 @@ pub enum AnnotationTool {
     Eraser,
 }
 
+/// Thinnest stroke, as a fraction of the shared screen's width
+pub const MIN_STROKE_WIDTH: f32 = 0.0005;
+
+/// Widest stroke, as a fraction of the shared screen's width
+pub const MAX_STROKE_WIDTH: f32 = 0.05;
+
+impl AnnotationTool {
+    /// Width strokes are drawn at unless the author picks another; a thin
+    /// pen, a wide highlighter
+    pub fn default_width(self) -> f32 {
+        match self {
+            AnnotationTool::Pen => 0.003,
+            AnnotationTool::Highlighter => 0.015,
+            AnnotationTool::Eraser => 0.02,
+        }
+    }
+
+    /// `width` clamped to `MIN_STROKE_WIDTH..=MAX_STROKE_WIDTH`
+    ///
+    /// A missing, zero or non-finite width - e.g. from a peer that predates
+    /// stroke widths - falls back to `default_width`.
+    pub fn stroke_width(self, width: Option<f32>) -> f32 {
+        match width {
+            Some(width) if width.is_finite() && width > 0.0 => {
+                width.clamp(MIN_STROKE_WIDTH, MAX_STROKE_WIDTH)
+            }
+            _ => self.default_width(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
 pub struct Point {
     pub x: f32, // Normalized 0.0-1.0
@@ -279,6 +708,29 @@ fn default_pressure() -> f32 {
     1.0
 }
 
+impl Point {
+    /// The point clamped to the normalized [0, 1] range, or None if any
+    /// component is NaN or infinite
+    ///
+    /// Points come from peers and the WebView; nothing else bounds them.
+    pub fn sanitized(self) -> Option<Self> {
+        Some(Self {
+            x: sanitize_coordinate(self.x)?,
+            y: sanitize_coordinate(self.y)?,
+            pressure: sanitize_coordinate(self.pressure)?,
+        })
+    }
+}
+
+/// Clamp a normalized value to [0, 1], rejecting NaN and infinity
+pub fn sanitize_coordinate(value: f32) -> Option<f32> {
+    value.is_finite().then(|| value.clamp(0.0, 1.0))
+}
+
+/// An sRGB color with straight (non-premultiplied) alpha
+///
+/// Strokes keep whatever color they were drawn with, alpha included, from
+/// the WebView through the DataTrack to every peer's store.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct Color {
     pub r: u8,
@@ -288,6 +740,15 @@ pub struct Color {
 }
 
 impl Color {
+    pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// The same color at another opacity, e.g. a faint highlighter
+    pub const fn with_alpha(self, a: u8) -> Self {
+        Self { a, ..self }
+    }
+
     pub const RED: Color = Color {
         r: 255,
         g: 87,
@@ -325,6 +786,9 @@ impl Color {
         a: 255,
     };
 
+    /// Colors assigned to participants' cursors in join order
+    ///
+    /// Only a default; strokes may use any color.
     pub const PALETTE: [Color; 6] = [
         Self::RED,
         Self::BLUE,
@@ -343,6 +807,36 @@ pub enum CursorStyle {
     Highlighter,
     Eraser,
     Hidden,
+    /// Presenter pointer: a glowing dot with a fading trail, drawing nothing
+    Laser,
+}
+
+impl CursorStyle {
+    /// Point of the cursor shape that marks the reported position, as a
+    /// fraction of the shape's width and height from its top-left corner
+    ///
+    /// A renderer draws the shape at `(x - hx * w, y - hy * h)` so that the
+    /// arrow tip or pen nib, not the shape's corner, sits on `(x, y)`.
+    pub fn hotspot(self) -> (f32, f32) {
+        match self {
+            // Arrow pointing up-left
+            Self::Default => (0.0, 0.0),
+            // Nib at the bottom-left of a slanted pen
+            Self::Pen | Self::Highlighter => (0.0, 1.0),
+            // Shapes centred on the position
+            Self::Eraser | Self::Laser | Self::Hidden => (0.5, 0.5),
+        }
+    }
+}
+
+impl From<AnnotationTool> for CursorStyle {
+    fn from(tool: AnnotationTool) -> Self {
+        match tool {
+            AnnotationTool::Pen => Self::Pen,
+            AnnotationTool::Highlighter => Self::Highlighter,
+            AnnotationTool::Eraser => Self::Eraser,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
@@ -357,8 +851,16 @@ pub enum ConnectionState {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum FrameFormat {
+    /// Smallest on the wire (roughly a tenth of RGBA for screen content),
+    /// but each frame costs Core an RGB conversion and a JPEG encode and
+    /// the WebView a decode. Best when the socket is the bottleneck.
     Jpeg,
+    /// Cheapest for Core - a single conversion - and ready to draw, but
+    /// 4 bytes per pixel. Best when CPU is scarce and the socket is local.
     Rgba,
+    /// Y plane followed by interleaved UV, passed through without RGB
+    /// conversion: 1.5 bytes per pixel, for WebViews that convert to RGB
+    /// on the GPU (e.g. in a WebGL shader).
     Nv12,
 }
 
@@ -368,6 +870,9 @@ pub struct ParticipantData {
     pub name: String,
     pub is_local: bool,
     pub role: ParticipantRole,
+    /// Application metadata set by the server or the participant
+    #[serde(default)]
+    pub metadata: String,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
@@ -377,18 +882,297 @@ pub enum ParticipantRole {
     Participant,
 }
 
+impl ParticipantRole {
+    /// Role assigned by the server, from the `role` participant attribute
+    /// or a `{"role": "host"}` JSON metadata object (the attribute wins)
+    pub fn from_participant(metadata: &str, attributes: &HashMap<String, String>) -> Self {
+        let role = match attributes.get("role") {
+            Some(role) => Some(role.clone()),
+            None => serde_json::from_str::<serde_json::Value>(metadata)
+                .ok()
+                .and_then(|value| value.get("role")?.as_str().map(str::to_string)),
+        };
+
+        match role {
+            Some(role) if role.eq_ignore_ascii_case("host") => Self::Host,
+            _ => Self::Participant,
+        }
+    }
+}
+
+/// Machine-readable reason attached to every error sent to the WebView
+///
+/// Serialized as the snake_case strings clients already match on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// The control socket could not be bound at startup
+    SocketInitFailed,
+    /// Screen recording permission is missing when starting a share
+    PermissionDenied,
+    /// StartScreenShare arrived while sharing, without `replace`
+    AlreadySharing,
+    /// A share-only command (pause, resume, still, switch) arrived while not sharing
+    NotSharing,
+    /// Publishing a track to the room failed
+    PublishFailed,
+    /// The capturer or frame publisher could not be started
+    CaptureFailed,
+    /// System audio capture could not be started
+    AudioCaptureFailed,
+    /// The requested capture source is not among the available sources
+    SourceNotFound,
+    /// Capture stopped and every restart attempt failed
+    RestartFailed,
+    /// SetCaptureQuality carried a framerate out of bounds
+    InvalidCaptureQuality,
+    /// The capture thread panicked; the share has ended
+    CapturePanicked,
+    /// StartRecording arrived while a recording is in progress
+    AlreadyRecording,
+    /// A recording could not be started or finalized
+    RecordingFailed,
+    /// Connecting to the LiveKit room failed
+    RoomJoinFailed,
+    /// The room service's runtime could not be created
+    RoomServiceFailed,
+    /// E2EE was requested from a build without the `e2ee` feature
+    E2eeUnavailable,
+    /// A host-only command was sent by a participant
+    NotHost,
+    /// SetLogLevel carried a filter that doesn't parse
+    InvalidLogFilter,
+    /// SetTrackSubscribed named a participant or track not in the room
+    TrackNotFound,
+}
+
+impl ErrorCode {
+    /// The wire form, e.g. `"capture_failed"`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::SocketInitFailed => "socket_init_failed",
+            Self::PermissionDenied => "permission_denied",
+            Self::AlreadySharing => "already_sharing",
+            Self::NotSharing => "not_sharing",
+            Self::PublishFailed => "publish_failed",
+            Self::CaptureFailed => "capture_failed",
+            Self::AudioCaptureFailed => "audio_capture_failed",
+            Self::SourceNotFound => "source_not_found",
+            Self::RestartFailed => "restart_failed",
+            Self::InvalidCaptureQuality => "invalid_capture_quality",
+            Self::CapturePanicked => "capture_panicked",
+            Self::AlreadyRecording => "already_recording",
+            Self::RecordingFailed => "recording_failed",
+            Self::RoomJoinFailed => "room_join_failed",
+            Self::RoomServiceFailed => "room_service_failed",
+            Self::E2eeUnavailable => "e2ee_unavailable",
+            Self::NotHost => "not_host",
+            Self::InvalidLogFilter => "invalid_log_filter",
+            Self::TrackNotFound => "track_not_found",
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// What a remote track carries, as published by the sender
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrackSourceType {
+    Camera,
+    Microphone,
+    ScreenShare,
+    ScreenShareAudio,
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ActiveSpeaker {
+    pub participant_id: String,
+    /// Audio level 0.0-1.0
+    pub audio_level: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Resolution {
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Network-side health of the local publication
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ConnectionStats {
+    /// Total outbound bitrate (bits/s)
+    pub outbound_bitrate: u64,
+    /// Fraction of packets lost (0.0-1.0) as reported by the receiver
+    pub packet_loss: f64,
+    /// Round-trip time to the server (ms), once measured
+    pub rtt_ms: Option<f64>,
+    /// Frames per second sent for the screen share
+    pub fps: f64,
+    /// Resolution sent for the screen share (if publishing video)
+    pub resolution: Option<Resolution>,
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // REMOTE CURSOR STATE
 // ═══════════════════════════════════════════════════════════════════════════════
 
+/// Time constant of remote cursor smoothing
+///
+/// After one constant a cursor has covered ~63% of the way to its latest
+/// reported position, after three ~95%. Short enough that the cursor
+/// doesn't visibly lag, long enough to hide the gaps between updates on a
+/// lossy link.
+pub const CURSOR_SMOOTHING: Duration = Duration::from_millis(60);
+
+/// Distance (normalized) below which a smoothed cursor snaps to its target
+const CURSOR_SNAP_DISTANCE: f32 = 0.0005;
+
+/// Time between animation frames while a cursor glides or a trail fades
+/// (60 Hz)
+pub const ANIMATION_FRAME_INTERVAL: Duration = Duration::from_micros(16_667);
+
+/// How long a point of a laser pointer's trail takes to fade out
+pub const LASER_TRAIL_DURATION: Duration = Duration::from_millis(300);
+
+/// A past position of a laser pointer, fading with age
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrailPoint {
+    pub x: f32,
+    pub y: f32,
+    pub age: Duration,
+}
+
+impl TrailPoint {
+    /// Opacity to draw the point with, from 1.0 when new to 0.0 when expired
+    pub fn opacity(&self) -> f32 {
+        (1.0 - self.age.as_secs_f32() / LASER_TRAIL_DURATION.as_secs_f32()).max(0.0)
+    }
+}
+
+/// A remote participant's cursor
+///
+/// `x`/`y` are the displayed position and glide toward the last reported
+/// `target_x`/`target_y` as `advance` is called from the render loop.
 #[derive(Debug, Clone)]
 pub struct RemoteCursor {
     pub participant_id: String,
     pub x: f32,
     pub y: f32,
+    pub target_x: f32,
+    pub target_y: f32,
+    pub visible: bool,
+    pub style: CursorStyle,
+    pub color: Color,
+    /// Recent displayed positions while `style` is `Laser`, oldest first
+    pub trail: VecDeque<TrailPoint>,
+}
+
+impl RemoteCursor {
+    /// Record a reported position
+    ///
+    /// A cursor that was hidden reappears at the new position instead of
+    /// gliding in from where it disappeared.
+    pub fn set_target(&mut self, x: f32, y: f32, visible: bool) {
+        self.target_x = x;
+        self.target_y = y;
+        if visible && !self.visible {
+            self.x = x;
+            self.y = y;
+        }
+        self.visible = visible;
+    }
+
+    /// Move the displayed position toward the target by `elapsed` of
+    /// exponential smoothing; true while the cursor is still moving or its
+    /// laser trail is still fading
+    pub fn advance(&mut self, elapsed: Duration) -> bool {
+        for point in &mut self.trail {
+            point.age += elapsed;
+        }
+        while self
+            .trail
+            .front()
+            .is_some_and(|point| point.age >= LASER_TRAIL_DURATION)
+        {
+            self.trail.pop_front();
+        }
+
+        let dx = self.target_x - self.x;
+        let dy = self.target_y - self.y;
+        let moving = !(dx.abs() < CURSOR_SNAP_DISTANCE && dy.abs() < CURSOR_SNAP_DISTANCE);
+        if moving {
+            let alpha = 1.0 - (-elapsed.as_secs_f32() / CURSOR_SMOOTHING.as_secs_f32()).exp();
+            self.x += dx * alpha;
+            self.y += dy * alpha;
+        } else {
+            self.x = self.target_x;
+            self.y = self.target_y;
+        }
+
+        if moving && self.visible && self.style == CursorStyle::Laser {
+            self.trail.push_back(TrailPoint {
+                x: self.x,
+                y: self.y,
+                age: Duration::ZERO,
+            });
+        }
+        moving || !self.trail.is_empty()
+    }
+
+    /// What the overlay needs to draw the cursor
+    pub fn state(&self) -> CursorState {
+        CursorState {
+            participant_id: self.participant_id.clone(),
+            x: self.x,
+            y: self.y,
+            visible: self.visible,
+            style: self.style,
+            hotspot: self.style.hotspot(),
+            color: self.color,
+            trail: self
+                .trail
+                .iter()
+                .map(|point| TrailDot {
+                    x: point.x,
+                    y: point.y,
+                    opacity: point.opacity(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A remote cursor as the WebView overlay draws it
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct CursorState {
+    pub participant_id: String,
+    /// Smoothed position (normalized 0-1)
+    pub x: f32,
+    pub y: f32,
     pub visible: bool,
     pub style: CursorStyle,
+    /// `style.hotspot()`, so the overlay aligns the shape without its own
+    /// copy of the table
+    pub hotspot: (f32, f32),
     pub color: Color,
+    /// Laser pointer trail, oldest first; empty for other styles
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trail: Vec<TrailDot>,
+}
+
+/// A point of a laser pointer's trail as the overlay draws it
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TrailDot {
+    pub x: f32,
+    pub y: f32,
+    /// 1.0 when new, fading to 0.0
+    pub opacity: f32,
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -405,7 +1189,7 @@ pub struct Application {
     // EVENT DISPATCH
     // ═══════════════════════════════════════════════════════════════════════
     /// Proxy to send events to the main event loop from any thread
-    event_loop_proxy: EventLoopProxy<UserEvent>,
+    event_loop_proxy: EventProxy,
 
     // ═══════════════════════════════════════════════════════════════════════
     // SCREEN CAPTURE
@@ -416,6 +1200,34 @@ pub struct Application {
     /// Handle to capturer event forwarding task
     _capturer_events_task: Option<JoinHandle<()>>,
 
+    /// System audio capture published with the screen share (if enabled)
+    system_audio: Option<capture::audio::SystemAudioCapture>,
+
+    /// Steps the published resolution with network conditions
+    adaptive_resolution: capture::adaptive::AdaptiveResolution,
+
+    /// Enumerated sources, readable while a scan holds the capturer
+    source_cache: capture::SourceCache,
+
+    /// Native resolution of the display being captured (if sharing)
+    capture_resolution: Option<Resolution>,
+
+    /// Config the current share was started with (if sharing)
+    capture_config: Option<CaptureConfig>,
+
+    /// Resolution frames are published at after adaptive downscaling (if sharing)
+    published_resolution: Option<Resolution>,
+
+    /// Timestamps for frames relayed to the WebView
+    relay_clock: clock::FrameClock,
+
+    /// Watches for permissions granted outside the app (while a needed one
+    /// is missing)
+    permission_poller: Option<permissions::PermissionPoller>,
+
+    /// Media permissions requested or in use this session
+    needed_permissions: permissions::NeededPermissions,
+
     // ═══════════════════════════════════════════════════════════════════════
     // LIVEKIT
     // ═══════════════════════════════════════════════════════════════════════
@@ -429,15 +1241,39 @@ pub struct Application {
     /// In-memory annotation store
     annotation_store: AnnotationStore,
 
+    /// Remote stroke points waiting for the next batch flush
+    stroke_updates: annotation::StrokeUpdateBatcher,
+
+    /// Completed local strokes not yet acknowledged by every peer
+    stroke_acks: annotation::StrokeAcks,
+
     /// Remote cursor positions (participant_id → cursor state)
     remote_cursors: HashMap<String, RemoteCursor>,
 
+    /// When `animate` last stepped animations, while any are running
+    last_animation_frame: Option<Instant>,
+
+    /// Remote cursors changed since the WebView was last sent them
+    cursors_changed: bool,
+
+    /// Remote screen shares and which one to spotlight
+    share_focus: room::focus::ShareFocus,
+
+    /// Tool the local user draws with, set by the WebView toolbar
+    current_tool: AnnotationTool,
+
+    /// Color the local user draws with
+    current_color: Color,
+
     // ═══════════════════════════════════════════════════════════════════════
     // SOCKET (Communication with Tauri/WebView)
     // ═══════════════════════════════════════════════════════════════════════
     /// Socket server for Tauri communication (shared with AppHandler)
     socket: Arc<Mutex<Option<CoreSocket>>>,
 
+    /// Shuts Core down when no client stays connected (armed by `init_socket`)
+    idle_watchdog: watchdog::IdleWatchdog,
+
     // ═══════════════════════════════════════════════════════════════════════
     // STATE
     // ═══════════════════════════════════════════════════════════════════════
@@ -456,59 +1292,290 @@ pub struct Application {
     /// Current connection state
     connection_state: ConnectionState,
 
-    /// Annotations enabled
-    annotations_enabled: bool,
+    /// Set once `Terminate` has been handled
+    exit_requested: bool,
 }
 
 impl Application {
     /// Create a new Application instance with a shared socket reference
-    pub fn new(
-        event_loop_proxy: EventLoopProxy<UserEvent>,
-        socket: Arc<Mutex<Option<CoreSocket>>>,
-    ) -> Self {
-        let screen_capturer = Arc::new(Mutex::new(capture::Capturer::new()));
+    pub fn new(event_loop_proxy: EventProxy, socket: Arc<Mutex<Option<CoreSocket>>>) -> Self {
+        let mut screen_capturer = capture::Capturer::new();
+        screen_capturer.set_event_loop_proxy(event_loop_proxy.clone());
+        let source_cache = screen_capturer.source_cache();
+        let screen_capturer = Arc::new(Mutex::new(screen_capturer));
+
+        // Invalidate cached sources when displays change
+        capture::display_watch::start();
+        clock::start();
 
         Self {
             event_loop_proxy,
             screen_capturer,
             _capturer_events_task: None,
+            system_audio: None,
+            adaptive_resolution: capture::adaptive::AdaptiveResolution::new(),
+            source_cache,
+            capture_resolution: None,
+            capture_config: None,
+            published_resolution: None,
+            relay_clock: clock::FrameClock::millis(),
+            permission_poller: None,
+            needed_permissions: permissions::NeededPermissions::default(),
             room_service: Arc::new(Mutex::new(None)),
             annotation_store: AnnotationStore::new(),
+            stroke_updates: annotation::StrokeUpdateBatcher::new(),
+            stroke_acks: annotation::StrokeAcks::new(),
             remote_cursors: HashMap::new(),
+            last_animation_frame: None,
+            cursors_changed: false,
+            share_focus: room::focus::ShareFocus::new(),
+            current_tool: AnnotationTool::Pen,
+            current_color: Color::RED,
             socket,
+            idle_watchdog: watchdog::IdleWatchdog::new(watchdog::IdleTimeouts::from_env()),
             is_sharing: false,
             shared_source_id: None,
             local_participant: None,
             participants: HashMap::new(),
             connection_state: ConnectionState::Disconnected,
-            annotations_enabled: true,
+            exit_requested: false,
         }
     }
 
-    /// Initialize the socket server (stores in the shared socket reference)
-    pub async fn init_socket(&mut self, socket_path: &str) -> anyhow::Result<()> {
-        let socket = CoreSocket::new(socket_path, self.event_loop_proxy.clone()).await?;
-        *self.socket.lock() = Some(socket);
-        Ok(())
+    /// Start the socket server (stores in the shared socket reference)
+    ///
+    /// Binding runs as a task on the current tokio runtime, so this can be
+    /// called from the event loop. Failures are reported as a
+    /// `socket_init_failed` error event. Also starts the idle watchdog:
+    /// Core terminates if no client connects (see `watchdog`).
+    pub fn init_socket(&mut self, socket_path: &str) {
+        self.idle_watchdog.start(&self.event_loop_proxy);
+
+        let socket_path = socket_path.to_string();
+        let socket_holder = self.socket.clone();
+        let proxy = self.event_loop_proxy.clone();
+
+        tokio::spawn(async move {
+            match CoreSocket::new(&socket_path, proxy.clone()).await {
+                Ok(socket) => {
+                    tracing::info!("Socket server started on: {}", socket_path);
+                    #[cfg(unix)]
+                    if let Some(file) = socket.socket_file() {
+                        socket::cleanup_on_signal(file.clone(), proxy.clone());
+                    }
+                    *socket_holder.lock() = Some(socket);
+                }
+                Err(e) => {
+                    tracing::error!("Failed to start socket server: {}", e);
+                    let _ = proxy.send_event(UserEvent::Error {
+                        code: ErrorCode::SocketInitFailed,
+                        message: e.to_string(),
+                    });
+                }
+            }
+        });
     }
 
     /// Get the event loop proxy for sending events from other threads
-    pub fn event_loop_proxy(&self) -> EventLoopProxy<UserEvent> {
+    pub fn event_loop_proxy(&self) -> EventProxy {
         self.event_loop_proxy.clone()
     }
 
-    /// Handle UserEvent dispatched through the event loop
-    pub fn handle_user_event(
-        &mut self,
-        event: UserEvent,
-        elwt: &winit::event_loop::ActiveEventLoop,
-    ) {
+    /// Handle for driving this application from Rust instead of the socket
+    pub fn handle(&self) -> CoreHandle {
+        CoreHandle::new(self.event_loop_proxy.clone())
+    }
+
+    /// Annotations currently held, local and remote
+    pub fn annotations(&self) -> &AnnotationStore {
+        &self.annotation_store
+    }
+
+    /// Tool and color the local user currently draws with
+    pub fn current_tool(&self) -> (AnnotationTool, Color) {
+        (self.current_tool, self.current_color)
+    }
+
+    /// Native resolution of the display being captured, once known
+    pub fn capture_resolution(&self) -> Option<Resolution> {
+        self.capture_resolution
+    }
+
+    /// Remote screen share currently in focus
+    pub fn focused_share(&self) -> Option<&room::focus::FocusedShare> {
+        self.share_focus.focused()
+    }
+
+    /// Share annotations are drawn over: our own while sharing, otherwise
+    /// the remote share in focus
+    ///
+    /// Identified by the sharer's participant id, which unlike the track sid
+    /// survives the share being republished.
+    pub fn displayed_share_id(&self) -> Option<&str> {
+        if self.is_sharing {
+            self.local_participant
+                .as_ref()
+                .map(|local| local.id.as_str())
+        } else {
+            self.focused_share()
+                .map(|share| share.participant_id.as_str())
+        }
+    }
+
+    /// Strokes to draw over the displayed share, in render order
+    pub fn displayed_annotations(&self) -> impl Iterator<Item = &Stroke> {
+        self.annotation_store
+            .strokes_for_share(self.displayed_share_id())
+    }
+
+    /// Everyone in the room: the local participant first, then remote
+    /// participants ordered by id
+    pub fn participants(&self) -> Vec<ParticipantData> {
+        let mut remote: Vec<&ParticipantData> = self.participants.values().collect();
+        remote.sort_by(|a, b| a.id.cmp(&b.id));
+        self.local_participant
+            .iter()
+            .chain(remote)
+            .cloned()
+            .collect()
+    }
+
+    /// Cursors of participants in the room, at their smoothed positions
+    pub fn remote_cursors(&self) -> impl Iterator<Item = &RemoteCursor> {
+        self.remote_cursors.values()
+    }
+
+    /// Advance cursor smoothing by the time since the last frame
+    ///
+    /// Returns true while any visible cursor is still moving or a laser
+    /// trail is still fading, i.e. another redraw is needed.
+    pub fn advance_cursors(&mut self, elapsed: Duration) -> bool {
+        let mut moving = false;
+        for cursor in self.remote_cursors.values_mut() {
+            moving |= cursor.advance(elapsed) && (cursor.visible || !cursor.trail.is_empty());
+        }
+        moving
+    }
+
+    /// Step animations (cursor smoothing, laser trails) to `now` and send
+    /// the WebView the resulting cursors
+    ///
+    /// Returns when the next frame is due while anything is animating, or
+    /// `None` once everything has settled, so the event loop only wakes at
+    /// frame rate while it has something to animate. The first step after
+    /// settling advances one frame rather than the whole idle gap. Calls
+    /// before the next frame is due (the loops call this after every event)
+    /// change nothing and return the same deadline.
+    pub fn animate(&mut self, now: Instant) -> Option<Instant> {
+        if let Some(next_frame) = self
+            .last_animation_frame
+            .map(|last| last + ANIMATION_FRAME_INTERVAL)
+            .filter(|next_frame| now < *next_frame)
+        {
+            return Some(next_frame);
+        }
+        let was_animating = self.last_animation_frame.is_some();
+        let elapsed = self
+            .last_animation_frame
+            .map_or(ANIMATION_FRAME_INTERVAL, |last| {
+                now.saturating_duration_since(last)
+            });
+        let animating = self.advance_cursors(elapsed);
+        // The settling frame goes out too, leaving cursors on their targets
+        if animating || was_animating || self.cursors_changed {
+            self.send_remote_cursors();
+        }
+        self.last_animation_frame = animating.then_some(now);
+        animating.then_some(now + ANIMATION_FRAME_INTERVAL)
+    }
+
+    /// Configuration of the current share as it is actually published
+    ///
+    /// The requested config with the resolution after adaptive downscaling,
+    /// the codec after any fallback, and the framerate capped by the
+    /// encoder. `None` while not sharing.
+    pub fn capture_config(&self) -> Option<CaptureConfig> {
+        let mut config = self.capture_config.clone()?;
+        if let Some(resolution) = self.published_resolution {
+            config.width = resolution.width;
+            config.height = resolution.height;
+        }
+        let encoding = self
+            .room_service
+            .lock()
+            .as_ref()
+            .and_then(|room| room.screen_share_encoding());
+        if let Some(encoding) = encoding {
+            config.codec = encoding.codec;
+            config.bitrate = encoding.max_bitrate as u32;
+            config.framerate = config.framerate.min(encoding.max_framerate);
+        }
+        Some(config)
+    }
+
+    /// Current room connection state
+    pub fn connection_state(&self) -> ConnectionState {
+        self.connection_state
+    }
+
+    /// Run without a winit event loop, for capture-and-publish-only use
+    /// (recorders, bots, CI) where no display or compositor is available
+    ///
+    /// Events are handled on the calling thread until `Terminate`, and
+    /// animations are stepped between them the way `about_to_wait` does in
+    /// the windowed loop. Like `init_socket`, this must be called within a
+    /// tokio runtime.
+    pub fn run_headless(socket_path: &str) {
+        use std::sync::mpsc::RecvTimeoutError;
+
+        let (sender, events) = std::sync::mpsc::channel();
+        let mut app = Self::new(EventProxy::Channel(sender), Arc::new(Mutex::new(None)));
+        app.init_socket(socket_path);
+        tracing::info!("Core application initialized (headless)");
+
+        // The application holds a sender, so this only ends on Terminate
+        loop {
+            // Wake at frame rate while something animates, otherwise sleep
+            // until the next event
+            let event = match app.animate(Instant::now()) {
+                Some(next_frame) => {
+                    let timeout = next_frame.saturating_duration_since(Instant::now());
+                    match events.recv_timeout(timeout) {
+                        Ok(event) => event,
+                        Err(RecvTimeoutError::Timeout) => continue,
+                        Err(RecvTimeoutError::Disconnected) => break,
+                    }
+                }
+                None => match events.recv() {
+                    Ok(event) => event,
+                    Err(_) => break,
+                },
+            };
+            app.handle_user_event(event);
+            if app.exit_requested() {
+                break;
+            }
+        }
+    }
+
+    /// Whether the local screen share is running
+    pub fn is_sharing(&self) -> bool {
+        self.is_sharing
+    }
+
+    /// Whether `Terminate` was handled and the driving loop should exit
+    pub fn exit_requested(&self) -> bool {
+        self.exit_requested
+    }
+
+    /// Handle UserEvent dispatched through the event loop
+    pub fn handle_user_event(&mut self, event: UserEvent) {
         match event {
             // ═══════════════════════════════════════════════════════════════
             // SCREEN CAPTURE EVENTS
             // ═══════════════════════════════════════════════════════════════
-            UserEvent::GetAvailableContent => {
-                self.handle_get_available_content();
+            UserEvent::GetAvailableContent { force_refresh } => {
+                self.handle_get_available_content(force_refresh);
             }
 
             UserEvent::StartScreenShare(msg) => {
@@ -519,36 +1586,209 @@ impl Application {
                 self.handle_stop_screen_share();
             }
 
+            UserEvent::PauseScreenShare => {
+                self.handle_set_screen_share_paused(true);
+            }
+
+            UserEvent::SwitchScreenShareSource { source_id } => {
+                if !self.is_sharing {
+                    self.send_error(ErrorCode::NotSharing, "No screen share in progress");
+                    return;
+                }
+                if let Err(e) = self.screen_capturer.lock().switch_source(&source_id) {
+                    self.send_error(ErrorCode::SourceNotFound, &e.to_string());
+                }
+            }
+
+            UserEvent::CaptureSourceChanged { source_id } => {
+                tracing::info!("Now sharing {}", source_id);
+                self.screen_capturer
+                    .lock()
+                    .source_switched(source_id.clone());
+                self.send_capture_status(OutgoingMessage::CaptureSourceChanged { source_id });
+            }
+
+            UserEvent::ResumeScreenShare => {
+                self.handle_set_screen_share_paused(false);
+            }
+
+            UserEvent::CaptureStill => {
+                if !self.screen_capturer.lock().capture_still() {
+                    self.send_error(ErrorCode::NotSharing, "No screen share in progress");
+                }
+            }
+
+            UserEvent::GetCaptureConfig => {
+                self.send_capture_config();
+            }
+
+            UserEvent::SetCaptureQuality { framerate } => {
+                self.handle_set_capture_quality(framerate);
+            }
+
+            UserEvent::StillCaptured {
+                width,
+                height,
+                data,
+            } => {
+                if let Some(socket) = &*self.socket.lock() {
+                    socket.send(OutgoingMessage::Still {
+                        width,
+                        height,
+                        format: FrameFormat::Jpeg,
+                        data,
+                    });
+                }
+            }
+
             UserEvent::ScreenShareStateChanged {
                 is_sharing,
                 source_id,
             } => {
+                // The capture thread ended without `stop_capture` (e.g. it
+                // gave up restarting), so unpublish and clean up; that
+                // reports again
+                if !is_sharing && self.screen_capturer.lock().capture_thread_exited() {
+                    self.handle_stop_screen_share();
+                    return;
+                }
                 self.is_sharing = is_sharing;
                 self.shared_source_id = source_id;
+                if !is_sharing {
+                    self.capture_resolution = None;
+                    self.capture_config = None;
+                    self.published_resolution = None;
+                }
                 self.send_screen_share_state();
             }
 
+            UserEvent::CapturePanicked { message } => {
+                self.send_error(ErrorCode::CapturePanicked, &message);
+                // Joins the thread, which is on its way out
+                self.handle_stop_screen_share();
+            }
+
             UserEvent::AvailableContentReady { screens } => {
                 self.send_available_content(screens);
             }
 
+            UserEvent::CaptureResolutionChanged { width, height } => {
+                self.handle_capture_resolution_changed(Resolution { width, height });
+            }
+
+            UserEvent::PublishedResolutionChanged { width, height } => {
+                tracing::info!("Publishing screen share at {}x{}", width, height);
+                self.published_resolution = Some(Resolution { width, height });
+                self.send_published_resolution(width, height);
+            }
+
+            UserEvent::CaptureRestarting { attempt, max } => {
+                self.send_capture_status(OutgoingMessage::CaptureRestarting { attempt, max });
+            }
+
+            UserEvent::CaptureRestored => {
+                self.send_capture_status(OutgoingMessage::CaptureRestored);
+            }
+
+            UserEvent::CaptureStats {
+                fps,
+                frames_sent,
+                frames_dropped,
+                restart_attempts,
+            } => {
+                self.send_capture_status(OutgoingMessage::CaptureStats {
+                    fps,
+                    frames_sent,
+                    frames_dropped,
+                    restart_attempts,
+                });
+            }
+
+            UserEvent::CaptureGivingUp => {
+                self.send_capture_status(OutgoingMessage::CaptureGivingUp);
+                // The capture thread has exited - tear down the rest of the share
+                if self.is_sharing {
+                    self.handle_stop_screen_share();
+                }
+            }
+
+            UserEvent::CaptureSourceLost {
+                source_id,
+                switched_to,
+            } => {
+                tracing::warn!(
+                    "Capture source {} lost, switched to {:?}",
+                    source_id,
+                    switched_to
+                );
+                // The display list changed
+                self.source_cache.invalidate();
+                self.send_capture_status(OutgoingMessage::CaptureSourceLost {
+                    source_id,
+                    switched_to: switched_to.clone(),
+                });
+                match switched_to {
+                    Some(new_source) => self.screen_capturer.lock().source_switched(new_source),
+                    // The capture thread has exited
+                    None if self.is_sharing => self.handle_stop_screen_share(),
+                    None => {}
+                }
+            }
+
+            // ═══════════════════════════════════════════════════════════════
+            // RECORDING EVENTS
+            // ═══════════════════════════════════════════════════════════════
+            UserEvent::StartRecording { path } => {
+                self.handle_start_recording(path);
+            }
+
+            UserEvent::StopRecording => {
+                self.handle_stop_recording();
+            }
+
+            UserEvent::RecordingFinished { path, error } => {
+                if let Some(e) = error {
+                    tracing::error!("Recording {} failed: {}", path, e);
+                    self.send_error(ErrorCode::RecordingFailed, &e);
+                }
+                self.send_recording_state(false, Some(path));
+            }
+
             // ═══════════════════════════════════════════════════════════════
             // ANNOTATION EVENTS
             // ═══════════════════════════════════════════════════════════════
+            UserEvent::SetTool { tool, color } => {
+                self.current_tool = tool;
+                self.current_color = color;
+                if let Some(local) = &self.local_participant {
+                    let _ = self
+                        .event_loop_proxy
+                        .send_event(UserEvent::RemoteCursorStyle {
+                            participant_id: local.id.clone(),
+                            style: tool.into(),
+                        });
+                }
+            }
+
             UserEvent::StrokeStart {
                 stroke_id,
                 participant_id,
                 tool,
                 color,
+                width,
                 start_point,
+                share_id,
             } => {
-                self.annotation_store.start_stroke(
+                if let Some(stroke) = self.annotation_store.start_stroke(
                     &stroke_id,
                     &participant_id,
                     tool,
                     color,
+                    width,
                     start_point,
-                );
+                ) {
+                    stroke.share_id = share_id;
+                }
             }
 
             UserEvent::StrokeUpdate { stroke_id, points } => {
@@ -557,6 +1797,15 @@ impl Application {
 
             UserEvent::StrokeComplete { stroke_id } => {
                 self.annotation_store.complete_stroke(&stroke_id);
+                self.acknowledge_strokes([stroke_id.as_str()]);
+            }
+
+            UserEvent::FlushStrokeUpdates => {
+                self.flush_stroke_updates();
+            }
+
+            UserEvent::ResendUnackedStrokes => {
+                self.resend_unacked_strokes();
             }
 
             UserEvent::StrokeDelete { stroke_id } => {
@@ -567,8 +1816,47 @@ impl Application {
                 self.annotation_store.clear_all();
             }
 
+            UserEvent::AnnotationSnapshot { strokes } => {
+                let received = strokes.len();
+                let stroke_ids: Vec<String> = strokes.iter().map(|s| s.id.clone()).collect();
+                let mut added = 0;
+                for stroke in strokes {
+                    if self.annotation_store.insert_stroke(stroke) {
+                        added += 1;
+                    }
+                }
+                self.acknowledge_strokes(stroke_ids.iter().map(String::as_str));
+                tracing::info!(
+                    "Applied annotation snapshot: {} of {} strokes new",
+                    added,
+                    received
+                );
+            }
+
+            UserEvent::ClearParticipantAnnotations { participant_id } => {
+                tracing::info!("Clearing annotations by {}", participant_id);
+                self.annotation_store.delete_by_participant(&participant_id);
+            }
+
             UserEvent::AnnotationPermissionChanged { enabled } => {
-                self.annotations_enabled = enabled;
+                self.annotation_store.set_enabled(enabled);
+            }
+
+            UserEvent::ParticipantAnnotationPermissionChanged {
+                participant_id,
+                enabled,
+                clear_strokes,
+            } => {
+                tracing::info!(
+                    "Annotations {} for {}",
+                    if enabled { "allowed" } else { "revoked" },
+                    participant_id
+                );
+                self.annotation_store.set_participant_enabled(
+                    &participant_id,
+                    enabled,
+                    clear_strokes,
+                );
             }
 
             // ═══════════════════════════════════════════════════════════════
@@ -580,21 +1868,36 @@ impl Application {
                 y,
                 visible,
             } => {
+                let (Some(x), Some(y)) = (sanitize_coordinate(x), sanitize_coordinate(y)) else {
+                    tracing::warn!("Dropping invalid cursor position from {}", participant_id);
+                    return;
+                };
+                self.cursors_changed = true;
                 if let Some(cursor) = self.remote_cursors.get_mut(&participant_id) {
-                    cursor.x = x;
-                    cursor.y = y;
-                    cursor.visible = visible;
+                    cursor.set_target(x, y, visible);
                 } else {
                     let color = self.get_participant_color(&participant_id);
+                    let is_local = self
+                        .local_participant
+                        .as_ref()
+                        .is_some_and(|local| local.id == participant_id);
+                    let style = if is_local {
+                        self.current_tool.into()
+                    } else {
+                        CursorStyle::Default
+                    };
                     self.remote_cursors.insert(
                         participant_id.clone(),
                         RemoteCursor {
                             participant_id,
                             x,
                             y,
+                            target_x: x,
+                            target_y: y,
                             visible,
-                            style: CursorStyle::Default,
+                            style,
                             color,
+                            trail: VecDeque::new(),
                         },
                     );
                 }
@@ -605,30 +1908,80 @@ impl Application {
                 style,
             } => {
                 if let Some(cursor) = self.remote_cursors.get_mut(&participant_id) {
-                    cursor.style = style;
+                    if cursor.style != style {
+                        cursor.style = style;
+                        self.cursors_changed = true;
+                    }
                 }
             }
 
             // ═══════════════════════════════════════════════════════════════
             // LIVEKIT EVENTS
             // ═══════════════════════════════════════════════════════════════
-            UserEvent::JoinRoom { server_url, token } => {
-                self.handle_join_room(server_url, token);
+            UserEvent::JoinRoom {
+                server_url,
+                token,
+                e2ee_passphrase,
+                initial_media,
+                auto_subscribe,
+                relay_format,
+            } => {
+                self.handle_join_room(
+                    server_url,
+                    token,
+                    e2ee_passphrase,
+                    *initial_media,
+                    auto_subscribe,
+                    relay_format,
+                );
             }
 
             UserEvent::LeaveRoom => {
                 self.handle_leave_room();
             }
 
+            UserEvent::SetTrackSubscribed {
+                participant_id,
+                track_id,
+                subscribed,
+            } => {
+                let result = match &*self.room_service.lock() {
+                    Some(room) => room.set_track_subscribed(&participant_id, &track_id, subscribed),
+                    None => Err("Not connected to a room".to_string()),
+                };
+                if let Err(e) = result {
+                    self.send_error(ErrorCode::TrackNotFound, &e);
+                }
+            }
+
+            UserEvent::GetParticipants => {
+                if let Some(socket) = &*self.socket.lock() {
+                    socket.send(OutgoingMessage::Participants {
+                        participants: self.participants(),
+                    });
+                }
+            }
+
             UserEvent::ParticipantConnected(data) => {
                 self.participants.insert(data.id.clone(), data.clone());
                 self.send_participant_joined(&data);
+                self.send_annotation_snapshot(&data.id);
             }
 
             UserEvent::ParticipantDisconnected(data) => {
                 self.participants.remove(&data.id);
-                self.remote_cursors.remove(&data.id);
+                if self.remote_cursors.remove(&data.id).is_some() {
+                    self.cursors_changed = true;
+                }
+                self.stroke_acks.peer_left(&data.id);
                 self.send_participant_left(&data.id);
+                if self.share_focus.participant_left(&data.id, Instant::now()) {
+                    self.dispatch_focused_share();
+                }
+            }
+
+            UserEvent::ParticipantMetadataChanged(data) => {
+                self.handle_participant_metadata_changed(data);
             }
 
             UserEvent::ConnectionStateChanged(state) => {
@@ -643,16 +1996,101 @@ impl Application {
                 self.handle_data_received(&participant_id, &payload);
             }
 
+            UserEvent::PublishData(msg) => {
+                self.handle_publish_data(msg);
+            }
+
+            UserEvent::TrackSubscribed {
+                participant_id,
+                track_id,
+                source,
+            } => {
+                if let Some(socket) = &*self.socket.lock() {
+                    socket.send(OutgoingMessage::TrackSubscribed {
+                        participant_id,
+                        track_id,
+                        source,
+                    });
+                }
+            }
+
+            UserEvent::TrackUnsubscribed {
+                participant_id,
+                track_id,
+            } => {
+                if let Some(socket) = &*self.socket.lock() {
+                    socket.send(OutgoingMessage::TrackUnsubscribed {
+                        participant_id,
+                        track_id,
+                    });
+                }
+            }
+
+            // Focus follows what is shared, not what this client happens to
+            // be subscribed to
+            UserEvent::RemoteTrackPublished {
+                participant_id,
+                track_id,
+                source,
+            } => {
+                if source == TrackSourceType::ScreenShare {
+                    let share = room::focus::FocusedShare {
+                        participant_id,
+                        track_id,
+                    };
+                    if self.share_focus.share_added(share, Instant::now()) {
+                        self.dispatch_focused_share();
+                    }
+                }
+            }
+
+            UserEvent::RemoteTrackUnpublished { track_id, .. } => {
+                if self.share_focus.track_removed(&track_id, Instant::now()) {
+                    self.dispatch_focused_share();
+                }
+            }
+
+            UserEvent::ActiveSpeakersChanged { speakers } => {
+                if self.share_focus.speakers_changed(&speakers, Instant::now()) {
+                    self.dispatch_focused_share();
+                }
+                self.send_active_speakers(speakers);
+            }
+
+            UserEvent::FocusedShareChanged {
+                participant_id,
+                track_id,
+            } => {
+                if let Some(socket) = &*self.socket.lock() {
+                    socket.send(OutgoingMessage::FocusedShareChanged {
+                        participant_id,
+                        track_id,
+                    });
+                }
+            }
+
+            UserEvent::ConnectionStatsUpdated(stats) => {
+                if self.is_sharing {
+                    if let Some(max_height) = self.adaptive_resolution.observe(&stats) {
+                        tracing::info!("Adapting screen share height cap to {:?}", max_height);
+                        self.screen_capturer.lock().set_max_height(max_height);
+                    }
+                }
+                self.send_connection_stats(stats);
+            }
+
             // ═══════════════════════════════════════════════════════════════
             // AUDIO/VIDEO CONTROLS
             // ═══════════════════════════════════════════════════════════════
             UserEvent::SetMicrophoneMuted(muted) => {
+                self.needed_permissions.microphone |= !muted;
                 if let Some(room) = &*self.room_service.lock() {
                     room.set_microphone_muted(muted);
                 }
             }
 
             UserEvent::SetCameraEnabled(enabled) => {
+                self.needed_permissions.camera |= enabled;
                 if let Some(room) = &*self.room_service.lock() {
                     room.set_camera_enabled(enabled);
                 }
@@ -696,34 +2134,67 @@ impl Application {
             // ═══════════════════════════════════════════════════════════════
             UserEvent::SocketConnected => {
                 tracing::info!("Socket client connected");
+                self.idle_watchdog.client_connected();
             }
 
             UserEvent::SocketDisconnected => {
                 tracing::info!("Socket client disconnected");
+                self.idle_watchdog
+                    .client_disconnected(&self.event_loop_proxy);
+            }
+
+            UserEvent::Ping { timestamp } => {
+                self.send_pong(timestamp);
             }
 
+            UserEvent::SetLogLevel { filter } => match logging::set_filter(&filter) {
+                Ok(()) => tracing::info!(filter = %filter, "Log filter updated"),
+                Err(e) => self.send_error(
+                    ErrorCode::InvalidLogFilter,
+                    &format!("Invalid log filter {:?}: {}", filter, e),
+                ),
+            },
+
             UserEvent::Error { code, message } => {
                 tracing::error!("Error [{}]: {}", code, message);
-                self.send_error(&code, &message);
+                self.send_error(code, &message);
             }
 
             // ═══════════════════════════════════════════════════════════════
             // ROOM EVENTS (internal notifications)
             // ═══════════════════════════════════════════════════════════════
-            UserEvent::RoomConnected { room_name } => {
-                eprintln!("[DEBUG] RoomConnected event received: {}", room_name);
+            UserEvent::RoomConnected {
+                room_name,
+                relay_format,
+            } => {
+                tracing::debug!(room_name = %room_name, "Room connected");
                 self.connection_state = ConnectionState::Connected;
                 // Send Connected state to WebView via socket
                 if let Some(socket) = &*self.socket.lock() {
                     socket.send(OutgoingMessage::ConnectionStateChanged {
                         state: ConnectionState::Connected,
                     });
+                    socket.send(OutgoingMessage::RoomConnected {
+                        room_name,
+                        relay_format,
+                    });
                 }
             }
 
-            UserEvent::RoomDisconnected => {
-                tracing::info!("Room disconnected");
-                // Notify WebView via socket if connected
+            UserEvent::LocalParticipant(data) => {
+                tracing::info!("Local participant: {}", data.id);
+                // Announce ourselves like any other participant so the
+                // WebView's roster is complete (marked by `is_local`)
+                self.send_participant_joined(&data);
+                self.local_participant = Some(data);
+            }
+
+            UserEvent::RoomReconnected => {
+                self.handle_room_reconnected();
+            }
+
+            UserEvent::RoomDisconnected { reason } => {
+                self.handle_room_disconnected(reason);
             }
 
             UserEvent::ScreenSharePublished => {
@@ -747,8 +2218,19 @@ impl Application {
                 self.handle_request_screen_recording_permission();
             }
 
+            UserEvent::RequestMicrophonePermission => {
+                self.needed_permissions.microphone = true;
+                self.handle_request_media_permission(permissions::request_microphone);
+            }
+
+            UserEvent::RequestCameraPermission => {
+                self.needed_permissions.camera = true;
+                self.handle_request_media_permission(permissions::request_camera);
+            }
+
             UserEvent::PermissionStateChanged(state) => {
                 self.send_permission_state(&state);
+                self.ensure_permission_poller(state);
             }
 
             // ═══════════════════════════════════════════════════════════════
@@ -756,7 +2238,7 @@ impl Application {
             // ═══════════════════════════════════════════════════════════════
             UserEvent::Terminate => {
                 self.handle_shutdown();
-                elwt.exit();
+                self.exit_requested = true;
             }
         }
     }
@@ -765,6 +2247,17 @@ impl Application {
     // INTERNAL HELPERS
     // ═══════════════════════════════════════════════════════════════════════════
 
+    /// Replace the socket's local placeholder id with the local identity
+    ///
+    /// Before the room is connected the identity is unknown and the
+    /// placeholder is kept.
+    fn resolve_participant_id(&self, participant_id: String) -> String {
+        match &self.local_participant {
+            Some(local) if participant_id == socket::LOCAL_PARTICIPANT_ID => local.id.clone(),
+            _ => participant_id,
+        }
+    }
+
     fn get_participant_color(&self, participant_id: &str) -> Color {
         let index = self
             .participants
@@ -778,26 +2271,62 @@ impl Application {
     // SCREEN CAPTURE HANDLERS
     // ═══════════════════════════════════════════════════════════════════════════
 
-    fn handle_get_available_content(&self) {
+    fn handle_get_available_content(&self, force_refresh: bool) {
+        // Answer immediately from the cache, then rescan for fresh thumbnails
+        if !force_refresh {
+            if let Some(screens) = self.source_cache.get() {
+                tracing::debug!("Serving {} cached sources", screens.len());
+                let _ = self
+                    .event_loop_proxy
+                    .send_event(UserEvent::AvailableContentReady { screens });
+            }
+        }
+
+        // A scan already in progress will report its results
+        if !self.source_cache.begin_refresh() {
+            return;
+        }
+
         let capturer = self.screen_capturer.clone();
+        let source_cache = self.source_cache.clone();
         let proxy = self.event_loop_proxy.clone();
 
         tokio::spawn(async move {
-            let capturer = capturer.lock();
-            let screens = capturer.enumerate_sources();
-
-            let _ = proxy.send_event(UserEvent::AvailableContentReady { screens });
+            let result = capturer.lock().enumerate_sources();
+            source_cache.end_refresh();
+
+            let event = match result {
+                Ok(screens) => UserEvent::AvailableContentReady { screens },
+                // Without a capturer nothing can be listed - tell the picker why
+                Err(e) => UserEvent::Error {
+                    code: ErrorCode::PermissionDenied,
+                    message: e.to_string(),
+                },
+            };
+            let _ = proxy.send_event(event);
         });
     }
 
     fn handle_start_screen_share(&mut self, msg: ScreenShareMessage) {
+        // The capturer is checked too: `is_sharing` only updates once the
+        // state change event is handled, after a queued duplicate start
+        if self.is_sharing || self.screen_capturer.lock().is_capturing() {
+            if !msg.replace {
+                self.send_error(
+                    ErrorCode::AlreadySharing,
+                    "A screen share is already in progress; stop it first or set replace",
+                );
+                return;
+            }
+            tracing::info!("Replacing the current screen share");
+            self.handle_stop_screen_share();
+        }
+
         let source_id = msg.source_id.clone();
-        let width = msg.config.width;
-        let height = msg.config.height;
 
         // Publish screen share track to LiveKit if connected (sync call)
         let video_source = if let Some(ref room) = *self.room_service.lock() {
-            match room.publish_screen_share(width, height) {
+            match room.publish_screen_share(&msg.config) {
                 Ok(source) => {
                     tracing::info!("Screen share track published to LiveKit");
                     Some(source)
@@ -805,7 +2334,7 @@ impl Application {
                 Err(e) => {
                     tracing::error!("Failed to publish screen share track: {}", e);
                     let _ = self.event_loop_proxy.send_event(UserEvent::Error {
-                        code: "publish_failed".to_string(),
+                        code: ErrorCode::PublishFailed,
                         message: e,
                     });
                     return;
@@ -827,6 +2356,9 @@ impl Application {
             match capturer.start_capture(&msg.source_id, msg.source_type, &msg.config) {
                 Ok(()) => {
                     tracing::info!("Screen capture started for source: {}", msg.source_id);
+                    self.adaptive_resolution = capture::adaptive::AdaptiveResolution::new();
+                    self.capture_config = Some(msg.config.clone());
+                    self.published_resolution = None;
                     let _ = self
                         .event_loop_proxy
                         .send_event(UserEvent::ScreenShareStateChanged {
@@ -837,17 +2369,119 @@ impl Application {
                 Err(e) => {
                     tracing::error!("Failed to start capture: {}", e);
                     let _ = self.event_loop_proxy.send_event(UserEvent::Error {
-                        code: "capture_failed".to_string(),
+                        code: ErrorCode::CaptureFailed,
                         message: e.to_string(),
                     });
+                    return;
                 }
             }
         }
+
+        if msg.config.capture_audio {
+            self.start_system_audio();
+        }
+    }
+
+    /// Capture system audio and publish it alongside the screen share
+    ///
+    /// Failures are reported but do not stop the (video) screen share.
+    fn start_system_audio(&mut self) {
+        self.system_audio = None;
+
+        let result = capture::audio::SystemAudioCapture::probe()
+            .map_err(|e| e.to_string())
+            .and_then(|format| {
+                let room_service = self.room_service.lock();
+                let Some(room) = room_service.as_ref() else {
+                    return Err("Not connected to room".to_string());
+                };
+                let sink =
+                    room.publish_screen_share_audio(format.sample_rate, format.num_channels)?;
+                capture::audio::SystemAudioCapture::start(format, sink).map_err(|e| {
+                    // Don't leave a silent track published until the share stops
+                    if let Err(unpublish_err) = room.unpublish_screen_share_audio() {
+                        tracing::warn!("Failed to unpublish screen share audio: {}", unpublish_err);
+                    }
+                    e.to_string()
+                })
+            });
+
+        match result {
+            Ok(audio) => self.system_audio = Some(audio),
+            Err(e) => {
+                tracing::warn!("Screen share audio unavailable: {}", e);
+                let _ = self.event_loop_proxy.send_event(UserEvent::Error {
+                    code: ErrorCode::AudioCaptureFailed,
+                    message: e,
+                });
+            }
+        }
+    }
+
+    fn handle_set_screen_share_paused(&mut self, paused: bool) {
+        if !self.is_sharing {
+            let _ = self.event_loop_proxy.send_event(UserEvent::Error {
+                code: ErrorCode::NotSharing,
+                message: "No screen share in progress".to_string(),
+            });
+            return;
+        }
+
+        let changed = {
+            let mut capturer = self.screen_capturer.lock();
+            if paused {
+                capturer.pause_capture()
+            } else {
+                capturer.resume_capture()
+            }
+        };
+
+        // Pausing twice (or resuming a running share) is a no-op
+        if changed {
+            self.send_screen_share_paused(paused);
+        }
+    }
+
+    fn handle_set_capture_quality(&mut self, framerate: Option<u32>) {
+        if self.capture_config.is_none() {
+            self.send_error(ErrorCode::NotSharing, "No screen share in progress");
+            return;
+        }
+        let framerate_range = capture::MIN_FRAMERATE..=capture::MAX_FRAMERATE;
+        if framerate.is_some_and(|framerate| !framerate_range.contains(&framerate)) {
+            self.send_error(
+                ErrorCode::InvalidCaptureQuality,
+                &format!(
+                    "Framerate must be between {} and {} fps",
+                    framerate_range.start(),
+                    framerate_range.end()
+                ),
+            );
+            return;
+        }
+
+        if let Some(framerate) = framerate {
+            self.screen_capturer.lock().set_framerate(framerate);
+            if let Some(config) = &mut self.capture_config {
+                config.framerate = framerate;
+            }
+        }
+
+        self.send_capture_config();
+    }
+
+    fn send_capture_config(&self) {
+        if let Some(socket) = &*self.socket.lock() {
+            socket.send(OutgoingMessage::CaptureConfig {
+                config: self.capture_config(),
+            });
+        }
     }
 
     fn handle_stop_screen_share(&mut self) {
         // Stop capture first
         self.screen_capturer.lock().stop_capture();
+        self.system_audio = None;
 
         // Unpublish the track from LiveKit (sync call)
         if let Some(ref room) = *self.room_service.lock() {
@@ -866,40 +2500,115 @@ impl Application {
             });
     }
 
+    // ═══════════════════════════════════════════════════════════════════════════
+    // RECORDING HANDLERS
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    fn handle_start_recording(&mut self, path: String) {
+        let capturer = self.screen_capturer.lock();
+        if capturer.is_recording() {
+            let _ = self.event_loop_proxy.send_event(UserEvent::Error {
+                code: ErrorCode::AlreadyRecording,
+                message: "A recording is already in progress".to_string(),
+            });
+            return;
+        }
+
+        match capturer.start_recording(&path) {
+            Ok(()) => self.send_recording_state(true, Some(path)),
+            Err(e) => {
+                tracing::error!("Failed to start recording: {}", e);
+                let _ = self.event_loop_proxy.send_event(UserEvent::Error {
+                    code: ErrorCode::RecordingFailed,
+                    message: e.to_string(),
+                });
+            }
+        }
+    }
+
+    fn handle_stop_recording(&mut self) {
+        let Some(recorder) = self.screen_capturer.lock().take_recorder() else {
+            tracing::warn!("StopRecording received with no active recording");
+            return;
+        };
+
+        // Finalizing waits for the encoder to flush - keep it off the event loop
+        let proxy = self.event_loop_proxy.clone();
+        std::thread::spawn(move || {
+            let path = recorder.path().display().to_string();
+            let error = recorder.finish().err().map(|e| e.to_string());
+            let _ = proxy.send_event(UserEvent::RecordingFinished { path, error });
+        });
+    }
+
     // ═══════════════════════════════════════════════════════════════════════════
     // ROOM HANDLERS
     // ═══════════════════════════════════════════════════════════════════════════
 
-    fn handle_join_room(&mut self, server_url: String, token: String) {
+    fn handle_join_room(
+        &mut self,
+        server_url: String,
+        token: Secret,
+        e2ee_passphrase: Option<Secret>,
+        initial_media: InitialMedia,
+        auto_subscribe: bool,
+        relay_format: FrameFormat,
+    ) {
         let proxy = self.event_loop_proxy.clone();
         let room_service_holder = self.room_service.clone();
 
+        // Refuse to join unencrypted if the client asked for E2EE
+        #[cfg(not(feature = "e2ee"))]
+        if e2ee_passphrase.is_some() {
+            self.send_error(
+                ErrorCode::E2eeUnavailable,
+                "This build of Core does not support end-to-end encryption",
+            );
+            return;
+        }
+
+        // Core has no microphone or camera track to publish yet
+        if initial_media.microphone || initial_media.camera {
+            self.send_error(
+                ErrorCode::RoomJoinFailed,
+                "Joining with the microphone or camera on isn't supported yet",
+            );
+            return;
+        }
+
         let _ = proxy.send_event(UserEvent::ConnectionStateChanged(
             ConnectionState::Connecting,
         ));
 
         // Spawn a thread for the blocking connection (don't block winit event loop)
         std::thread::spawn(move || {
-            eprintln!("[DEBUG] handle_join_room thread started");
-            eprintln!("[DEBUG] Creating RoomService for {}", server_url);
-
             match room::RoomService::new(server_url.clone(), proxy.clone()) {
-                Ok(room_service) => {
-                    eprintln!("[DEBUG] RoomService created, calling connect() (blocking)");
+                Ok(mut room_service) => {
+                    room_service.set_auto_subscribe(auto_subscribe);
+
+                    #[cfg(feature = "e2ee")]
+                    if let Some(passphrase) = &e2ee_passphrase {
+                        room_service.enable_data_encryption(passphrase);
+                    }
+
                     // Blocking call - waits for connection result
-                    match room_service.connect(token) {
+                    match room_service.connect(token.into_inner(), relay_format) {
                         Ok(()) => {
-                            eprintln!("[DEBUG] connect() succeeded, storing RoomService");
                             *room_service_holder.lock() = Some(room_service);
                             let _ = proxy.send_event(UserEvent::ConnectionStateChanged(
                                 ConnectionState::Connected,
                             ));
-                            eprintln!("[DEBUG] Connected state sent to frontend");
+
+                            // Queued behind the Connected state, so the tracks
+                            // publish in order with the usual state events
+                            for event in initial_media.events() {
+                                let _ = proxy.send_event(event);
+                            }
                         }
                         Err(e) => {
-                            eprintln!("[DEBUG] connect() failed: {}", e);
+                            tracing::error!("Failed to join room: {}", e);
                             let _ = proxy.send_event(UserEvent::Error {
-                                code: "room_join_failed".to_string(),
+                                code: ErrorCode::RoomJoinFailed,
                                 message: e,
                             });
                             let _ = proxy.send_event(UserEvent::ConnectionStateChanged(
@@ -909,9 +2618,9 @@ impl Application {
                     }
                 }
                 Err(e) => {
-                    eprintln!("[DEBUG] Failed to create RoomService: {}", e);
+                    tracing::error!("Failed to create RoomService: {}", e);
                     let _ = proxy.send_event(UserEvent::Error {
-                        code: "room_service_failed".to_string(),
+                        code: ErrorCode::RoomServiceFailed,
                         message: e.to_string(),
                     });
                     let _ = proxy.send_event(UserEvent::ConnectionStateChanged(
@@ -919,18 +2628,50 @@ impl Application {
                     ));
                 }
             }
-            eprintln!("[DEBUG] handle_join_room thread finished");
         });
     }
 
+    /// Restore session state after LiveKit reconnected
+    ///
+    /// Flow for an active share: the connection drops (`Reconnecting` is sent
+    /// to the WebView while capture keeps running), LiveKit reconnects, then
+    /// the screen share publication is verified and republished from the
+    /// same video source if needed - the share never stops locally.
+    fn handle_room_reconnected(&mut self) {
+        tracing::info!("Room reconnected (sharing: {})", self.is_sharing);
+
+        if self.is_sharing {
+            if let Some(room) = &*self.room_service.lock() {
+                if let Err(e) = room.restore_screen_share() {
+                    tracing::error!("Failed to restore screen share after reconnect: {}", e);
+                    let _ = self.event_loop_proxy.send_event(UserEvent::Error {
+                        code: ErrorCode::PublishFailed,
+                        message: e,
+                    });
+                }
+            }
+        }
+
+        let _ = self
+            .event_loop_proxy
+            .send_event(UserEvent::ConnectionStateChanged(
+                ConnectionState::Connected,
+            ));
+    }
+
     fn handle_leave_room(&mut self) {
         // Disconnect from room (sync call - RoomService handles async internally)
         if let Some(room) = self.room_service.lock().take() {
             room.disconnect();
         }
 
+        self.local_participant = None;
         self.participants.clear();
         self.remote_cursors.clear();
+        self.cursors_changed = true;
+        if self.share_focus.clear() {
+            self.dispatch_focused_share();
+        }
         self.connection_state = ConnectionState::Disconnected;
 
         let _ = self
@@ -940,56 +2681,352 @@ impl Application {
             ));
     }
 
+    fn handle_room_disconnected(&mut self, reason: String) {
+        // Leaving already reported the disconnect
+        if self.connection_state == ConnectionState::Disconnected {
+            tracing::info!("Room disconnected ({})", reason);
+            return;
+        }
+
+        tracing::warn!("Room disconnected unexpectedly: {}", reason);
+        self.local_participant = None;
+        self.participants.clear();
+        self.remote_cursors.clear();
+        self.cursors_changed = true;
+        if self.share_focus.clear() {
+            self.dispatch_focused_share();
+        }
+        self.connection_state = ConnectionState::Disconnected;
+
+        if let Some(socket) = &*self.socket.lock() {
+            socket.send(OutgoingMessage::RoomDisconnected { reason });
+        }
+        self.send_connection_state();
+    }
+
     fn handle_data_received(&mut self, participant_id: &str, payload: &[u8]) {
+        // Decrypt first (no-op unless the room uses E2EE)
+        let payload = match &*self.room_service.lock() {
+            Some(room) => match room.open_data(payload) {
+                Some(plaintext) => plaintext,
+                None => return,
+            },
+            None => payload.to_vec(),
+        };
+
         // Parse DataTrack message and dispatch appropriate event
-        if let Ok(msg) = serde_json::from_slice::<socket::DataTrackMessage>(payload) {
-            match msg {
-                socket::DataTrackMessage::StrokeStart {
-                    stroke_id,
+        if let Ok(msg) = serde_json::from_slice::<socket::DataTrackMessage>(&payload) {
+            self.apply_data_message(participant_id, msg);
+        }
+    }
+
+    fn handle_publish_data(&mut self, mut msg: socket::DataTrackMessage) {
+        let participant_id = self.resolve_participant_id(socket::LOCAL_PARTICIPANT_ID.to_string());
+        if msg.requires_host() && !self.is_host(&participant_id) {
+            self.send_error(ErrorCode::NotHost, "Only the host can moderate annotations");
+            return;
+        }
+        if let socket::DataTrackMessage::StrokeStart { share_id, .. } = &mut msg {
+            if share_id.is_none() {
+                *share_id = self.displayed_share_id().map(str::to_string);
+            }
+        }
+        self.apply_data_message(&participant_id, msg.clone());
+
+        if let Some(room) = &*self.room_service.lock() {
+            match serde_json::to_vec(&msg) {
+                Ok(payload) => room.send_data(payload, msg.is_reliable()),
+                Err(e) => tracing::error!("Failed to encode DataTrack message: {}", e),
+            }
+        }
+
+        if let socket::DataTrackMessage::StrokeComplete { stroke_id } = &msg {
+            self.track_stroke_acks(stroke_id);
+        }
+    }
+
+    /// Expect every peer in the room to acknowledge a completed local stroke
+    fn track_stroke_acks(&mut self, stroke_id: &str) {
+        if self.room_service.lock().is_none() {
+            return;
+        }
+        let peers = self.participants.keys().cloned();
+        if self.stroke_acks.track(stroke_id, peers, Instant::now()) {
+            self.schedule_stroke_resend();
+        }
+    }
+
+    /// Whether `participant_id` is the local participant (or its placeholder)
+    fn is_local(&self, participant_id: &str) -> bool {
+        participant_id == socket::LOCAL_PARTICIPANT_ID
+            || self
+                .local_participant
+                .as_ref()
+                .is_some_and(|local| local.id == participant_id)
+    }
+
+    /// Whether a participant (local or remote) joined with the host role
+    fn is_host(&self, participant_id: &str) -> bool {
+        let participant = match &self.local_participant {
+            Some(local) if local.id == participant_id => Some(local),
+            _ => self.participants.get(participant_id),
+        };
+        participant.is_some_and(|p| p.role == ParticipantRole::Host)
+    }
+
+    /// Bring a late joiner's canvas up to date
+    ///
+    /// Sent by the sharer, or by a host; receivers skip strokes they already
+    /// have, so getting a snapshot from both is harmless.
+    fn send_annotation_snapshot(&self, participant_id: &str) {
+        let local_id = self.resolve_participant_id(socket::LOCAL_PARTICIPANT_ID.to_string());
+        if self.annotation_store.is_empty() || !(self.is_sharing || self.is_host(&local_id)) {
+            return;
+        }
+        let Some(room) = &*self.room_service.lock() else {
+            return;
+        };
+
+        let strokes = self
+            .annotation_store
+            .snapshot(annotation::SNAPSHOT_MAX_STROKES);
+        for strokes in annotation::chunk_strokes(strokes, annotation::SNAPSHOT_CHUNK_BYTES) {
+            match serde_json::to_vec(&socket::DataTrackMessage::Snapshot { strokes }) {
+                Ok(payload) => room.send_data_to(payload, participant_id),
+                Err(e) => tracing::error!("Failed to encode annotation snapshot: {}", e),
+            }
+        }
+    }
+
+    /// Dispatch the events for a DataTrack message authored by `participant_id`
+    fn apply_data_message(&mut self, participant_id: &str, msg: socket::DataTrackMessage) {
+        if msg.requires_host() && !self.is_host(participant_id) {
+            tracing::warn!(
+                "Ignoring host-only DataTrack message from {}",
+                participant_id
+            );
+            return;
+        }
+
+        // Points are batched; anything else must not overtake them
+        if !matches!(msg, socket::DataTrackMessage::StrokeUpdate { .. }) {
+            self.flush_stroke_updates();
+        }
+
+        match msg {
+            socket::DataTrackMessage::StrokeStart {
+                stroke_id,
+                tool,
+                color,
+                width,
+                point,
+                points,
+                share_id,
+            } => {
+                let _ = self.event_loop_proxy.send_event(UserEvent::StrokeStart {
+                    stroke_id: stroke_id.clone(),
+                    participant_id: participant_id.to_string(),
                     tool,
                     color,
-                    point,
-                } => {
-                    let _ = self.event_loop_proxy.send_event(UserEvent::StrokeStart {
-                        stroke_id,
-                        participant_id: participant_id.to_string(),
-                        tool,
-                        color,
-                        start_point: point,
-                    });
-                }
-                socket::DataTrackMessage::StrokeUpdate { stroke_id, points } => {
+                    width: tool.stroke_width(width),
+                    start_point: point,
+                    share_id,
+                });
+                if !points.is_empty() {
                     let _ = self
                         .event_loop_proxy
                         .send_event(UserEvent::StrokeUpdate { stroke_id, points });
                 }
-                socket::DataTrackMessage::StrokeComplete { stroke_id } => {
-                    let _ = self
-                        .event_loop_proxy
-                        .send_event(UserEvent::StrokeComplete { stroke_id });
+            }
+            socket::DataTrackMessage::StrokeUpdate { stroke_id, points } => {
+                if self.stroke_updates.push(&stroke_id, points) {
+                    self.schedule_stroke_flush();
                 }
-                socket::DataTrackMessage::StrokeDelete { stroke_id } => {
-                    let _ = self
-                        .event_loop_proxy
-                        .send_event(UserEvent::StrokeDelete { stroke_id });
+            }
+            socket::DataTrackMessage::StrokePoints { stroke_id, points } => {
+                let _ = self
+                    .event_loop_proxy
+                    .send_event(UserEvent::StrokeUpdate { stroke_id, points });
+            }
+            socket::DataTrackMessage::StrokeComplete { stroke_id } => {
+                let _ = self
+                    .event_loop_proxy
+                    .send_event(UserEvent::StrokeComplete { stroke_id });
+            }
+            socket::DataTrackMessage::StrokeAck { stroke_ids } => {
+                for stroke_id in &stroke_ids {
+                    self.stroke_acks.ack(stroke_id, participant_id);
                 }
-                socket::DataTrackMessage::ClearAll => {
-                    let _ = self
-                        .event_loop_proxy
-                        .send_event(UserEvent::ClearAllAnnotations);
+            }
+            socket::DataTrackMessage::StrokeDelete { stroke_id } => {
+                let _ = self
+                    .event_loop_proxy
+                    .send_event(UserEvent::StrokeDelete { stroke_id });
+            }
+            socket::DataTrackMessage::ClearAll => {
+                let _ = self
+                    .event_loop_proxy
+                    .send_event(UserEvent::ClearAllAnnotations);
+            }
+            socket::DataTrackMessage::ClearParticipant { participant_id } => {
+                let _ = self
+                    .event_loop_proxy
+                    .send_event(UserEvent::ClearParticipantAnnotations { participant_id });
+            }
+            socket::DataTrackMessage::CursorMove {
+                x,
+                y,
+                visible,
+                style,
+            } => {
+                let style = style.unwrap_or_else(|| {
+                    if self.is_local(participant_id) {
+                        self.current_tool.into()
+                    } else {
+                        CursorStyle::Default
+                    }
+                });
+                let _ = self
+                    .event_loop_proxy
+                    .send_event(UserEvent::RemoteCursorPosition {
+                        participant_id: participant_id.to_string(),
+                        x,
+                        y,
+                        visible,
+                    });
+                let _ = self
+                    .event_loop_proxy
+                    .send_event(UserEvent::RemoteCursorStyle {
+                        participant_id: participant_id.to_string(),
+                        style,
+                    });
+            }
+            socket::DataTrackMessage::AnnotationPermission {
+                participant_id,
+                enabled,
+                clear_strokes,
+            } => {
+                let _ = self.event_loop_proxy.send_event(
+                    UserEvent::ParticipantAnnotationPermissionChanged {
+                        participant_id,
+                        enabled,
+                        clear_strokes,
+                    },
+                );
+            }
+            socket::DataTrackMessage::Snapshot { mut strokes } => {
+                // The host and sharers bring late joiners up to date with
+                // everyone's strokes; anyone else only re-sends their own
+                if !self.is_host(participant_id) && !self.share_focus.is_sharing(participant_id) {
+                    let before = strokes.len();
+                    strokes.retain(|stroke| stroke.participant_id == participant_id);
+                    if strokes.len() < before {
+                        tracing::warn!(
+                            "Ignoring {} snapshot strokes from {} drawn by others",
+                            before - strokes.len(),
+                            participant_id
+                        );
+                    }
                 }
-                socket::DataTrackMessage::CursorMove { x, y, visible } => {
-                    let _ = self
-                        .event_loop_proxy
-                        .send_event(UserEvent::RemoteCursorPosition {
-                            participant_id: participant_id.to_string(),
-                            x,
-                            y,
-                            visible,
-                        });
+                let _ = self
+                    .event_loop_proxy
+                    .send_event(UserEvent::AnnotationSnapshot { strokes });
+            }
+        }
+    }
+
+    /// Flush batched stroke points once the batch window has passed
+    fn schedule_stroke_flush(&self) {
+        let proxy = self.event_loop_proxy.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(annotation::STROKE_BATCH_WINDOW).await;
+            let _ = proxy.send_event(UserEvent::FlushStrokeUpdates);
+        });
+    }
+
+    /// Check for unacknowledged strokes once the ack timeout has passed
+    fn schedule_stroke_resend(&self) {
+        let proxy = self.event_loop_proxy.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(annotation::STROKE_ACK_TIMEOUT).await;
+            let _ = proxy.send_event(UserEvent::ResendUnackedStrokes);
+        });
+    }
+
+    /// Tell the authors of remote strokes we hold completed that we have them
+    fn acknowledge_strokes<'a>(&self, stroke_ids: impl IntoIterator<Item = &'a str>) {
+        let mut by_author: HashMap<&str, Vec<String>> = HashMap::new();
+        for stroke_id in stroke_ids {
+            let Some(stroke) = self.annotation_store.get(stroke_id) else {
+                continue;
+            };
+            if stroke.completed && !self.is_local(&stroke.participant_id) {
+                by_author
+                    .entry(&stroke.participant_id)
+                    .or_default()
+                    .push(stroke.id.clone());
+            }
+        }
+
+        let Some(room) = &*self.room_service.lock() else {
+            return;
+        };
+        for (author, stroke_ids) in by_author {
+            match serde_json::to_vec(&socket::DataTrackMessage::StrokeAck { stroke_ids }) {
+                Ok(payload) => room.send_data_to(payload, author),
+                Err(e) => tracing::error!("Failed to encode stroke ack: {}", e),
+            }
+        }
+    }
+
+    /// Send completed local strokes again, as snapshots, to the peers that
+    /// haven't acknowledged them
+    fn resend_unacked_strokes(&mut self) {
+        let room_guard = self.room_service.lock();
+        let Some(room) = &*room_guard else {
+            self.stroke_acks.clear();
+            return;
+        };
+
+        let mut by_peer: HashMap<String, Vec<annotation::Stroke>> = HashMap::new();
+        for (stroke_id, peers) in self.stroke_acks.due(Instant::now()) {
+            let Some(stroke) = self.annotation_store.get(&stroke_id) else {
+                // Deleted or cleared since - nothing to deliver
+                self.stroke_acks.forget(&stroke_id);
+                continue;
+            };
+            for peer in peers {
+                by_peer.entry(peer).or_default().push(stroke.clone());
+            }
+        }
+
+        for (peer, strokes) in by_peer {
+            tracing::info!(
+                "Re-sending {} unacknowledged strokes to {}",
+                strokes.len(),
+                peer
+            );
+            for strokes in annotation::chunk_strokes(strokes, annotation::SNAPSHOT_CHUNK_BYTES) {
+                match serde_json::to_vec(&socket::DataTrackMessage::Snapshot { strokes }) {
+                    Ok(payload) => room.send_data_to(payload, &peer),
+                    Err(e) => tracing::error!("Failed to encode annotation snapshot: {}", e),
                 }
             }
         }
+        drop(room_guard);
+
+        if !self.stroke_acks.is_empty() {
+            self.schedule_stroke_resend();
+        }
+    }
+
+    /// Dispatch one `StrokeUpdate` per stroke with all points batched so far
+    fn flush_stroke_updates(&mut self) {
+        for (stroke_id, points) in self.stroke_updates.take() {
+            let _ = self
+                .event_loop_proxy
+                .send_event(UserEvent::StrokeUpdate { stroke_id, points });
+        }
     }
 
     fn handle_shutdown(&mut self) {
@@ -998,14 +3035,26 @@ impl Application {
         // Stop screen capture
         self.screen_capturer.lock().stop_capture();
 
-        // Disconnect from room (sync call - RoomService handles async internally)
+        // Stop watching permissions
+        self.permission_poller.take();
+
+        // Finalize any recording before exiting so the file stays playable
+        if let Some(recorder) = self.screen_capturer.lock().take_recorder() {
+            if let Err(e) = recorder.finish() {
+                tracing::error!("Failed to finalize recording: {}", e);
+            }
+        }
+
+        // Leave the room cleanly before the process exits
         if let Some(room) = self.room_service.lock().take() {
-            room.disconnect();
+            if room.close(SHUTDOWN_TIMEOUT) {
+                tracing::info!("Left room");
+            }
         }
 
-        // Close socket
+        // Close socket once queued messages are written
         if let Some(socket) = self.socket.lock().take() {
-            socket.shutdown();
+            socket.shutdown(SHUTDOWN_TIMEOUT);
         }
 
         tracing::info!("Core shutdown complete");
@@ -1037,6 +3086,51 @@ impl Application {
         }
     }
 
+    fn send_screen_share_paused(&self, paused: bool) {
+        if let Some(socket) = &*self.socket.lock() {
+            socket.send(OutgoingMessage::ScreenSharePauseChanged { paused });
+        }
+    }
+
+    fn send_capture_status(&self, msg: OutgoingMessage) {
+        if let Some(socket) = &*self.socket.lock() {
+            socket.send(msg);
+        }
+    }
+
+    fn handle_capture_resolution_changed(&mut self, resolution: Resolution) {
+        let previous = self.capture_resolution.replace(resolution);
+        if previous.is_some_and(|previous| previous != resolution) {
+            tracing::info!(
+                "Captured display resized to {}x{}",
+                resolution.width,
+                resolution.height
+            );
+            // Screen bounds in the cache are stale; the WebView re-fetches
+            // them after this notification
+            self.source_cache.invalidate();
+        }
+
+        if let Some(socket) = &*self.socket.lock() {
+            socket.send(OutgoingMessage::CaptureResolutionChanged {
+                width: resolution.width,
+                height: resolution.height,
+            });
+        }
+    }
+
+    fn send_published_resolution(&self, width: u32, height: u32) {
+        if let Some(socket) = &*self.socket.lock() {
+            socket.send(OutgoingMessage::PublishedResolutionChanged { width, height });
+        }
+    }
+
+    fn send_recording_state(&self, is_recording: bool, path: Option<String>) {
+        if let Some(socket) = &*self.socket.lock() {
+            socket.send(OutgoingMessage::RecordingStateChanged { is_recording, path });
+        }
+    }
+
     fn send_participant_joined(&self, data: &ParticipantData) {
         if let Some(socket) = &*self.socket.lock() {
             socket.send(OutgoingMessage::ParticipantJoined {
@@ -1045,6 +3139,26 @@ impl Application {
         }
     }
 
+    fn handle_participant_metadata_changed(&mut self, data: ParticipantData) {
+        let previous = if data.is_local {
+            self.local_participant.replace(data.clone())
+        } else {
+            self.participants.insert(data.id.clone(), data.clone())
+        };
+        if let Some(previous) = previous.filter(|p| p.role != data.role) {
+            tracing::info!(
+                "Participant {} role changed: {:?} -> {:?}",
+                data.id,
+                previous.role,
+                data.role
+            );
+        }
+
+        if let Some(socket) = &*self.socket.lock() {
+            socket.send(OutgoingMessage::ParticipantMetadataChanged { participant: data });
+        }
+    }
+
     fn send_participant_left(&self, participant_id: &str) {
         if let Some(socket) = &*self.socket.lock() {
             socket.send(OutgoingMessage::ParticipantLeft {
@@ -1053,6 +3167,57 @@ impl Application {
         }
     }
 
+    /// Report the share now in focus (after `share_focus` changed)
+    fn dispatch_focused_share(&self) {
+        let focused = self.share_focus.focused().cloned();
+        let _ = self
+            .event_loop_proxy
+            .send_event(UserEvent::FocusedShareChanged {
+                participant_id: focused.as_ref().map(|f| f.participant_id.clone()),
+                track_id: focused.map(|f| f.track_id),
+            });
+    }
+
+    fn send_remote_cursors(&mut self) {
+        self.cursors_changed = false;
+        if let Some(socket) = &*self.socket.lock() {
+            socket.send(OutgoingMessage::RemoteCursors {
+                cursors: self
+                    .remote_cursors
+                    .values()
+                    .map(RemoteCursor::state)
+                    .collect(),
+            });
+        }
+    }
+
+    fn send_active_speakers(&self, speakers: Vec<(String, f32)>) {
+        if let Some(socket) = &*self.socket.lock() {
+            socket.send(OutgoingMessage::ActiveSpeakersChanged {
+                speakers: speakers
+                    .into_iter()
+                    .map(|(participant_id, audio_level)| ActiveSpeaker {
+                        participant_id,
+                        audio_level,
+                    })
+                    .collect(),
+            });
+        }
+    }
+
+    fn send_connection_stats(&self, stats: ConnectionStats) {
+        if let Some(socket) = &*self.socket.lock() {
+            socket.send(OutgoingMessage::ConnectionStats {
+                outbound_bitrate: stats.outbound_bitrate,
+                packet_loss: stats.packet_loss,
+                rtt_ms: stats.rtt_ms,
+                fps: stats.fps,
+                resolution: stats.resolution,
+                outgoing_queue: socket.queue_stats(),
+            });
+        }
+    }
+
     fn send_connection_state(&self) {
         if let Some(socket) = &*self.socket.lock() {
             socket.send(OutgoingMessage::ConnectionStateChanged {
@@ -1077,19 +3242,26 @@ impl Application {
                 width,
                 height,
                 format,
-                timestamp: std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_millis() as u64,
+                timestamp: self.relay_clock.next(),
+                wall_timestamp: unix_millis(),
                 frame_data,
             });
         }
     }
 
-    fn send_error(&self, code: &str, message: &str) {
+    fn send_pong(&self, timestamp: Option<u64>) {
+        if let Some(socket) = &*self.socket.lock() {
+            socket.send(OutgoingMessage::Pong {
+                timestamp,
+                core_timestamp: unix_millis(),
+            });
+        }
+    }
+
+    fn send_error(&self, code: ErrorCode, message: &str) {
         if let Some(socket) = &*self.socket.lock() {
             socket.send(OutgoingMessage::Error {
-                code: code.to_string(),
+                code,
                 message: message.to_string(),
             });
         }
@@ -1118,6 +3290,42 @@ impl Application {
         });
     }
 
+    /// Request camera or microphone access, then report the full state
+    fn handle_request_media_permission(&self, request: fn() -> PermissionStatus) {
+        let proxy = self.event_loop_proxy.clone();
+
+        // Waits for the user to answer the system prompt
+        tokio::task::spawn_blocking(move || {
+            let _status = request();
+            let state = permissions::get_permission_state();
+            let _ = proxy.send_event(UserEvent::PermissionStateChanged(state));
+        });
+    }
+
+    /// Keep polling for changes while a needed permission is still missing
+    fn ensure_permission_poller(&mut self, state: PermissionState) {
+        let needed = self.needed_permissions;
+        if !state.has_pending(needed) {
+            self.permission_poller = None;
+            return;
+        }
+        // A poller started before the microphone or camera became needed
+        // would stop without waiting for them
+        if self
+            .permission_poller
+            .as_ref()
+            .is_some_and(|poller| poller.is_running() && poller.needed() == needed)
+        {
+            return;
+        }
+
+        self.permission_poller = None;
+        match permissions::PermissionPoller::start(state, needed, self.event_loop_proxy.clone()) {
+            Ok(poller) => self.permission_poller = Some(poller),
+            Err(e) => tracing::warn!("Failed to start permission poller: {}", e),
+        }
+    }
+
     fn send_permission_state(&self, state: &PermissionState) {
         if let Some(socket) = &*self.socket.lock() {
             socket.send(OutgoingMessage::PermissionState {
@@ -1126,3 +3334,11 @@ impl Application {
         }
     }
 }
+
+/// Milliseconds since the Unix epoch
+pub(crate) fn unix_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
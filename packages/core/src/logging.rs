@@ -0,0 +1,43 @@
+//! Log filtering that can be changed at runtime
+//!
+//! `init` installs the global subscriber with its `EnvFilter` behind a
+//! `reload` layer, so `set_filter` (driven by the `SetLogLevel` socket
+//! message) can raise or lower per-module levels without a restart, e.g.
+//! `"etch_core=debug,etch_core::room=trace"`.
+
+use std::sync::OnceLock;
+
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{reload, EnvFilter, Registry};
+
+/// Filter used when `RUST_LOG` is unset or invalid
+pub const DEFAULT_FILTER: &str = "etch_core=info";
+
+static FILTER_HANDLE: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+
+/// Install the global subscriber, filtered by `RUST_LOG`
+pub fn init() {
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_FILTER));
+    let (filter, handle) = reload::Layer::new(filter);
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer())
+        .init();
+
+    let _ = FILTER_HANDLE.set(handle);
+}
+
+/// Replace the active filter with `directives` (`RUST_LOG` syntax)
+///
+/// The current filter is kept if the directives don't parse, or if `init`
+/// was never called (e.g. Core embedded in another process).
+pub fn set_filter(directives: &str) -> Result<(), String> {
+    let filter = EnvFilter::try_new(directives).map_err(|e| e.to_string())?;
+    let handle = FILTER_HANDLE
+        .get()
+        .ok_or_else(|| "Logging was not initialized by Core".to_string())?;
+    handle.reload(filter).map_err(|e| e.to_string())
+}
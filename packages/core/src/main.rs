@@ -2,13 +2,17 @@
 //!
 //! This binary is spawned by the Tauri app and communicates via Unix socket.
 //! It owns all media: screen capture, LiveKit connection.
+//!
+//! Usage: `etch-core [--headless] [SOCKET_PATH]`. `--headless` (or
+//! `Etch_HEADLESS=1`) skips the winit event loop so Core can run without a
+//! display, e.g. as a server-side recorder.
 
 use std::env;
 use std::sync::Arc;
+use std::time::Instant;
 
-use etch_core::{Application, CoreSocket, UserEvent};
+use etch_core::{Application, CoreSocket, EventProxy, UserEvent};
 use parking_lot::Mutex;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 use winit::application::ApplicationHandler;
 use winit::event::WindowEvent;
 use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop, EventLoopProxy};
@@ -18,7 +22,7 @@ use winit::window::WindowId;
 struct AppHandler {
     app: Option<Application>,
     socket_path: String,
-    event_loop_proxy: EventLoopProxy<UserEvent>,
+    event_loop_proxy: EventProxy,
     initialized: bool,
     socket: Arc<Mutex<Option<CoreSocket>>>,
 }
@@ -28,7 +32,7 @@ impl AppHandler {
         Self {
             app: None,
             socket_path,
-            event_loop_proxy,
+            event_loop_proxy: event_loop_proxy.into(),
             initialized: false,
             socket: Arc::new(Mutex::new(None)),
         }
@@ -39,31 +43,10 @@ impl ApplicationHandler<UserEvent> for AppHandler {
     fn resumed(&mut self, _event_loop: &ActiveEventLoop) {
         if !self.initialized {
             // Create application on first resume, sharing the socket Arc
-            let proxy = self.event_loop_proxy.clone();
-            let app = Application::new(proxy.clone(), self.socket.clone());
-
-            // Initialize socket server
-            let socket_path = self.socket_path.clone();
-            let socket_holder = self.socket.clone();
-
-            tokio::spawn(async move {
-                // Small delay to ensure event loop is running
-                tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-
-                match CoreSocket::new(&socket_path, proxy.clone()).await {
-                    Ok(socket) => {
-                        tracing::info!("Socket server started on: {}", socket_path);
-                        *socket_holder.lock() = Some(socket);
-                    }
-                    Err(e) => {
-                        tracing::error!("Failed to start socket server: {}", e);
-                        let _ = proxy.send_event(UserEvent::Error {
-                            code: "socket_init_failed".to_string(),
-                            message: e.to_string(),
-                        });
-                    }
-                }
-            });
+            let mut app = Application::new(self.event_loop_proxy.clone(), self.socket.clone());
+
+            // Start the socket server - the event loop is already running here
+            app.init_socket(&self.socket_path);
 
             self.app = Some(app);
             self.initialized = true;
@@ -74,7 +57,10 @@ impl ApplicationHandler<UserEvent> for AppHandler {
 
     fn user_event(&mut self, event_loop: &ActiveEventLoop, event: UserEvent) {
         if let Some(app) = &mut self.app {
-            app.handle_user_event(event, event_loop);
+            app.handle_user_event(event);
+            if app.exit_requested() {
+                event_loop.exit();
+            }
         }
     }
 
@@ -87,27 +73,33 @@ impl ApplicationHandler<UserEvent> for AppHandler {
         // No windows managed by Core anymore - overlay rendering moved to Tauri WebView
     }
 
-    fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
-        // Called when event loop is about to wait for new events
-        // Can be used for idle processing
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        // Wake at frame rate while something animates, otherwise sleep
+        // until the next event
+        let next_frame = self
+            .app
+            .as_mut()
+            .and_then(|app| app.animate(Instant::now()));
+        event_loop.set_control_flow(match next_frame {
+            Some(next_frame) => ControlFlow::WaitUntil(next_frame),
+            None => ControlFlow::Wait,
+        });
     }
 }
 
 fn main() -> anyhow::Result<()> {
-    // Initialize tracing
-    tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "etch_core=info".into()),
-        )
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+    // Initialize tracing; the filter can be changed later with SetLogLevel
+    etch_core::logging::init();
 
     tracing::info!("Etch Core starting...");
 
+    let headless = env::args().any(|arg| arg == "--headless")
+        || env::var("Etch_HEADLESS").is_ok_and(|v| v == "1");
+
     // Get socket path from command line or environment
     let socket_path = env::args()
-        .nth(1)
+        .skip(1)
+        .find(|arg| arg != "--headless")
         .or_else(|| env::var("Etch_SOCKET_PATH").ok())
         .unwrap_or_else(|| {
             let pid = std::process::id();
@@ -131,6 +123,13 @@ fn main() -> anyhow::Result<()> {
     // Enter tokio runtime context
     let _guard = runtime.enter();
 
+    if headless {
+        tracing::info!("Running headless");
+        Application::run_headless(&socket_path);
+        tracing::info!("Etch Core exited");
+        return Ok(());
+    }
+
     // Create winit event loop with custom UserEvent
     let event_loop: EventLoop<UserEvent> = EventLoop::with_user_event().build()?;
     event_loop.set_control_flow(ControlFlow::Wait);
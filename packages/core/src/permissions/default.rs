@@ -13,6 +13,16 @@ pub fn request_screen_recording() -> PermissionStatus {
     PermissionStatus::NotApplicable
 }
 
+/// Request microphone permission - no-op for unsupported platforms.
+pub fn request_microphone() -> PermissionStatus {
+    PermissionStatus::NotApplicable
+}
+
+/// Request camera permission - no-op for unsupported platforms.
+pub fn request_camera() -> PermissionStatus {
+    PermissionStatus::NotApplicable
+}
+
 /// Get current permission state.
 pub fn get_permission_state() -> PermissionState {
     PermissionState::default()
@@ -58,6 +58,20 @@ pub fn request_screen_recording() -> PermissionStatus {
     }
 }
 
+/// Request microphone permission.
+///
+/// On Linux, camera and microphone access is not gated by a system prompt.
+pub fn request_microphone() -> PermissionStatus {
+    PermissionStatus::NotApplicable
+}
+
+/// Request camera permission.
+///
+/// On Linux, camera and microphone access is not gated by a system prompt.
+pub fn request_camera() -> PermissionStatus {
+    PermissionStatus::NotApplicable
+}
+
 /// Get current permission state.
 pub fn get_permission_state() -> PermissionState {
     PermissionState {
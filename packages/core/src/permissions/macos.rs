@@ -1,8 +1,15 @@
 //! macOS-specific permission handling.
 //!
-//! Uses Core Graphics for screen recording permission checks.
+//! Uses Core Graphics for screen recording permission checks and
+//! AVFoundation (`AVCaptureDevice`) for camera and microphone.
 
+use std::sync::mpsc;
+
+use block2::RcBlock;
 use core_graphics::access::ScreenCaptureAccess;
+use objc2::msg_send;
+use objc2::runtime::{AnyClass, Bool};
+use objc2_foundation::{NSInteger, NSString};
 
 use super::{PermissionState, PermissionStatus};
 
@@ -43,13 +50,116 @@ pub fn request_screen_recording() -> PermissionStatus {
     }
 }
 
+#[link(name = "AVFoundation", kind = "framework")]
+extern "C" {
+    static AVMediaTypeAudio: &'static NSString;
+    static AVMediaTypeVideo: &'static NSString;
+}
+
+/// `AVAuthorizationStatus` values
+const AV_AUTHORIZATION_NOT_DETERMINED: NSInteger = 0;
+const AV_AUTHORIZATION_RESTRICTED: NSInteger = 1;
+const AV_AUTHORIZATION_DENIED: NSInteger = 2;
+const AV_AUTHORIZATION_AUTHORIZED: NSInteger = 3;
+
+fn capture_device_class() -> Option<&'static AnyClass> {
+    AnyClass::get("AVCaptureDevice")
+}
+
+/// Query `[AVCaptureDevice authorizationStatusForMediaType:]`.
+fn check_media_access(media_type: &NSString) -> PermissionStatus {
+    let Some(class) = capture_device_class() else {
+        tracing::warn!("AVCaptureDevice unavailable");
+        return PermissionStatus::NotApplicable;
+    };
+
+    let status: NSInteger =
+        unsafe { msg_send![class, authorizationStatusForMediaType: media_type] };
+    match status {
+        AV_AUTHORIZATION_AUTHORIZED => PermissionStatus::Granted,
+        AV_AUTHORIZATION_DENIED => PermissionStatus::Denied,
+        AV_AUTHORIZATION_RESTRICTED => PermissionStatus::Restricted,
+        AV_AUTHORIZATION_NOT_DETERMINED => PermissionStatus::NotDetermined,
+        other => {
+            tracing::warn!("Unknown AVAuthorizationStatus: {}", other);
+            PermissionStatus::NotDetermined
+        }
+    }
+}
+
+/// Call `[AVCaptureDevice requestAccessForMediaType:completionHandler:]`.
+///
+/// Prompts only when the status is not determined yet - otherwise the
+/// completion handler fires immediately with the stored decision. Blocks
+/// until the user answers the prompt.
+fn request_media_access(media_type: &NSString) -> PermissionStatus {
+    let current = check_media_access(media_type);
+    if current != PermissionStatus::NotDetermined {
+        return current;
+    }
+    let Some(class) = capture_device_class() else {
+        return PermissionStatus::NotApplicable;
+    };
+
+    let (tx, rx) = mpsc::channel();
+    let handler = RcBlock::new(move |granted: Bool| {
+        let _ = tx.send(granted.as_bool());
+    });
+    unsafe {
+        let _: () = msg_send![
+            class,
+            requestAccessForMediaType: media_type,
+            completionHandler: &*handler
+        ];
+    }
+
+    match rx.recv() {
+        Ok(true) => PermissionStatus::Granted,
+        Ok(false) => check_media_access(media_type),
+        Err(_) => PermissionStatus::NotDetermined,
+    }
+}
+
+/// Check microphone permission on macOS.
+///
+/// Uses `AVCaptureDevice.authorizationStatus(for: .audio)`. Does NOT prompt.
+pub fn check_microphone() -> PermissionStatus {
+    tracing::debug!("Checking macOS microphone permission");
+    check_media_access(unsafe { AVMediaTypeAudio })
+}
+
+/// Check camera permission on macOS.
+///
+/// Uses `AVCaptureDevice.authorizationStatus(for: .video)`. Does NOT prompt.
+pub fn check_camera() -> PermissionStatus {
+    tracing::debug!("Checking macOS camera permission");
+    check_media_access(unsafe { AVMediaTypeVideo })
+}
+
+/// Request microphone permission on macOS.
+///
+/// Shows the system prompt if the user hasn't decided yet and waits for
+/// the answer. Returns the resulting status.
+pub fn request_microphone() -> PermissionStatus {
+    tracing::info!("Requesting macOS microphone permission");
+    request_media_access(unsafe { AVMediaTypeAudio })
+}
+
+/// Request camera permission on macOS.
+///
+/// Shows the system prompt if the user hasn't decided yet and waits for
+/// the answer. Returns the resulting status.
+pub fn request_camera() -> PermissionStatus {
+    tracing::info!("Requesting macOS camera permission");
+    request_media_access(unsafe { AVMediaTypeVideo })
+}
+
 /// Check all permissions and return the current state.
 pub fn get_permission_state() -> PermissionState {
     PermissionState {
         screen_recording: check_screen_recording(),
-        // Camera and microphone are handled by LiveKit/AVFoundation at connection time
-        microphone: PermissionStatus::NotApplicable,
-        camera: PermissionStatus::NotApplicable,
+        microphone: check_microphone(),
+        camera: check_camera(),
         // Accessibility is only needed for remote control (future feature)
         accessibility: PermissionStatus::NotApplicable,
     }
@@ -8,6 +8,16 @@
 //! - **macOS**: Uses Core Graphics for screen recording, AVFoundation for camera/mic
 //! - **Linux**: Uses Wayland/X11 detection (permissions vary by desktop environment)
 //! - **Windows**: Returns `true` (permissions handled at OS level during first use)
+//!
+//! Permissions can be granted outside the app (e.g. in System Settings), so
+//! while one the session needs is still missing a `PermissionPoller`
+//! re-checks them and reports changes as they happen.
+
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crate::{EventProxy, UserEvent};
 
 #[cfg(target_os = "macos")]
 mod macos;
@@ -47,7 +57,7 @@ pub enum PermissionStatus {
 }
 
 /// All permission statuses for the application
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct PermissionState {
     pub screen_recording: PermissionStatus,
     pub microphone: PermissionStatus,
@@ -65,3 +75,112 @@ impl Default for PermissionState {
         }
     }
 }
+
+/// Permissions the session relies on
+///
+/// Screen recording always is. The microphone and camera only count once
+/// they have been requested or turned on, so a user who never uses them
+/// doesn't keep the poller running.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NeededPermissions {
+    pub microphone: bool,
+    pub camera: bool,
+}
+
+impl PermissionState {
+    /// Whether a needed permission is still missing and may be granted later
+    pub fn has_pending(&self, needed: NeededPermissions) -> bool {
+        [
+            (self.screen_recording, true),
+            (self.microphone, needed.microphone),
+            (self.camera, needed.camera),
+            (self.accessibility, true),
+        ]
+        .iter()
+        .any(|&(status, needed)| {
+            needed
+                && matches!(
+                    status,
+                    PermissionStatus::Denied | PermissionStatus::NotDetermined
+                )
+        })
+    }
+}
+
+/// Interval between permission checks while something needed is missing
+const PERMISSION_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Background poller reporting permission changes
+///
+/// Re-checks every `PERMISSION_POLL_INTERVAL` and dispatches
+/// `UserEvent::PermissionStateChanged` only when the state differs from the
+/// last one seen. Exits on its own once nothing needed is pending; stops when
+/// dropped.
+pub struct PermissionPoller {
+    needed: NeededPermissions,
+    stop_tx: Option<mpsc::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl PermissionPoller {
+    /// Start polling, treating `initial` as the state already reported
+    pub fn start(
+        initial: PermissionState,
+        needed: NeededPermissions,
+        event_proxy: EventProxy,
+    ) -> std::io::Result<Self> {
+        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+
+        let thread = std::thread::Builder::new()
+            .name("permission-poller".to_string())
+            .spawn(move || {
+                let mut last = initial;
+                while let Err(RecvTimeoutError::Timeout) =
+                    stop_rx.recv_timeout(PERMISSION_POLL_INTERVAL)
+                {
+                    let state = get_permission_state();
+                    if state != last {
+                        tracing::info!("Permission state changed: {:?}", state);
+                        let sent = event_proxy
+                            .send_event(UserEvent::PermissionStateChanged(state.clone()));
+                        if sent.is_err() {
+                            break;
+                        }
+                        last = state;
+                    }
+
+                    if !last.has_pending(needed) {
+                        tracing::debug!("Needed permissions granted - stopping poller");
+                        break;
+                    }
+                }
+            })?;
+
+        Ok(Self {
+            needed,
+            stop_tx: Some(stop_tx),
+            thread: Some(thread),
+        })
+    }
+
+    /// Permissions the poller waits for
+    pub fn needed(&self) -> NeededPermissions {
+        self.needed
+    }
+
+    /// Whether the poller is still checking
+    pub fn is_running(&self) -> bool {
+        self.thread
+            .as_ref()
+            .is_some_and(|thread| !thread.is_finished())
+    }
+}
+
+impl Drop for PermissionPoller {
+    fn drop(&mut self) {
+        self.stop_tx.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
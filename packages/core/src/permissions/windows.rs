@@ -21,6 +21,20 @@ pub fn request_screen_recording() -> PermissionStatus {
     PermissionStatus::Granted
 }
 
+/// Request microphone permission.
+///
+/// Windows privacy settings are enforced when the device is opened.
+pub fn request_microphone() -> PermissionStatus {
+    PermissionStatus::NotApplicable
+}
+
+/// Request camera permission.
+///
+/// Windows privacy settings are enforced when the device is opened.
+pub fn request_camera() -> PermissionStatus {
+    PermissionStatus::NotApplicable
+}
+
 /// Get current permission state.
 pub fn get_permission_state() -> PermissionState {
     PermissionState {
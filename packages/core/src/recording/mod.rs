@@ -0,0 +1,289 @@
+//! Local recording of the shared screen
+//!
+//! Taps the I420 frames produced by the capture loop and writes them to an
+//! H.264 MP4 file. Encoding and muxing are delegated to an `ffmpeg` process
+//! fed raw I420 over stdin, so no codec is linked into Core. The binary is
+//! looked up on `PATH`, or taken from `ETCH_FFMPEG_PATH` when set.
+//!
+//! Recording is independent of the LiveKit room: frames are recorded while
+//! capture runs, whether or not they are also being published.
+//!
+//! The encoder is started on the first frame, once the capture resolution is
+//! known. Frames are handed to a writer thread through a bounded queue and
+//! dropped when the encoder falls behind, so a slow disk never stalls
+//! capture. Timestamps come from the wall clock, so dropped frames and the
+//! variable capture rate do not shift the recording's timeline.
+//!
+//! Closing ffmpeg's stdin makes it flush the encoder and write the MP4 index;
+//! `Recorder::finish` does that and waits for the file to be complete.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, ExitStatus, Stdio};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::thread::JoinHandle;
+
+use livekit::webrtc::prelude::{I420Buffer, VideoBuffer};
+
+/// Environment variable overriding the ffmpeg binary
+const FFMPEG_PATH_ENV: &str = "ETCH_FFMPEG_PATH";
+
+/// Frames buffered between the capture thread and the encoder (~1s at 45fps)
+const RECORDING_QUEUE_FRAMES: usize = 45;
+
+#[derive(Debug, thiserror::Error)]
+pub enum RecordingError {
+    #[error("Recording directory does not exist: {0}")]
+    InvalidPath(String),
+
+    #[error("ffmpeg not available: {0}")]
+    EncoderUnavailable(String),
+
+    #[error("Encoder failed: {0}")]
+    EncoderFailed(String),
+
+    #[error("No frames were recorded")]
+    NoFrames,
+}
+
+/// An in-progress recording to a single MP4 file
+pub struct Recorder {
+    path: PathBuf,
+    encoder: Option<Encoder>,
+    /// Set once the encoder fails - later frames are ignored
+    error: Option<String>,
+    dropped_frames: u64,
+}
+
+impl Recorder {
+    /// Prepare a recording to `path`
+    ///
+    /// Checks that the destination directory exists and that ffmpeg can be
+    /// run, so configuration problems surface on `StartRecording` rather
+    /// than on the first frame.
+    pub fn new(path: impl Into<PathBuf>) -> Result<Self, RecordingError> {
+        let path = path.into();
+
+        let parent = path
+            .parent()
+            .filter(|p| !p.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        if !parent.is_dir() {
+            return Err(RecordingError::InvalidPath(parent.display().to_string()));
+        }
+
+        let probe = Command::new(ffmpeg_binary())
+            .arg("-version")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .map_err(|e| RecordingError::EncoderUnavailable(e.to_string()))?;
+        if !probe.success() {
+            return Err(RecordingError::EncoderUnavailable(format!(
+                "ffmpeg -version exited with {}",
+                probe
+            )));
+        }
+
+        Ok(Self {
+            path,
+            encoder: None,
+            error: None,
+            dropped_frames: 0,
+        })
+    }
+
+    /// Destination file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Queue a captured frame for encoding
+    ///
+    /// Called from the capture thread; never blocks on the encoder.
+    pub fn push_frame(&mut self, buffer: &I420Buffer) {
+        if self.error.is_some() {
+            return;
+        }
+
+        let width = buffer.width();
+        let height = buffer.height();
+
+        let encoder = match &mut self.encoder {
+            Some(encoder) => encoder,
+            None => match Encoder::spawn(&self.path, width, height) {
+                Ok(encoder) => {
+                    tracing::info!("Recording {}x{} to {}", width, height, self.path.display());
+                    self.encoder.insert(encoder)
+                }
+                Err(e) => {
+                    tracing::error!("Failed to start recording encoder: {}", e);
+                    self.error = Some(e.to_string());
+                    return;
+                }
+            },
+        };
+
+        // The raw stream has a fixed size - frames after a resolution change
+        // cannot be encoded into the same file
+        if (width, height) != (encoder.width, encoder.height) {
+            self.dropped_frames += 1;
+            if self.dropped_frames == 1 {
+                tracing::warn!(
+                    "Capture resolution changed to {}x{} - dropping frames from recording ({}x{})",
+                    width,
+                    height,
+                    encoder.width,
+                    encoder.height
+                );
+            }
+            return;
+        }
+
+        match encoder.frame_tx.try_send(pack_i420(buffer)) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => {
+                self.dropped_frames += 1;
+                tracing::debug!("Recording encoder behind, dropped frame");
+            }
+            Err(TrySendError::Disconnected(_)) => {
+                tracing::error!("Recording encoder exited unexpectedly");
+                self.error = Some("Encoder exited unexpectedly".to_string());
+            }
+        }
+    }
+
+    /// Flush the encoder and wait for the file to be finalized
+    pub fn finish(self) -> Result<PathBuf, RecordingError> {
+        let Some(encoder) = self.encoder else {
+            return Err(match self.error {
+                Some(e) => RecordingError::EncoderFailed(e),
+                None => RecordingError::NoFrames,
+            });
+        };
+
+        if self.dropped_frames > 0 {
+            tracing::warn!(
+                "Recording dropped {} frames (encoder backlog or resolution change)",
+                self.dropped_frames
+            );
+        }
+
+        let status = encoder.finish()?;
+        if !status.success() {
+            return Err(RecordingError::EncoderFailed(format!(
+                "ffmpeg exited with {}",
+                status
+            )));
+        }
+
+        tracing::info!("Recording finalized: {}", self.path.display());
+        Ok(self.path)
+    }
+}
+
+/// Running ffmpeg process and the thread feeding it
+struct Encoder {
+    width: u32,
+    height: u32,
+    frame_tx: SyncSender<Vec<u8>>,
+    writer: JoinHandle<std::io::Result<ExitStatus>>,
+}
+
+impl Encoder {
+    fn spawn(path: &Path, width: u32, height: u32) -> Result<Self, RecordingError> {
+        let mut child = Command::new(ffmpeg_binary())
+            .args(["-hide_banner", "-loglevel", "error", "-y"])
+            // Input: raw I420 on stdin, timestamped on arrival
+            .args(["-use_wallclock_as_timestamps", "1"])
+            .args(["-f", "rawvideo", "-pix_fmt", "yuv420p"])
+            .args(["-s", &format!("{}x{}", width, height)])
+            .args(["-i", "pipe:0"])
+            // Output: H.264 in MP4, even dimensions as required by yuv420p
+            .args(["-vf", "pad=ceil(iw/2)*2:ceil(ih/2)*2"])
+            .args(["-c:v", "libx264", "-preset", "veryfast", "-crf", "23"])
+            .args(["-pix_fmt", "yuv420p", "-vsync", "vfr"])
+            .args(["-movflags", "+faststart"])
+            .arg(path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|e| RecordingError::EncoderUnavailable(e.to_string()))?;
+
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| RecordingError::EncoderFailed("ffmpeg stdin unavailable".to_string()))?;
+
+        let (frame_tx, frame_rx) = mpsc::sync_channel(RECORDING_QUEUE_FRAMES);
+        let writer = std::thread::Builder::new()
+            .name("recording-writer".to_string())
+            .spawn(move || write_frames(child, stdin, frame_rx))
+            .map_err(|e| RecordingError::EncoderFailed(e.to_string()))?;
+
+        Ok(Self {
+            width,
+            height,
+            frame_tx,
+            writer,
+        })
+    }
+
+    /// Close the frame queue and wait for ffmpeg to exit
+    fn finish(self) -> Result<ExitStatus, RecordingError> {
+        drop(self.frame_tx);
+        self.writer
+            .join()
+            .map_err(|_| RecordingError::EncoderFailed("Writer thread panicked".to_string()))?
+            .map_err(|e| RecordingError::EncoderFailed(e.to_string()))
+    }
+}
+
+/// Pipe queued frames into ffmpeg until the queue closes, then let it finalize
+fn write_frames(
+    mut child: Child,
+    mut stdin: ChildStdin,
+    frame_rx: mpsc::Receiver<Vec<u8>>,
+) -> std::io::Result<ExitStatus> {
+    for frame in frame_rx {
+        if let Err(e) = stdin.write_all(&frame) {
+            tracing::error!("Failed to write frame to encoder: {}", e);
+            break;
+        }
+    }
+
+    // EOF on stdin tells ffmpeg to flush and write the MP4 index
+    drop(stdin);
+    child.wait()
+}
+
+/// Copy an I420 buffer into tightly packed Y, U, V planes
+fn pack_i420(buffer: &I420Buffer) -> Vec<u8> {
+    let width = buffer.width() as usize;
+    let height = buffer.height() as usize;
+    let chroma_width = buffer.chroma_width() as usize;
+    let chroma_height = buffer.chroma_height() as usize;
+    let (stride_y, stride_u, stride_v) = buffer.strides();
+    let (data_y, data_u, data_v) = buffer.data();
+
+    let mut packed = Vec::with_capacity(width * height + 2 * chroma_width * chroma_height);
+    let planes = [
+        (data_y, stride_y as usize, width, height),
+        (data_u, stride_u as usize, chroma_width, chroma_height),
+        (data_v, stride_v as usize, chroma_width, chroma_height),
+    ];
+    for (data, stride, row_len, rows) in planes {
+        for row in data.chunks(stride).take(rows) {
+            packed.extend_from_slice(&row[..row_len]);
+        }
+    }
+    packed
+}
+
+fn ffmpeg_binary() -> PathBuf {
+    std::env::var_os(FFMPEG_PATH_ENV)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("ffmpeg"))
+}
@@ -0,0 +1,259 @@
+//! Remote video frame relay
+//!
+//! Subscribed remote video tracks (screen shares, cameras) are decoded by
+//! LiveKit into I420 frames. This module attaches a sink to those tracks,
+//! converts frames into a format the WebView can display, and dispatches
+//! them to the event loop as `UserEvent::VideoFrameReady`.
+//!
+//! Frames are throttled to `RELAY_MAX_FPS` - the WebView is a preview
+//! surface, not a playback pipeline, and every relayed frame costs a
+//! conversion, an encode and a trip over the socket.
+//!
+//! Conversion runs through libyuv into buffers reused across frames; only
+//! the payload handed to the event loop is allocated per frame. Clients that
+//! can decode NV12 themselves skip the RGB conversion entirely. It runs on a
+//! thread per track, so a full-resolution conversion and JPEG encode never
+//! hold up the LiveKit runtime the signaling and WebRTC tasks share.
+
+use std::io::Cursor;
+use std::sync::mpsc::{self, Receiver, TrySendError};
+use std::time::{Duration, Instant};
+
+use futures_util::StreamExt;
+use livekit::prelude::RemoteVideoTrack;
+use livekit::webrtc::native::yuv_helper;
+use livekit::webrtc::prelude::{I420Buffer, VideoBuffer};
+use livekit::webrtc::video_stream::native::NativeVideoStream;
+
+use crate::{EventProxy, FrameFormat, UserEvent};
+
+/// Maximum number of frames per second relayed to the WebView per track
+const RELAY_MAX_FPS: u64 = 15;
+
+/// JPEG quality for relayed frames (0-100)
+const RELAY_JPEG_QUALITY: u8 = 70;
+
+/// Format used for relayed frames unless `JoinRoom` asks for another
+pub const DEFAULT_RELAY_FORMAT: FrameFormat = FrameFormat::Jpeg;
+
+/// Formats frames can be relayed in (see `FrameFormat` for the tradeoffs)
+pub const RELAY_FORMATS: [FrameFormat; 3] =
+    [FrameFormat::Jpeg, FrameFormat::Rgba, FrameFormat::Nv12];
+
+/// Relay frames from a subscribed remote video track until the track ends
+///
+/// Runs on the LiveKit runtime, only pulling frames off the track; they are
+/// converted and dispatched on a `video-relay` thread, which exits once this
+/// task ends. The task is aborted by the room event handler when the track
+/// is unsubscribed.
+pub async fn relay_video_track(
+    track: RemoteVideoTrack,
+    participant_id: String,
+    format: FrameFormat,
+    event_proxy: EventProxy,
+) {
+    let track_id = track.sid().to_string();
+
+    tracing::info!(
+        "Relaying video track {} from {} as {:?}",
+        track_id,
+        participant_id,
+        format
+    );
+
+    // At most one frame waits for the converter; the sender is dropped
+    // with this task, which ends the thread
+    let (frames_tx, frames_rx) = mpsc::sync_channel::<I420Buffer>(1);
+    let converter = {
+        let participant_id = participant_id.clone();
+        let track_id = track_id.clone();
+        std::thread::Builder::new()
+            .name("video-relay".to_string())
+            .spawn(move || relay_frames(frames_rx, participant_id, track_id, format, event_proxy))
+    };
+    if let Err(e) = converter {
+        tracing::error!("Failed to start video relay for track {}: {}", track_id, e);
+        return;
+    }
+
+    let mut stream = NativeVideoStream::new(track.rtc_track());
+    let min_interval = Duration::from_millis(1000 / RELAY_MAX_FPS);
+    let mut last_sent: Option<Instant> = None;
+
+    while let Some(frame) = stream.next().await {
+        // Throttle - drop frames arriving faster than RELAY_MAX_FPS
+        if last_sent.is_some_and(|sent| sent.elapsed() < min_interval) {
+            continue;
+        }
+
+        match frames_tx.try_send(frame.buffer.to_i420()) {
+            Ok(()) => last_sent = Some(Instant::now()),
+            // Still converting the previous frame - drop this one
+            Err(TrySendError::Full(_)) => {}
+            // Event loop is gone - nobody to relay to
+            Err(TrySendError::Disconnected(_)) => break,
+        }
+    }
+
+    stream.close();
+    tracing::info!("Video relay ended for track {}", track_id);
+}
+
+/// Convert frames and dispatch them to the event loop until the relay task
+/// drops its sender or the event loop is gone
+fn relay_frames(
+    frames: Receiver<I420Buffer>,
+    participant_id: String,
+    track_id: String,
+    format: FrameFormat,
+    event_proxy: EventProxy,
+) {
+    let mut converter = FrameConverter::default();
+
+    for i420 in frames {
+        let width = i420.width();
+        let height = i420.height();
+        if width == 0 || height == 0 {
+            continue;
+        }
+
+        let Some(frame_data) = converter.convert(&i420, format) else {
+            continue;
+        };
+
+        let sent = event_proxy.send_event(UserEvent::VideoFrameReady {
+            participant_id: participant_id.clone(),
+            track_id: track_id.clone(),
+            frame_data,
+            width,
+            height,
+            format,
+        });
+        if sent.is_err() {
+            break;
+        }
+    }
+}
+
+/// Per-track conversion state, reused across frames
+#[derive(Default)]
+struct FrameConverter {
+    /// RGBA scratch for JPEG encoding
+    rgba: Vec<u8>,
+    /// RGB scratch for JPEG encoding
+    rgb: Vec<u8>,
+}
+
+impl FrameConverter {
+    /// Convert a frame into the relay payload for `format`
+    fn convert(&mut self, buffer: &I420Buffer, format: FrameFormat) -> Option<Vec<u8>> {
+        let width = buffer.width();
+        let height = buffer.height();
+
+        match format {
+            FrameFormat::Jpeg => {
+                self.convert_rgb(buffer);
+                encode_jpeg(&self.rgb, width, height, RELAY_JPEG_QUALITY)
+            }
+            FrameFormat::Rgba => {
+                // The payload is owned by the event, so convert straight into it
+                let mut rgba = vec![0u8; (width * height * 4) as usize];
+                i420_to_rgba_into(buffer, &mut rgba);
+                Some(rgba)
+            }
+            FrameFormat::Nv12 => Some(i420_to_nv12(buffer)),
+        }
+    }
+
+    /// Convert a frame into tightly packed RGB in `self.rgb`
+    fn convert_rgb(&mut self, buffer: &I420Buffer) {
+        let width = buffer.width();
+        let height = buffer.height();
+        let pixels = (width * height) as usize;
+        self.rgba.resize(pixels * 4, 0);
+        self.rgb.resize(pixels * 3, 0);
+
+        i420_to_rgba_into(buffer, &mut self.rgba);
+        // JPEG has no alpha channel - drop it. libyuv's "RGB24" keeps
+        // the first three bytes of each "ARGB" pixel, which for our
+        // R,G,B,A data is exactly R,G,B.
+        yuv_helper::argb_to_rgb24(
+            &self.rgba,
+            width * 4,
+            &mut self.rgb,
+            width * 3,
+            width as i32,
+            height as i32,
+        );
+    }
+}
+
+/// Encode a single I420 frame as JPEG at `quality` (0-100)
+pub fn encode_i420_jpeg(buffer: &I420Buffer, quality: u8) -> Option<Vec<u8>> {
+    let mut converter = FrameConverter::default();
+    converter.convert_rgb(buffer);
+    encode_jpeg(&converter.rgb, buffer.width(), buffer.height(), quality)
+}
+
+/// Convert an I420 buffer into tightly packed RGBA
+fn i420_to_rgba_into(buffer: &I420Buffer, rgba: &mut [u8]) {
+    let width = buffer.width();
+    let height = buffer.height();
+    let (stride_y, stride_u, stride_v) = buffer.strides();
+    let (data_y, data_u, data_v) = buffer.data();
+
+    // libyuv names formats by word order: "ABGR" is R,G,B,A in memory
+    yuv_helper::i420_to_abgr(
+        data_y,
+        stride_y,
+        data_u,
+        stride_u,
+        data_v,
+        stride_v,
+        rgba,
+        width * 4,
+        width as i32,
+        height as i32,
+    );
+}
+
+/// Repack an I420 buffer as tightly packed NV12
+///
+/// Only the chroma planes are interleaved - no color conversion. Layout is
+/// the Y plane (`width * height`) followed by the UV plane
+/// (`chroma_width * 2 * chroma_height`, chroma dimensions rounded up).
+fn i420_to_nv12(buffer: &I420Buffer) -> Vec<u8> {
+    let width = buffer.width() as usize;
+    let height = buffer.height() as usize;
+    let chroma_width = buffer.chroma_width() as usize;
+    let chroma_height = buffer.chroma_height() as usize;
+    let (stride_y, stride_u, stride_v) = buffer.strides();
+    let (data_y, data_u, data_v) = buffer.data();
+
+    let mut nv12 = Vec::with_capacity(width * height + chroma_width * chroma_height * 2);
+    for row in data_y.chunks(stride_y as usize).take(height) {
+        nv12.extend_from_slice(&row[..width]);
+    }
+    let u_rows = data_u.chunks(stride_u as usize);
+    let v_rows = data_v.chunks(stride_v as usize);
+    for (u_row, v_row) in u_rows.zip(v_rows).take(chroma_height) {
+        for (&u, &v) in u_row[..chroma_width].iter().zip(&v_row[..chroma_width]) {
+            nv12.push(u);
+            nv12.push(v);
+        }
+    }
+    nv12
+}
+
+/// Encode tightly packed RGB pixels as JPEG
+fn encode_jpeg(rgb: &[u8], width: u32, height: u32, quality: u8) -> Option<Vec<u8>> {
+    let mut jpeg_buffer = Cursor::new(Vec::new());
+    let mut encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(&mut jpeg_buffer, quality);
+
+    if let Err(e) = encoder.encode(rgb, width, height, image::ExtendedColorType::Rgb8) {
+        tracing::warn!("Failed to encode frame as JPEG: {}", e);
+        return None;
+    }
+
+    Some(jpeg_buffer.into_inner())
+}
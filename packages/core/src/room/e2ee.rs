@@ -0,0 +1,76 @@
+//! End-to-end encryption for DataTrack payloads
+//!
+//! Annotation and cursor messages are sealed with ChaCha20-Poly1305 before
+//! they are published, so the LiveKit server only relays ciphertext.
+//!
+//! The key is derived from a room-wide passphrase supplied at `JoinRoom`
+//! (PBKDF2-HMAC-SHA256). Every participant using the same passphrase derives
+//! the same key. Sealed payloads are laid out as `[12-byte nonce][ciphertext]`
+//! with a random nonce per message.
+
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use sha2::Sha256;
+
+/// Nonce length for ChaCha20-Poly1305
+const NONCE_LEN: usize = 12;
+
+/// Fixed salt - the passphrase is shared room-wide, so the salt only needs to
+/// separate Etch keys from other uses of the same passphrase
+const KDF_SALT: &[u8] = b"etch-datatrack-e2ee-v1";
+
+/// PBKDF2 iterations (runs once per join)
+const KDF_ROUNDS: u32 = 100_000;
+
+#[derive(Debug, thiserror::Error)]
+pub enum E2eeError {
+    #[error("Encrypted payload too short")]
+    TooShort,
+    #[error("Failed to encrypt payload")]
+    Encrypt,
+    #[error("Failed to decrypt payload (wrong passphrase or tampered data)")]
+    Decrypt,
+}
+
+/// Symmetric cipher for DataTrack payloads
+pub struct DataCipher {
+    cipher: ChaCha20Poly1305,
+}
+
+impl DataCipher {
+    /// Derive the room key from a shared passphrase
+    pub fn from_passphrase(passphrase: &str) -> Self {
+        let mut key = [0u8; 32];
+        pbkdf2::pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), KDF_SALT, KDF_ROUNDS, &mut key);
+
+        Self {
+            cipher: ChaCha20Poly1305::new(Key::from_slice(&key)),
+        }
+    }
+
+    /// Encrypt a payload for publishing
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, E2eeError> {
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext)
+            .map_err(|_| E2eeError::Encrypt)?;
+
+        let mut sealed = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+        sealed.extend_from_slice(&nonce);
+        sealed.extend_from_slice(&ciphertext);
+        Ok(sealed)
+    }
+
+    /// Decrypt a received payload
+    pub fn decrypt(&self, sealed: &[u8]) -> Result<Vec<u8>, E2eeError> {
+        if sealed.len() < NONCE_LEN {
+            return Err(E2eeError::TooShort);
+        }
+
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        self.cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| E2eeError::Decrypt)
+    }
+}
@@ -0,0 +1,118 @@
+//! Which remote screen share to spotlight
+//!
+//! With several people sharing, the share shown is the one belonging to the
+//! loudest active speaker. When no sharer is speaking the current focus is
+//! kept, and a new share takes focus only if nothing is focused. A focused
+//! share is held for `FOCUS_HOLD` before speech can move focus elsewhere,
+//! so two presenters talking over each other don't make the view flicker.
+
+use std::time::{Duration, Instant};
+
+/// Shortest time a share keeps focus before a speaker change can move it
+pub const FOCUS_HOLD: Duration = Duration::from_secs(2);
+
+/// A remote screen share track
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FocusedShare {
+    pub participant_id: String,
+    pub track_id: String,
+}
+
+/// Tracks remote screen shares and picks the one to focus
+#[derive(Debug, Default)]
+pub struct ShareFocus {
+    /// Published screen shares, oldest first
+    shares: Vec<FocusedShare>,
+    focused: Option<FocusedShare>,
+    focused_at: Option<Instant>,
+}
+
+impl ShareFocus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn focused(&self) -> Option<&FocusedShare> {
+        self.focused.as_ref()
+    }
+
+    /// Whether the participant has a screen share published
+    pub fn is_sharing(&self, participant_id: &str) -> bool {
+        self.shares
+            .iter()
+            .any(|s| s.participant_id == participant_id)
+    }
+
+    /// A screen share track was published; true if focus changed
+    pub fn share_added(&mut self, share: FocusedShare, now: Instant) -> bool {
+        self.shares.retain(|s| s.track_id != share.track_id);
+        self.shares.push(share.clone());
+        if self.focused.is_none() {
+            return self.focus(Some(share), now);
+        }
+        false
+    }
+
+    /// A track was unpublished; true if focus changed
+    pub fn track_removed(&mut self, track_id: &str, now: Instant) -> bool {
+        self.shares.retain(|s| s.track_id != track_id);
+        self.refocus_if_gone(now)
+    }
+
+    /// A participant left, taking their shares with them; true if focus changed
+    pub fn participant_left(&mut self, participant_id: &str, now: Instant) -> bool {
+        self.shares.retain(|s| s.participant_id != participant_id);
+        self.refocus_if_gone(now)
+    }
+
+    /// Active speakers changed (loudest first); true if focus changed
+    pub fn speakers_changed(&mut self, speakers: &[(String, f32)], now: Instant) -> bool {
+        let held = self
+            .focused_at
+            .is_some_and(|at| now.saturating_duration_since(at) < FOCUS_HOLD);
+        if held {
+            return false;
+        }
+
+        let dominant = speakers.iter().find_map(|(participant_id, _)| {
+            // Latest share if a participant shares more than one screen
+            self.shares
+                .iter()
+                .rev()
+                .find(|s| &s.participant_id == participant_id)
+        });
+        match dominant {
+            Some(share) if self.focused.as_ref() != Some(share) => {
+                let share = share.clone();
+                self.focus(Some(share), now)
+            }
+            _ => false,
+        }
+    }
+
+    /// Forget all shares (e.g. after leaving the room); true if focus changed
+    pub fn clear(&mut self) -> bool {
+        self.shares.clear();
+        self.focused_at = None;
+        self.focused.take().is_some()
+    }
+
+    /// Move focus to the newest remaining share if the focused one ended
+    fn refocus_if_gone(&mut self, now: Instant) -> bool {
+        let gone = self
+            .focused
+            .as_ref()
+            .is_some_and(|focused| !self.shares.contains(focused));
+        if gone {
+            let next = self.shares.last().cloned();
+            return self.focus(next, now);
+        }
+        false
+    }
+
+    fn focus(&mut self, share: Option<FocusedShare>, now: Instant) -> bool {
+        self.focused_at = share.as_ref().map(|_| now);
+        self.focused = share;
+        true
+    }
+}
@@ -6,25 +6,79 @@
 //! Uses runtime.block_on() pattern (like Hopp) to ensure WebRTC operations
 //! are properly driven by a dedicated tokio runtime.
 
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
 use livekit::options::{TrackPublishOptions, VideoCodec, VideoEncoding};
 use livekit::prelude::*;
 use livekit::publication::LocalTrackPublication;
-use livekit::track::{LocalTrack, LocalVideoTrack, TrackSource};
-use livekit::webrtc::prelude::{RtcVideoSource, VideoResolution};
+use livekit::track::{LocalAudioTrack, LocalTrack, LocalVideoTrack, TrackSource};
+use livekit::webrtc::audio_source::native::NativeAudioSource;
+use livekit::webrtc::prelude::{
+    AudioFrame, AudioSourceOptions, RtcAudioSource, RtcVideoSource, VideoResolution,
+};
 use livekit::webrtc::video_source::native::NativeVideoSource;
 use parking_lot::Mutex;
 use tokio::sync::mpsc;
-use winit::event_loop::EventLoopProxy;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
 
-use crate::UserEvent;
+use crate::relay;
+use crate::{
+    CaptureConfig, ConnectionStats, EventProxy, FrameFormat, UserEvent, VideoCodecPreference,
+};
+
+#[cfg(feature = "e2ee")]
+pub mod e2ee;
+pub mod focus;
+pub mod stats;
+
+use stats::{StatsReporter, StatsSampler};
+
+/// Minimum interval between active speaker updates (~10/sec)
+const ACTIVE_SPEAKERS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Buffer size for the screen share audio source
+const SCREEN_SHARE_AUDIO_QUEUE_MS: u32 = 100;
 
 /// Published screen share track info
 pub struct ScreenShareTrack {
     pub video_source: NativeVideoSource,
     #[allow(dead_code)]
     publication: LocalTrackPublication,
+    /// Encoding actually published (after any codec fallback), reused to republish
+    encoding: ScreenShareEncoding,
+}
+
+/// How the screen share video is encoded
+#[derive(Debug, Clone, Copy)]
+struct ScreenShareEncoding {
+    codec: VideoCodec,
+    max_bitrate: u64,
+    max_framerate: f64,
+    simulcast: bool,
+}
+
+/// Encoder framerate cap of the screen share, whatever the capture rate
+const SCREEN_SHARE_MAX_FRAMERATE: f64 = 30.0;
+
+/// How the published screen share is actually encoded, which can differ
+/// from the requested `CaptureConfig`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PublishedEncoding {
+    /// Requested codec, or VP8 if the server rejected it
+    pub codec: VideoCodecPreference,
+    pub max_bitrate: u64,
+    pub max_framerate: u32,
+}
+
+/// Published screen share audio track info
+struct ScreenShareAudioTrack {
+    audio_source: NativeAudioSource,
+    publication: LocalTrackPublication,
+    /// Task feeding captured samples into the audio source
+    forward_task: tokio::task::JoinHandle<()>,
 }
 
 /// LiveKit room service with dedicated tokio runtime
@@ -37,28 +91,34 @@ pub struct RoomService {
     /// LiveKit server URL
     server_url: String,
     /// Event proxy for winit event loop
-    event_proxy: EventLoopProxy<UserEvent>,
+    event_proxy: EventProxy,
     /// Connected room (if any)
     room: Arc<Mutex<Option<Room>>>,
     /// Screen share track (if any)
     screen_share_track: Arc<Mutex<Option<ScreenShareTrack>>>,
+    /// Screen share system audio track (if any)
+    screen_share_audio_track: Arc<Mutex<Option<ScreenShareAudioTrack>>>,
+    /// Rate state shared by periodic and on-demand stats
+    stats_sampler: Arc<Mutex<StatsSampler>>,
+    /// Periodic connection stats reporter (while connected)
+    stats_reporter: Mutex<Option<StatsReporter>>,
+    /// Subscribe to every remote track as it is published
+    auto_subscribe: bool,
+    /// DataTrack payload cipher (set when the room uses E2EE)
+    #[cfg(feature = "e2ee")]
+    data_cipher: Option<e2ee::DataCipher>,
 }
 
 impl RoomService {
     /// Create a new RoomService with its own tokio runtime
-    pub fn new(
-        server_url: String,
-        event_proxy: EventLoopProxy<UserEvent>,
-    ) -> std::io::Result<Self> {
-        eprintln!("[DEBUG] RoomService::new - creating runtime");
-
+    pub fn new(server_url: String, event_proxy: EventProxy) -> std::io::Result<Self> {
         // Create dedicated tokio runtime (like Hopp)
         let runtime = tokio::runtime::Builder::new_multi_thread()
             .enable_all()
             .thread_name("livekit-runtime")
             .build()?;
 
-        eprintln!("[DEBUG] RoomService::new - runtime created");
+        tracing::debug!("RoomService runtime created");
 
         Ok(Self {
             runtime: Arc::new(runtime),
@@ -66,32 +126,66 @@ impl RoomService {
             event_proxy,
             room: Arc::new(Mutex::new(None)),
             screen_share_track: Arc::new(Mutex::new(None)),
+            screen_share_audio_track: Arc::new(Mutex::new(None)),
+            stats_sampler: Arc::new(Mutex::new(StatsSampler::default())),
+            stats_reporter: Mutex::new(None),
+            auto_subscribe: true,
+            #[cfg(feature = "e2ee")]
+            data_cipher: None,
         })
     }
 
-    /// Connect to the LiveKit room (blocking)
-    pub fn connect(&self, token: String) -> Result<(), String> {
-        eprintln!("[DEBUG] RoomService::connect - starting");
-        eprintln!("[DEBUG] Token length: {} chars", token.len());
-        eprintln!(
-            "[DEBUG] Token preview: {}...{}",
-            &token[..50.min(token.len())],
-            &token[token.len().saturating_sub(20)..]
-        );
+    /// Encrypt DataTrack payloads with a key derived from a room-wide passphrase
+    #[cfg(feature = "e2ee")]
+    pub fn enable_data_encryption(&mut self, passphrase: &str) {
+        self.data_cipher = Some(e2ee::DataCipher::from_passphrase(passphrase));
+    }
+
+    /// Leave remote tracks unsubscribed until `set_track_subscribed` asks
+    /// for them (takes effect on the next `connect`)
+    pub fn set_auto_subscribe(&mut self, auto_subscribe: bool) {
+        self.auto_subscribe = auto_subscribe;
+    }
+
+    /// Decrypt a received DataTrack payload
+    ///
+    /// Returns None if the payload could not be decrypted and should be dropped.
+    /// Without E2EE the payload is returned unchanged.
+    pub fn open_data(&self, payload: &[u8]) -> Option<Vec<u8>> {
+        #[cfg(feature = "e2ee")]
+        if let Some(cipher) = &self.data_cipher {
+            return match cipher.decrypt(payload) {
+                Ok(plaintext) => Some(plaintext),
+                Err(e) => {
+                    tracing::warn!("Dropping DataTrack message: {}", e);
+                    None
+                }
+            };
+        }
+
+        Some(payload.to_vec())
+    }
+
+    /// Connect to the LiveKit room (blocking); remote video is relayed to
+    /// the WebView as `relay_format`
+    ///
+    /// `token` is only used to connect. While connected the server pushes
+    /// refreshed tokens over the signal channel and the SDK reconnects with
+    /// the latest one, so the session outlives this token's TTL.
+    pub fn connect(&self, token: String, relay_format: FrameFormat) -> Result<(), String> {
+        tracing::debug!(token_len = token.len(), "RoomService::connect starting");
 
         let server_url = self.server_url.clone();
         let event_proxy = self.event_proxy.clone();
         let room_holder = self.room.clone();
+        // RoomOptions is non-exhaustive, so it can't be built with a literal
+        let mut options = RoomOptions::default();
+        options.auto_subscribe = self.auto_subscribe;
 
         // Use runtime.block_on() to drive the async operation
         // This ensures the WebRTC connection is properly polled
-        eprintln!("[DEBUG] RoomService::connect - calling runtime.block_on()");
-
         let result = self.runtime.block_on(async move {
-            eprintln!(
-                "[DEBUG] Inside block_on - calling Room::connect to {}",
-                server_url
-            );
+            tracing::info!(server_url = %server_url, "Connecting to room");
 
             // Close existing room if any
             {
@@ -100,40 +194,72 @@ impl RoomService {
                     room_guard.take()
                 };
                 if let Some(room) = room_to_close {
-                    eprintln!("[DEBUG] Closing existing room");
+                    tracing::debug!("Closing existing room");
                     let _ = room.close().await;
                 }
             }
 
             // Connect with timeout - increased to 45s for cloud connections
-            eprintln!("[DEBUG] Starting Room::connect with 45s timeout...");
-            let connect_future = Room::connect(&server_url, &token, RoomOptions::default());
+            let connect_future = Room::connect(&server_url, &token, options);
 
             match tokio::time::timeout(std::time::Duration::from_secs(45), connect_future).await {
                 Ok(Ok((room, room_events))) => {
                     let room_name = room.name().to_string();
-                    eprintln!("[DEBUG] SUCCESS: Connected to room: {}", room_name);
+                    tracing::info!(room_name = %room_name, "Connected to room");
+
+                    let local = room.local_participant();
+                    let local_participant = participant_data(
+                        local.identity().to_string(),
+                        local.name(),
+                        local.metadata(),
+                        &local.attributes(),
+                        true,
+                    );
+                    // Participants already in the room get no
+                    // ParticipantConnected event of their own, nor do the
+                    // tracks they already publish get TrackPublished
+                    let mut remote_events = Vec::new();
+                    for participant in room.remote_participants().values() {
+                        let participant_id = participant.identity().to_string();
+                        remote_events.push(UserEvent::ParticipantConnected(participant_data(
+                            participant_id.clone(),
+                            participant.name(),
+                            participant.metadata(),
+                            &participant.attributes(),
+                            false,
+                        )));
+                        for publication in participant.track_publications().values() {
+                            remote_events.push(UserEvent::RemoteTrackPublished {
+                                participant_id: participant_id.clone(),
+                                track_id: publication.sid().to_string(),
+                                source: track_source(publication.source()),
+                            });
+                        }
+                    }
 
                     // Store room
                     *room_holder.lock() = Some(room);
-                    eprintln!("[DEBUG] Room stored in holder");
 
                     // Notify winit event loop
-                    let _ = event_proxy.send_event(UserEvent::RoomConnected { room_name });
+                    let _ = event_proxy.send_event(UserEvent::LocalParticipant(local_participant));
+                    for event in remote_events {
+                        let _ = event_proxy.send_event(event);
+                    }
+                    let _ = event_proxy.send_event(UserEvent::RoomConnected {
+                        room_name,
+                        relay_format,
+                    });
 
                     // Return the event receiver for spawning the handler
                     Ok(room_events)
                 }
                 Ok(Err(e)) => {
-                    eprintln!("[DEBUG] Room::connect FAILED: {:?}", e);
-                    eprintln!("[DEBUG] Error details: {}", e);
+                    tracing::error!("Room::connect failed: {:?}", e);
                     Err(e.to_string())
                 }
                 Err(_) => {
-                    eprintln!("[DEBUG] Room::connect TIMED OUT after 45s");
-                    eprintln!(
-                        "[DEBUG] This usually indicates WebSocket or ICE connectivity issues"
-                    );
+                    // Usually WebSocket or ICE connectivity issues
+                    tracing::error!("Room::connect timed out after 45s");
                     Err("Connection timed out after 45s".to_string())
                 }
             }
@@ -142,11 +268,19 @@ impl RoomService {
         // If connection succeeded, spawn event handler on the runtime
         match result {
             Ok(room_events) => {
-                eprintln!("[DEBUG] Connection succeeded, spawning event handler");
                 let event_proxy = self.event_proxy.clone();
                 self.runtime
-                    .spawn(handle_room_events(room_events, event_proxy));
-                eprintln!("[DEBUG] Event handler spawned");
+                    .spawn(handle_room_events(room_events, event_proxy, relay_format));
+
+                match StatsReporter::start(
+                    self.runtime.handle().clone(),
+                    self.room.clone(),
+                    self.stats_sampler.clone(),
+                    self.event_proxy.clone(),
+                ) {
+                    Ok(reporter) => *self.stats_reporter.lock() = Some(reporter),
+                    Err(e) => tracing::warn!("Failed to start connection stats reporter: {}", e),
+                }
                 Ok(())
             }
             Err(e) => Err(e),
@@ -157,6 +291,9 @@ impl RoomService {
     pub fn disconnect(&self) {
         tracing::info!("RoomService::disconnect");
 
+        // Stop reporting before the room goes away
+        self.stats_reporter.lock().take();
+
         // Take room out of mutex before spawning async task
         let room_to_close = self.room.lock().take();
         let event_proxy = self.event_proxy.clone();
@@ -166,22 +303,73 @@ impl RoomService {
             self.runtime.spawn(async move {
                 let _ = room.close().await;
                 tracing::info!("Room disconnected");
-                let _ = event_proxy.send_event(UserEvent::RoomDisconnected);
+                let _ = event_proxy.send_event(UserEvent::RoomDisconnected {
+                    reason: disconnect_reason(DisconnectReason::ClientInitiated),
+                });
             });
         }
     }
 
+    /// Leave the room and wait for the server to acknowledge (blocking)
+    ///
+    /// Used on shutdown, where a fire-and-forget `disconnect` would be cut
+    /// short by the process exiting and leave a ghost participant behind.
+    /// Returns false if the room did not close within `timeout`.
+    pub fn close(&self, timeout: Duration) -> bool {
+        tracing::info!("RoomService::close");
+
+        self.stats_reporter.lock().take();
+
+        let Some(room) = self.room.lock().take() else {
+            return true;
+        };
+
+        self.runtime.block_on(async move {
+            match tokio::time::timeout(timeout, room.close()).await {
+                Ok(result) => {
+                    if let Err(e) = result {
+                        tracing::warn!("Room close failed: {}", e);
+                    }
+                    true
+                }
+                Err(_) => {
+                    tracing::warn!("Room did not close within {:?}", timeout);
+                    false
+                }
+            }
+        })
+    }
+
+    /// Collect current connection statistics (blocking)
+    ///
+    /// Bitrate is measured since the previous sample, periodic or on demand.
+    pub fn get_stats(&self) -> Result<ConnectionStats, String> {
+        stats::collect(self.runtime.handle(), &self.room, &self.stats_sampler)
+    }
+
     /// Publish screen share track (blocking), returns the video source
     pub fn publish_screen_share(
         &self,
-        width: u32,
-        height: u32,
+        config: &CaptureConfig,
     ) -> Result<NativeVideoSource, String> {
-        tracing::info!("RoomService::publish_screen_share {}x{}", width, height);
+        let (width, height) = (config.width, config.height);
+        tracing::info!(
+            "RoomService::publish_screen_share {}x{} {:?} @ {} bps",
+            width,
+            height,
+            config.codec,
+            config.bitrate
+        );
 
         let room_holder = self.room.clone();
         let screen_share_holder = self.screen_share_track.clone();
         let event_proxy = self.event_proxy.clone();
+        let encoding = ScreenShareEncoding {
+            codec: video_codec(config.codec),
+            max_bitrate: config.bitrate as u64,
+            max_framerate: SCREEN_SHARE_MAX_FRAMERATE,
+            simulcast: config.simulcast,
+        };
 
         #[allow(clippy::await_holding_lock)]
         self.runtime.block_on(async move {
@@ -192,30 +380,23 @@ impl RoomService {
                 // Create video source
                 let video_source = NativeVideoSource::new(VideoResolution { width, height });
 
-                // Create video track
-                let track = LocalVideoTrack::create_video_track(
-                    "screen_share",
-                    RtcVideoSource::Native(video_source.clone()),
-                );
+                // Publish the track, retrying with VP8 (supported everywhere)
+                // if the server rejects the requested codec
+                let mut encoding = encoding;
+                let result = match publish_screen_share_track(room, &video_source, encoding).await {
+                    Err(e) if encoding.codec != VideoCodec::VP8 => {
+                        tracing::warn!(
+                            "Publishing screen share with {} failed ({}), falling back to VP8",
+                            encoding.codec.as_str(),
+                            e
+                        );
+                        encoding.codec = VideoCodec::VP8;
+                        publish_screen_share_track(room, &video_source, encoding).await
+                    }
+                    result => result,
+                };
 
-                // Publish the track
-                match room
-                    .local_participant()
-                    .publish_track(
-                        LocalTrack::Video(track),
-                        TrackPublishOptions {
-                            source: TrackSource::Screenshare,
-                            video_codec: VideoCodec::VP9,
-                            video_encoding: Some(VideoEncoding {
-                                max_bitrate: 4_000_000,
-                                max_framerate: 30.0,
-                            }),
-                            simulcast: false,
-                            ..Default::default()
-                        },
-                    )
-                    .await
-                {
+                match result {
                     Ok(publication) => {
                         tracing::info!("Screen share track published: {}", publication.sid());
 
@@ -223,6 +404,7 @@ impl RoomService {
                         let screen_share = ScreenShareTrack {
                             video_source: video_source.clone(),
                             publication,
+                            encoding,
                         };
                         *screen_share_holder.lock() = Some(screen_share);
 
@@ -240,6 +422,146 @@ impl RoomService {
         })
     }
 
+    /// Publish system audio as a second track of the screen share
+    ///
+    /// Returns a sender for interleaved i16 samples in the given format.
+    pub fn publish_screen_share_audio(
+        &self,
+        sample_rate: u32,
+        num_channels: u32,
+    ) -> Result<mpsc::UnboundedSender<Vec<i16>>, String> {
+        tracing::info!(
+            "RoomService::publish_screen_share_audio {} Hz, {} ch",
+            sample_rate,
+            num_channels
+        );
+
+        let room_holder = self.room.clone();
+        let audio_holder = self.screen_share_audio_track.clone();
+        let runtime = self.runtime.clone();
+
+        #[allow(clippy::await_holding_lock)]
+        self.runtime.block_on(async move {
+            let room_guard = room_holder.lock();
+            let Some(room) = room_guard.as_ref() else {
+                return Err("Not connected to room".to_string());
+            };
+
+            let audio_source = NativeAudioSource::new(
+                AudioSourceOptions::default(),
+                sample_rate,
+                num_channels,
+                SCREEN_SHARE_AUDIO_QUEUE_MS,
+            );
+            let track = LocalAudioTrack::create_audio_track(
+                "screen_share_audio",
+                RtcAudioSource::Native(audio_source.clone()),
+            );
+
+            let publication = room
+                .local_participant()
+                .publish_track(
+                    LocalTrack::Audio(track),
+                    screen_share_audio_publish_options(),
+                )
+                .await
+                .map_err(|e| {
+                    tracing::error!("Failed to publish screen share audio: {}", e);
+                    e.to_string()
+                })?;
+
+            tracing::info!("Screen share audio track published: {}", publication.sid());
+
+            let (tx, mut rx) = mpsc::unbounded_channel::<Vec<i16>>();
+            let forward_source = audio_source.clone();
+            let forward_task = runtime.spawn(async move {
+                while let Some(samples) = rx.recv().await {
+                    let frame = AudioFrame {
+                        samples_per_channel: samples.len() as u32 / num_channels,
+                        data: samples.into(),
+                        sample_rate,
+                        num_channels,
+                    };
+                    if let Err(e) = forward_source.capture_frame(&frame).await {
+                        tracing::warn!("Failed to push screen share audio: {:?}", e);
+                    }
+                }
+            });
+
+            *audio_holder.lock() = Some(ScreenShareAudioTrack {
+                audio_source,
+                publication,
+                forward_task,
+            });
+            Ok(tx)
+        })
+    }
+
+    /// Make sure the screen share is still published after a reconnect
+    ///
+    /// On a full reconnect LiveKit republishes local tracks under new SIDs, so
+    /// the stored publications are refreshed to keep unpublishing working.
+    /// Tracks that did not come back are published again from the same
+    /// sources - the capture pipeline keeps feeding them untouched.
+    pub fn restore_screen_share(&self) -> Result<(), String> {
+        tracing::info!("RoomService::restore_screen_share");
+
+        let room_holder = self.room.clone();
+        let screen_share_holder = self.screen_share_track.clone();
+        let audio_holder = self.screen_share_audio_track.clone();
+
+        #[allow(clippy::await_holding_lock)]
+        self.runtime.block_on(async move {
+            let room_guard = room_holder.lock();
+            let Some(room) = room_guard.as_ref() else {
+                return Err("Not connected to room".to_string());
+            };
+            let local = room.local_participant();
+            let publications = local.track_publications();
+            let find_published = |source: TrackSource| {
+                publications
+                    .values()
+                    .find(|publication| publication.source() == source)
+                    .cloned()
+            };
+
+            if let Some(share) = screen_share_holder.lock().as_mut() {
+                share.publication = match find_published(TrackSource::Screenshare) {
+                    Some(publication) => publication,
+                    None => {
+                        tracing::warn!("Screen share missing after reconnect, republishing");
+                        publish_screen_share_track(room, &share.video_source, share.encoding)
+                            .await
+                            .map_err(|e| e.to_string())?
+                    }
+                };
+                tracing::info!("Screen share published as {}", share.publication.sid());
+            }
+
+            if let Some(audio) = audio_holder.lock().as_mut() {
+                audio.publication = match find_published(TrackSource::ScreenshareAudio) {
+                    Some(publication) => publication,
+                    None => {
+                        tracing::warn!("Screen share audio missing after reconnect, republishing");
+                        let track = LocalAudioTrack::create_audio_track(
+                            "screen_share_audio",
+                            RtcAudioSource::Native(audio.audio_source.clone()),
+                        );
+                        local
+                            .publish_track(
+                                LocalTrack::Audio(track),
+                                screen_share_audio_publish_options(),
+                            )
+                            .await
+                            .map_err(|e| e.to_string())?
+                    }
+                };
+            }
+
+            Ok(())
+        })
+    }
+
     /// Get the video source for the current screen share (if any)
     pub fn get_screen_share_source(&self) -> Option<NativeVideoSource> {
         self.screen_share_track
@@ -248,10 +570,24 @@ impl RoomService {
             .map(|t| t.video_source.clone())
     }
 
+    /// How the current screen share is encoded (if one is published)
+    pub fn screen_share_encoding(&self) -> Option<PublishedEncoding> {
+        self.screen_share_track
+            .lock()
+            .as_ref()
+            .map(|share| PublishedEncoding {
+                codec: codec_preference(share.encoding.codec),
+                max_bitrate: share.encoding.max_bitrate,
+                max_framerate: share.encoding.max_framerate as u32,
+            })
+    }
+
     /// Unpublish screen share track
     pub fn unpublish_screen_share(&self) -> Result<(), String> {
         tracing::info!("RoomService::unpublish_screen_share");
 
+        self.unpublish_screen_share_audio()?;
+
         let room_holder = self.room.clone();
         let screen_share_holder = self.screen_share_track.clone();
 
@@ -259,9 +595,9 @@ impl RoomService {
         self.runtime.block_on(async move {
             let track_info = screen_share_holder.lock().take();
 
-            if let Some(track) = track_info {
-                let room_guard = room_holder.lock();
-                if let Some(room) = room_guard.as_ref() {
+            let room_guard = room_holder.lock();
+            if let Some(room) = room_guard.as_ref() {
+                if let Some(track) = track_info {
                     let _ = room
                         .local_participant()
                         .unpublish_track(&track.publication.sid())
@@ -273,8 +609,66 @@ impl RoomService {
         })
     }
 
+    /// Unpublish the screen share's system audio track, if any, leaving
+    /// the video published
+    pub fn unpublish_screen_share_audio(&self) -> Result<(), String> {
+        tracing::info!("RoomService::unpublish_screen_share_audio");
+
+        let room_holder = self.room.clone();
+        let audio_holder = self.screen_share_audio_track.clone();
+
+        #[allow(clippy::await_holding_lock)]
+        self.runtime.block_on(async move {
+            let Some(audio) = audio_holder.lock().take() else {
+                return Ok(());
+            };
+            audio.forward_task.abort();
+
+            let room_guard = room_holder.lock();
+            if let Some(room) = room_guard.as_ref() {
+                let _ = room
+                    .local_participant()
+                    .unpublish_track(&audio.publication.sid())
+                    .await;
+                tracing::info!("Screen share audio track unpublished");
+            }
+            Ok(())
+        })
+    }
+
     /// Send data via DataTrack (blocking)
     pub fn send_data(&self, data: Vec<u8>, reliable: bool) {
+        self.publish_data(data, reliable, Vec::new());
+    }
+
+    /// Send data reliably to one participant only (blocking)
+    pub fn send_data_to(&self, data: Vec<u8>, participant_id: &str) {
+        self.publish_data(
+            data,
+            true,
+            vec![ParticipantIdentity::from(participant_id.to_string())],
+        );
+    }
+
+    /// Publish a DataTrack packet; no destinations means everyone
+    fn publish_data(
+        &self,
+        data: Vec<u8>,
+        reliable: bool,
+        destination_identities: Vec<ParticipantIdentity>,
+    ) {
+        #[cfg(feature = "e2ee")]
+        let data = match &self.data_cipher {
+            Some(cipher) => match cipher.encrypt(&data) {
+                Ok(sealed) => sealed,
+                Err(e) => {
+                    tracing::error!("Not sending DataTrack message: {}", e);
+                    return;
+                }
+            },
+            None => data,
+        };
+
         let room_holder = self.room.clone();
 
         // Use block_on to ensure data is sent
@@ -287,6 +681,7 @@ impl RoomService {
                     .publish_data(DataPacket {
                         payload: data,
                         reliable,
+                        destination_identities,
                         ..Default::default()
                     })
                     .await;
@@ -294,12 +689,49 @@ impl RoomService {
         });
     }
 
-    /// Set microphone muted state (placeholder)
+    /// Subscribe to or unsubscribe from one remote track
+    ///
+    /// Unsubscribing stops the track's media (and its relay to the WebView)
+    /// until it is subscribed again.
+    pub fn set_track_subscribed(
+        &self,
+        participant_id: &str,
+        track_id: &str,
+        subscribed: bool,
+    ) -> Result<(), String> {
+        let room_guard = self.room.lock();
+        let room = room_guard.as_ref().ok_or("Not connected to a room")?;
+
+        let participant = room
+            .remote_participants()
+            .get(&ParticipantIdentity(participant_id.to_string()))
+            .cloned()
+            .ok_or_else(|| format!("Participant {} not found", participant_id))?;
+        let publication = TrackSid::try_from(track_id.to_string())
+            .ok()
+            .and_then(|sid| participant.get_track_publication(&sid))
+            .ok_or_else(|| format!("Track {} not found", track_id))?;
+
+        // Subscription updates are sent to the SFU from the room's runtime
+        let _runtime = self.runtime.enter();
+        publication.set_subscribed(subscribed);
+        tracing::info!(
+            participant_id = participant_id,
+            track_id = track_id,
+            subscribed = subscribed,
+            "Track subscription changed"
+        );
+        Ok(())
+    }
+
+    /// Set microphone muted state (placeholder: no microphone track is
+    /// published yet)
     pub fn set_microphone_muted(&self, muted: bool) {
         tracing::debug!("Set microphone muted: {} (track management TBD)", muted);
     }
 
-    /// Set camera enabled state (placeholder)
+    /// Set camera enabled state (placeholder: no camera track is published
+    /// yet)
     pub fn set_camera_enabled(&self, enabled: bool) {
         tracing::debug!("Set camera enabled: {} (track management TBD)", enabled);
     }
@@ -315,37 +747,199 @@ impl RoomService {
     }
 }
 
+/// Create a screen share track on `video_source` and publish it
+async fn publish_screen_share_track(
+    room: &Room,
+    video_source: &NativeVideoSource,
+    encoding: ScreenShareEncoding,
+) -> RoomResult<LocalTrackPublication> {
+    let track = LocalVideoTrack::create_video_track(
+        "screen_share",
+        RtcVideoSource::Native(video_source.clone()),
+    );
+    room.local_participant()
+        .publish_track(
+            LocalTrack::Video(track),
+            screen_share_publish_options(encoding),
+        )
+        .await
+}
+
+/// Publish options for the screen share video track
+///
+/// With simulcast the SDK adds a half-resolution layer at 3 fps below the
+/// full one, at max(150 kbps, max_bitrate / 40) for a 30 fps source - a small
+/// upstream cost that lets the SFU serve weak subscribers without stalling.
+/// The extra layer needs a source at least 480 px on its longer side.
+fn screen_share_publish_options(encoding: ScreenShareEncoding) -> TrackPublishOptions {
+    TrackPublishOptions {
+        source: TrackSource::Screenshare,
+        video_codec: encoding.codec,
+        video_encoding: Some(VideoEncoding {
+            max_bitrate: encoding.max_bitrate,
+            max_framerate: encoding.max_framerate,
+        }),
+        simulcast: encoding.simulcast,
+        ..Default::default()
+    }
+}
+
+fn video_codec(codec: VideoCodecPreference) -> VideoCodec {
+    match codec {
+        VideoCodecPreference::Vp8 => VideoCodec::VP8,
+        VideoCodecPreference::Vp9 => VideoCodec::VP9,
+        VideoCodecPreference::H264 => VideoCodec::H264,
+        VideoCodecPreference::Av1 => VideoCodec::AV1,
+    }
+}
+
+fn codec_preference(codec: VideoCodec) -> VideoCodecPreference {
+    match codec {
+        VideoCodec::VP9 => VideoCodecPreference::Vp9,
+        VideoCodec::H264 => VideoCodecPreference::H264,
+        VideoCodec::AV1 => VideoCodecPreference::Av1,
+        _ => VideoCodecPreference::Vp8,
+    }
+}
+
+/// Publish options for the screen share system audio track
+fn screen_share_audio_publish_options() -> TrackPublishOptions {
+    TrackPublishOptions {
+        source: TrackSource::ScreenshareAudio,
+        ..Default::default()
+    }
+}
+
+/// Wire name of a disconnect reason, e.g. `duplicate_identity`
+fn disconnect_reason(reason: DisconnectReason) -> String {
+    reason.as_str_name().to_lowercase()
+}
+
+fn participant_data(
+    id: String,
+    name: String,
+    metadata: String,
+    attributes: &HashMap<String, String>,
+    is_local: bool,
+) -> crate::ParticipantData {
+    crate::ParticipantData {
+        id,
+        name,
+        is_local,
+        role: crate::ParticipantRole::from_participant(&metadata, attributes),
+        metadata,
+    }
+}
+
+fn track_source(source: TrackSource) -> crate::TrackSourceType {
+    match source {
+        TrackSource::Camera => crate::TrackSourceType::Camera,
+        TrackSource::Microphone => crate::TrackSourceType::Microphone,
+        TrackSource::Screenshare => crate::TrackSourceType::ScreenShare,
+        TrackSource::ScreenshareAudio => crate::TrackSourceType::ScreenShareAudio,
+        TrackSource::Unknown => crate::TrackSourceType::Unknown,
+    }
+}
+
 /// Handle LiveKit room events
 async fn handle_room_events(
     mut events: mpsc::UnboundedReceiver<RoomEvent>,
-    event_proxy: EventLoopProxy<UserEvent>,
+    event_proxy: EventProxy,
+    relay_format: FrameFormat,
 ) {
-    eprintln!("[DEBUG] Room event handler started");
+    tracing::debug!("Room event handler started");
+
+    // Video relay tasks keyed by track SID
+    let mut relay_tasks: HashMap<String, JoinHandle<()>> = HashMap::new();
+
+    // Active speaker updates are throttled; the latest pending update is
+    // flushed once the interval has elapsed
+    let mut speakers_last_sent: Option<Instant> = None;
+    let mut pending_speakers: Option<Vec<(String, f32)>> = None;
+
+    loop {
+        let flush_at = speakers_last_sent
+            .map(|sent| sent + ACTIVE_SPEAKERS_INTERVAL)
+            .unwrap_or_else(Instant::now);
+
+        let event = tokio::select! {
+            event = events.recv() => match event {
+                Some(event) => event,
+                None => break,
+            },
+            _ = tokio::time::sleep_until(flush_at), if pending_speakers.is_some() => {
+                if let Some(speakers) = pending_speakers.take() {
+                    speakers_last_sent = Some(Instant::now());
+                    let _ = event_proxy.send_event(UserEvent::ActiveSpeakersChanged { speakers });
+                }
+                continue;
+            }
+        };
 
-    while let Some(event) = events.recv().await {
         match event {
             RoomEvent::ParticipantConnected(participant) => {
                 tracing::info!("Participant connected: {}", participant.identity());
-                let _ = event_proxy.send_event(UserEvent::ParticipantConnected(
-                    crate::ParticipantData {
-                        id: participant.identity().to_string(),
-                        name: participant.name().to_string(),
-                        is_local: false,
-                        role: crate::ParticipantRole::Participant,
-                    },
-                ));
+                let _ = event_proxy.send_event(UserEvent::ParticipantConnected(participant_data(
+                    participant.identity().to_string(),
+                    participant.name(),
+                    participant.metadata(),
+                    &participant.attributes(),
+                    false,
+                )));
             }
             RoomEvent::ParticipantDisconnected(participant) => {
                 tracing::info!("Participant disconnected: {}", participant.identity());
-                let _ = event_proxy.send_event(UserEvent::ParticipantDisconnected(
-                    crate::ParticipantData {
-                        id: participant.identity().to_string(),
-                        name: participant.name().to_string(),
-                        is_local: false,
-                        role: crate::ParticipantRole::Participant,
-                    },
+                let _ =
+                    event_proxy.send_event(UserEvent::ParticipantDisconnected(participant_data(
+                        participant.identity().to_string(),
+                        participant.name(),
+                        participant.metadata(),
+                        &participant.attributes(),
+                        false,
+                    )));
+            }
+            RoomEvent::ParticipantMetadataChanged { participant, .. }
+            | RoomEvent::ParticipantAttributesChanged { participant, .. } => {
+                tracing::debug!("Participant metadata changed: {}", participant.identity());
+                let _ = event_proxy.send_event(UserEvent::ParticipantMetadataChanged(
+                    participant_data(
+                        participant.identity().to_string(),
+                        participant.name(),
+                        participant.metadata(),
+                        &participant.attributes(),
+                        matches!(participant, Participant::Local(_)),
+                    ),
                 ));
             }
+            RoomEvent::TrackPublished {
+                publication,
+                participant,
+            } => {
+                tracing::info!(
+                    "Track published: {} from {}",
+                    publication.sid(),
+                    participant.identity()
+                );
+                let _ = event_proxy.send_event(UserEvent::RemoteTrackPublished {
+                    participant_id: participant.identity().to_string(),
+                    track_id: publication.sid().to_string(),
+                    source: track_source(publication.source()),
+                });
+            }
+            RoomEvent::TrackUnpublished {
+                publication,
+                participant,
+            } => {
+                tracing::info!(
+                    "Track unpublished: {} from {}",
+                    publication.sid(),
+                    participant.identity()
+                );
+                let _ = event_proxy.send_event(UserEvent::RemoteTrackUnpublished {
+                    participant_id: participant.identity().to_string(),
+                    track_id: publication.sid().to_string(),
+                });
+            }
             RoomEvent::TrackSubscribed {
                 track, participant, ..
             } => {
@@ -354,6 +948,23 @@ async fn handle_room_events(
                     track.sid(),
                     participant.identity()
                 );
+                let _ = event_proxy.send_event(UserEvent::TrackSubscribed {
+                    participant_id: participant.identity().to_string(),
+                    track_id: track.sid().to_string(),
+                    source: track_source(track.source()),
+                });
+                if let RemoteTrack::Video(video_track) = track {
+                    let track_sid = video_track.sid().to_string();
+                    let handle = tokio::spawn(relay::relay_video_track(
+                        video_track,
+                        participant.identity().to_string(),
+                        relay_format,
+                        event_proxy.clone(),
+                    ));
+                    if let Some(previous) = relay_tasks.insert(track_sid, handle) {
+                        previous.abort();
+                    }
+                }
             }
             RoomEvent::TrackUnsubscribed {
                 track, participant, ..
@@ -363,6 +974,13 @@ async fn handle_room_events(
                     track.sid(),
                     participant.identity()
                 );
+                if let Some(handle) = relay_tasks.remove(&track.sid().to_string()) {
+                    handle.abort();
+                }
+                let _ = event_proxy.send_event(UserEvent::TrackUnsubscribed {
+                    participant_id: participant.identity().to_string(),
+                    track_id: track.sid().to_string(),
+                });
             }
             RoomEvent::DataReceived {
                 payload,
@@ -383,20 +1001,46 @@ async fn handle_room_events(
                     });
                 }
             }
+            RoomEvent::ActiveSpeakersChanged { speakers } => {
+                let speakers: Vec<(String, f32)> = speakers
+                    .iter()
+                    .map(|p| (p.identity().to_string(), p.audio_level()))
+                    .collect();
+
+                let due = speakers_last_sent
+                    .is_none_or(|sent| sent.elapsed() >= ACTIVE_SPEAKERS_INTERVAL);
+                if due {
+                    pending_speakers = None;
+                    speakers_last_sent = Some(Instant::now());
+                    let _ = event_proxy.send_event(UserEvent::ActiveSpeakersChanged { speakers });
+                } else {
+                    pending_speakers = Some(speakers);
+                }
+            }
             RoomEvent::Disconnected { reason } => {
                 tracing::warn!("Room disconnected: {:?}", reason);
-                let _ = event_proxy.send_event(UserEvent::RoomDisconnected);
+                let _ = event_proxy.send_event(UserEvent::RoomDisconnected {
+                    reason: disconnect_reason(reason),
+                });
             }
             RoomEvent::Reconnecting => {
                 tracing::info!("Room reconnecting...");
+                let _ = event_proxy.send_event(UserEvent::ConnectionStateChanged(
+                    crate::ConnectionState::Reconnecting,
+                ));
             }
             RoomEvent::Reconnected => {
                 tracing::info!("Room reconnected");
+                let _ = event_proxy.send_event(UserEvent::RoomReconnected);
             }
             _ => {
                 tracing::trace!("Room event: {:?}", event);
             }
         }
     }
+
+    for (_, handle) in relay_tasks.drain() {
+        handle.abort();
+    }
     tracing::info!("Room event handler exited");
 }
@@ -0,0 +1,176 @@
+//! Connection statistics
+//!
+//! Condenses the publisher's WebRTC stats into the few numbers needed to tell
+//! a network-side degradation (bitrate, loss, RTT) apart from a capture-side
+//! fps drop. While connected, a reporter samples them every
+//! `CONNECTION_STATS_INTERVAL` and dispatches `UserEvent::ConnectionStatsUpdated`.
+
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use livekit::prelude::Room;
+use livekit::webrtc::stats::{OutboundRtpStats, RtcStats};
+use parking_lot::Mutex;
+
+use crate::{ConnectionStats, EventProxy, Resolution, UserEvent};
+
+/// Interval between connection stats reports
+const CONNECTION_STATS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Turns cumulative RTC counters into rates between consecutive samples
+#[derive(Debug, Default)]
+pub struct StatsSampler {
+    /// Time and total outbound bytes of the previous sample
+    last: Option<(Instant, u64)>,
+}
+
+impl StatsSampler {
+    /// Summarize one snapshot of publisher stats
+    pub fn sample(&mut self, stats: &[RtcStats]) -> ConnectionStats {
+        let mut bytes_sent = 0u64;
+        let mut video: Option<&OutboundRtpStats> = None;
+        let mut loss_sum = 0.0;
+        let mut loss_reports = 0u32;
+        let mut candidate_rtt = None;
+        let mut remote_rtt = None;
+
+        for stat in stats {
+            match stat {
+                RtcStats::OutboundRtp(outbound) => {
+                    bytes_sent += outbound.sent.bytes_sent;
+                    // With simulcast, report the largest layer being sent
+                    let width = outbound.outbound.frame_width;
+                    if outbound.stream.kind == "video"
+                        && width > video.map_or(0, |v| v.outbound.frame_width)
+                    {
+                        video = Some(outbound);
+                    }
+                }
+                RtcStats::RemoteInboundRtp(remote) => {
+                    loss_sum += remote.remote_inbound.fraction_lost;
+                    loss_reports += 1;
+                    if remote.remote_inbound.round_trip_time > 0.0 {
+                        remote_rtt = Some(remote.remote_inbound.round_trip_time);
+                    }
+                }
+                RtcStats::CandidatePair(pair) if pair.candidate_pair.nominated => {
+                    candidate_rtt = Some(pair.candidate_pair.current_round_trip_time);
+                }
+                _ => {}
+            }
+        }
+
+        let now = Instant::now();
+        let outbound_bitrate = match self.last {
+            // Counters reset when tracks are republished - skip that sample
+            Some((at, last_bytes)) if bytes_sent >= last_bytes => {
+                let elapsed = now.duration_since(at).as_secs_f64();
+                if elapsed > 0.0 {
+                    ((bytes_sent - last_bytes) as f64 * 8.0 / elapsed) as u64
+                } else {
+                    0
+                }
+            }
+            _ => 0,
+        };
+        self.last = Some((now, bytes_sent));
+
+        ConnectionStats {
+            outbound_bitrate,
+            packet_loss: if loss_reports > 0 {
+                loss_sum / loss_reports as f64
+            } else {
+                0.0
+            },
+            // RTC stats report seconds
+            rtt_ms: candidate_rtt.or(remote_rtt).map(|rtt| rtt * 1000.0),
+            fps: video.map_or(0.0, |v| v.outbound.frames_per_second),
+            resolution: video.map(|v| Resolution {
+                width: v.outbound.frame_width,
+                height: v.outbound.frame_height,
+            }),
+        }
+    }
+}
+
+/// Fetch and summarize the current stats of the connected room
+///
+/// Blocks on the room runtime; must not be called from within it.
+pub(super) fn collect(
+    runtime: &tokio::runtime::Handle,
+    room_holder: &Mutex<Option<Room>>,
+    sampler: &Mutex<StatsSampler>,
+) -> Result<ConnectionStats, String> {
+    #[allow(clippy::await_holding_lock)]
+    let session = runtime.block_on(async {
+        let room_guard = room_holder.lock();
+        match room_guard.as_ref() {
+            Some(room) => room.get_stats().await.map_err(|e| e.to_string()),
+            None => Err("Not connected to room".to_string()),
+        }
+    })?;
+
+    Ok(sampler.lock().sample(&session.publisher_stats))
+}
+
+/// Background reporter emitting connection stats while connected
+///
+/// Runs on its own thread and drives the stats requests on the room runtime
+/// with `block_on`, like the rest of `RoomService`. Stops when dropped or
+/// when the room goes away.
+pub(super) struct StatsReporter {
+    stop_tx: Option<mpsc::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl StatsReporter {
+    pub(super) fn start(
+        runtime: tokio::runtime::Handle,
+        room_holder: Arc<Mutex<Option<Room>>>,
+        sampler: Arc<Mutex<StatsSampler>>,
+        event_proxy: EventProxy,
+    ) -> std::io::Result<Self> {
+        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+
+        let thread = std::thread::Builder::new()
+            .name("connection-stats".to_string())
+            .spawn(move || {
+                // Wake every interval until stopped (or the sender is dropped)
+                while let Err(RecvTimeoutError::Timeout) =
+                    stop_rx.recv_timeout(CONNECTION_STATS_INTERVAL)
+                {
+                    if room_holder.lock().is_none() {
+                        break;
+                    }
+
+                    match collect(&runtime, &room_holder, &sampler) {
+                        Ok(stats) => {
+                            tracing::trace!("Connection stats: {:?}", stats);
+                            let sent =
+                                event_proxy.send_event(UserEvent::ConnectionStatsUpdated(stats));
+                            if sent.is_err() {
+                                break;
+                            }
+                        }
+                        Err(e) => tracing::debug!("Failed to collect connection stats: {}", e),
+                    }
+                }
+            })?;
+
+        Ok(Self {
+            stop_tx: Some(stop_tx),
+            thread: Some(thread),
+        })
+    }
+}
+
+impl Drop for StatsReporter {
+    fn drop(&mut self) {
+        self.stop_tx.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
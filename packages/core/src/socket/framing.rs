@@ -0,0 +1,160 @@
+//! Outgoing message framing
+//!
+//! Two wire formats are supported for Core → WebView messages:
+//!
+//! - `Json` (default): newline-delimited JSON. Binary payloads are base64.
+//! - `Binary`: every message is a length-prefixed frame
+//!   `[u32 LE length][u8 tag][payload]`, where `length` counts the tag and
+//!   payload bytes. Control messages keep a JSON payload (`TAG_JSON`), video
+//!   frames carry raw bytes (`TAG_VIDEO_FRAME`) to avoid the base64 blow-up.
+//!
+//! A client opts into binary framing by sending
+//! `{"type":"hello","framing":"binary"}` after connecting. Clients that never
+//! send it keep receiving JSON lines.
+//!
+//! Video frame payload layout:
+//! `[u32 LE header length][JSON header][raw frame bytes]`, where the header is
+//! the `video_frame` message without `frame_data`.
+//!
+//! ## Compression
+//!
+//! Binary-framing clients may also send `"compress":true` in the hello. Payloads
+//! of at least `COMPRESSION_MIN_BYTES` are then deflated (raw deflate, fast
+//! level) and marked with `FLAG_COMPRESSED` in the tag byte. A payload is only
+//! sent compressed if that actually made it smaller, so small control messages
+//! and already-compressed JPEG frames go out unchanged.
+//!
+//! Tradeoff (`cargo bench --bench socket_compression`, synthetic 1280x720
+//! frames, one desktop core):
+//!
+//! | Frame        | Raw               | Deflate            |
+//! |--------------|-------------------|--------------------|
+//! | RGBA screen  | 3.6 MB, ~4.6 ms   | ~94 KB, ~2.8 ms    |
+//! | JPEG (120KB) | 120 KB, ~8 µs     | 120 KB, ~1.5 ms    |
+//!
+//! Flat screen content in RGBA deflates so well that encoding gets cheaper
+//! overall (less to copy and write). JPEG frames do not shrink and pay ~1.5 ms
+//! per frame for the attempt, so compression stays off by default and is only
+//! worth enabling together with an RGBA relay.
+
+use std::io::Write;
+
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use serde::Serialize;
+
+use super::OutgoingMessage;
+use crate::FrameFormat;
+
+/// Frame tag for a JSON-encoded control message
+pub const TAG_JSON: u8 = 0;
+
+/// Frame tag for a video frame with raw pixel/JPEG bytes
+pub const TAG_VIDEO_FRAME: u8 = 1;
+
+/// Tag bit set when the payload is deflate-compressed
+pub const FLAG_COMPRESSED: u8 = 0x80;
+
+/// Payloads smaller than this are never compressed
+pub const COMPRESSION_MIN_BYTES: usize = 1024;
+
+/// Wire format for outgoing messages on a connection
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FramingMode {
+    #[default]
+    Json,
+    Binary,
+}
+
+/// Per-connection encoding options negotiated via `hello`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FramingOptions {
+    pub mode: FramingMode,
+    /// Deflate large payloads (binary framing only)
+    pub compress: bool,
+}
+
+/// Video frame metadata sent ahead of the raw bytes in binary mode
+#[derive(Serialize)]
+#[serde(tag = "type", rename = "video_frame")]
+struct VideoFrameHeader<'a> {
+    participant_id: &'a str,
+    track_id: &'a str,
+    width: u32,
+    height: u32,
+    timestamp: u64,
+    wall_timestamp: u64,
+    format: FrameFormat,
+}
+
+/// Encode an outgoing message with the given connection options
+pub fn encode_message(
+    msg: &OutgoingMessage,
+    options: FramingOptions,
+) -> serde_json::Result<Vec<u8>> {
+    match options.mode {
+        FramingMode::Json => {
+            let mut line = serde_json::to_vec(msg)?;
+            line.push(b'\n');
+            Ok(line)
+        }
+        FramingMode::Binary => {
+            let (tag, payload) = match msg {
+                OutgoingMessage::VideoFrame {
+                    participant_id,
+                    track_id,
+                    width,
+                    height,
+                    timestamp,
+                    wall_timestamp,
+                    format,
+                    frame_data,
+                } => {
+                    let header = serde_json::to_vec(&VideoFrameHeader {
+                        participant_id,
+                        track_id,
+                        width: *width,
+                        height: *height,
+                        timestamp: *timestamp,
+                        wall_timestamp: *wall_timestamp,
+                        format: *format,
+                    })?;
+
+                    let mut payload = Vec::with_capacity(4 + header.len() + frame_data.len());
+                    payload.extend_from_slice(&(header.len() as u32).to_le_bytes());
+                    payload.extend_from_slice(&header);
+                    payload.extend_from_slice(frame_data);
+                    (TAG_VIDEO_FRAME, payload)
+                }
+                _ => (TAG_JSON, serde_json::to_vec(msg)?),
+            };
+
+            if options.compress && payload.len() >= COMPRESSION_MIN_BYTES {
+                if let Some(compressed) = deflate(&payload) {
+                    return Ok(frame(tag | FLAG_COMPRESSED, &compressed));
+                }
+            }
+            Ok(frame(tag, &payload))
+        }
+    }
+}
+
+/// Deflate a payload, returning `None` if it did not get smaller
+fn deflate(payload: &[u8]) -> Option<Vec<u8>> {
+    let mut encoder =
+        DeflateEncoder::new(Vec::with_capacity(payload.len() / 2), Compression::fast());
+    encoder.write_all(payload).ok()?;
+    let compressed = encoder.finish().ok()?;
+    (compressed.len() < payload.len()).then_some(compressed)
+}
+
+/// Wrap a payload in a `[u32 LE length][u8 tag]` frame
+fn frame(tag: u8, payload: &[u8]) -> Vec<u8> {
+    let len = (payload.len() + 1) as u32;
+    let mut buf = Vec::with_capacity(5 + payload.len());
+    buf.extend_from_slice(&len.to_le_bytes());
+    buf.push(tag);
+    buf.extend_from_slice(payload);
+    buf
+}
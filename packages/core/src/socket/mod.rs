@@ -1,18 +1,33 @@
 //! Socket module for IPC between Core and Tauri WebView
 //!
-//! Uses Unix domain sockets on Unix systems and named pipes on Windows.
-//! Protocol is JSON-based with binary frame payloads.
+//! Uses Unix domain sockets on Unix systems and named pipes on Windows, or
+//! any other `Transport`.
+//! Protocol is newline-delimited JSON by default; clients can opt into
+//! length-prefixed binary framing for video frames (see `framing`).
 
-use std::sync::Arc;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
 
 use parking_lot::Mutex;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tokio::sync::mpsc;
-use winit::event_loop::EventLoopProxy;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::{broadcast, mpsc, Notify};
+use tokio::task::JoinSet;
 
+pub mod framing;
+mod queue;
+pub mod transport;
+
+use framing::{FramingMode, FramingOptions};
+use queue::FrameQueue;
+use transport::Transport;
+
+use crate::annotation::Stroke;
 use crate::{
-    AnnotationTool, CaptureConfig, Color, ConnectionState, FrameFormat, ParticipantData,
-    PermissionState, Point, ScreenInfo, SourceType, UserEvent,
+    ActiveSpeaker, AnnotationTool, Capabilities, CaptureConfig, Color, ConnectionState,
+    CursorState, CursorStyle, ErrorCode, EventProxy, FrameFormat, InitialMedia, ParticipantData,
+    PermissionState, Point, Resolution, ScreenInfo, Secret, SourceType, TrackSourceType, UserEvent,
 };
 
 /// Messages from WebView to Core
@@ -22,36 +37,121 @@ pub enum IncomingMessage {
     // Room
     JoinRoom {
         server_url: String,
-        token: String,
+        /// Only needed to connect: LiveKit renews the connection's token
+        /// from the refreshed tokens the server pushes, so a long session
+        /// outlives this one's TTL without it being sent again
+        token: Secret,
+        /// Room-wide passphrase enabling DataTrack E2EE (requires `e2ee` feature)
+        #[serde(default)]
+        e2ee_passphrase: Option<Secret>,
+        /// Media to publish as soon as the connection succeeds
+        #[serde(default)]
+        initial_media: Option<InitialMedia>,
+        /// Subscribe to remote tracks as they are published; when false,
+        /// tracks are only received after `SetTrackSubscribed`
+        #[serde(default = "default_auto_subscribe")]
+        auto_subscribe: bool,
+        /// Format remote video is relayed in; one of `capabilities.relay_formats`
+        #[serde(default = "default_relay_format")]
+        relay_format: FrameFormat,
     },
     LeaveRoom,
+    /// Current roster, e.g. after a WebView reload; answered with `Participants`
+    GetParticipants,
+    /// Receive (or stop receiving) one remote track, e.g. only what's visible
+    SetTrackSubscribed {
+        participant_id: String,
+        track_id: String,
+        subscribed: bool,
+    },
 
     // Screen share
-    GetAvailableContent,
+    GetAvailableContent {
+        /// Rescan even if cached sources are still valid
+        #[serde(default)]
+        force_refresh: bool,
+    },
     StartScreenShare {
         source_id: String,
         source_type: SourceType,
         #[serde(default)]
         config: Option<CaptureConfig>,
+        /// Stop a share already in progress instead of failing with
+        /// `already_sharing`
+        #[serde(default)]
+        replace: bool,
     },
     StopScreenShare,
+    PauseScreenShare,
+    ResumeScreenShare,
+    /// Share another screen without unpublishing the track
+    SwitchScreenShareSource {
+        source_id: String,
+    },
+    /// Snapshot the shared screen; answered with `Still`
+    CaptureStill,
+    /// Answered with `CaptureConfig`
+    GetCaptureConfig,
+    /// Change quality mid-share (omitted fields stay as they are);
+    /// answered with the updated `CaptureConfig`
+    SetCaptureQuality {
+        /// Maximum frames per second
+        #[serde(default)]
+        framerate: Option<u32>,
+    },
+
+    // Recording
+    StartRecording {
+        /// Destination MP4 file
+        path: String,
+    },
+    StopRecording,
 
     // Annotations (local user drawing)
+    /// A finished stroke, published to the room
     SendAnnotation {
         stroke_id: String,
         tool: AnnotationTool,
         color: Color,
+        /// Fraction of the shared screen's width; the tool's default if omitted
+        #[serde(default)]
+        width: Option<f32>,
         points: Vec<Point>,
+        /// Share the stroke was drawn on (the sharer's participant id); the
+        /// share currently shown if omitted
+        #[serde(default)]
+        share_id: Option<String>,
     },
     DeleteAnnotation {
         stroke_id: String,
     },
+    /// Tool and color picked in the toolbar
+    SetTool {
+        tool: AnnotationTool,
+        color: Color,
+    },
     ClearAnnotations,
+    /// Remove one participant's strokes on all peers (host only)
+    ClearParticipantAnnotations {
+        participant_id: String,
+    },
+    /// Grant or revoke annotating for a participant; propagated to all peers
+    /// (host only)
+    SetAnnotationPermission {
+        participant_id: String,
+        enabled: bool,
+        /// Also remove the participant's existing strokes when revoking
+        #[serde(default)]
+        clear_strokes: bool,
+    },
 
     // Cursor (local user's cursor for others to see)
     CursorMove {
         x: f32,
         y: f32,
+        /// Show the cursor as a laser pointer (leaves no strokes)
+        #[serde(default)]
+        laser: bool,
     },
     CursorHide,
 
@@ -72,10 +172,53 @@ pub enum IncomingMessage {
     // Permissions
     CheckPermissions,
     RequestScreenRecordingPermission,
+    RequestMicrophonePermission,
+    RequestCameraPermission,
+
+    // Connection setup
+    /// Select the wire format for outgoing messages on this connection
+    Hello {
+        framing: FramingMode,
+        /// Deflate large payloads (binary framing only)
+        #[serde(default)]
+        compress: bool,
+        /// Opt into liveness checking in both directions
+        #[serde(default)]
+        heartbeat: Option<HeartbeatOptions>,
+    },
+
+    // Diagnostics
+    /// Replace the log filter, e.g. `"etch_core=debug"` (`RUST_LOG` syntax)
+    SetLogLevel {
+        filter: String,
+    },
 
     // Lifecycle
-    Ping,
+    Ping {
+        /// Client send time (ms), echoed back in the Pong
+        #[serde(default)]
+        timestamp: Option<u64>,
+    },
     Shutdown,
+
+    /// A `type` this Core doesn't know, e.g. from a newer WebView; skipped
+    #[serde(other)]
+    Unknown,
+}
+
+impl IncomingMessage {
+    /// Whether the message carries an access token or passphrase
+    fn has_credentials(&self) -> bool {
+        matches!(self, Self::JoinRoom { .. })
+    }
+}
+
+fn default_relay_format() -> FrameFormat {
+    crate::relay::DEFAULT_RELAY_FORMAT
+}
+
+fn default_auto_subscribe() -> bool {
+    true
 }
 
 /// Messages from Core to WebView
@@ -94,15 +237,131 @@ pub enum OutgoingMessage {
     ParticipantLeft {
         participant_id: String,
     },
+    /// Metadata or role changed mid-session
+    ParticipantMetadataChanged {
+        participant: ParticipantData,
+    },
     ConnectionStateChanged {
         state: ConnectionState,
     },
+    /// Joined a room; sent after `ConnectionStateChanged` to connected
+    RoomConnected {
+        room_name: String,
+        /// Format this room's `VideoFrame`s are relayed in, from `JoinRoom`
+        relay_format: FrameFormat,
+    },
+    /// Dropped from the room by the server or network, not by `leave_room`
+    RoomDisconnected {
+        /// LiveKit disconnect reason, e.g. `participant_removed`
+        reason: String,
+    },
+    /// Snapshot of the room, answering `GetParticipants`; local participant first
+    Participants {
+        participants: Vec<ParticipantData>,
+    },
+    /// `track_id` matches the `track_id` of relayed `VideoFrame`s
+    TrackSubscribed {
+        participant_id: String,
+        track_id: String,
+        source: TrackSourceType,
+    },
+    TrackUnsubscribed {
+        participant_id: String,
+        track_id: String,
+    },
+    ActiveSpeakersChanged {
+        speakers: Vec<ActiveSpeaker>,
+    },
+    /// Remote cursors at their smoothed positions: every animation frame
+    /// while one moves or a laser trail fades, and whenever one appears,
+    /// hides, changes style or leaves
+    RemoteCursors {
+        cursors: Vec<CursorState>,
+    },
+    /// Remote screen share to spotlight: the dominant speaker's, if they
+    /// share; both null when nobody shares
+    FocusedShareChanged {
+        participant_id: Option<String>,
+        track_id: Option<String>,
+    },
+    /// Emitted every second while connected
+    ConnectionStats {
+        /// Total outbound bitrate (bits/s)
+        outbound_bitrate: u64,
+        /// Fraction of packets lost (0.0-1.0)
+        packet_loss: f64,
+        rtt_ms: Option<f64>,
+        /// Screen share frames per second actually sent
+        fps: f64,
+        resolution: Option<Resolution>,
+        /// Backlog of messages from Core to the WebView
+        outgoing_queue: OutgoingQueueStats,
+    },
 
     // Screen share
     ScreenShareStarted {
         sharer_id: String,
     },
     ScreenShareStopped,
+    /// The share's config as actually published (resolution after
+    /// adaptation, codec after fallback), or null while not sharing
+    CaptureConfig {
+        config: Option<CaptureConfig>,
+    },
+    /// Share frozen (viewers see the last frame) or resumed
+    ScreenSharePauseChanged {
+        paused: bool,
+    },
+    /// The captured display's own resolution changed (or is first known);
+    /// screen bounds should be re-fetched so annotations stay aligned
+    CaptureResolutionChanged {
+        width: u32,
+        height: u32,
+    },
+    /// Resolution the screen share is published at (adapts to the network)
+    PublishedResolutionChanged {
+        width: u32,
+        height: u32,
+    },
+    /// Capture stalled and is being restarted (e.g. display went to sleep)
+    CaptureRestarting {
+        attempt: u32,
+        max: u32,
+    },
+    /// Capture recovered after a restart
+    CaptureRestored,
+    /// Capture could not be restarted; the share stops
+    CaptureGivingUp,
+    /// Emitted every second while sharing, at 0 fps if capture stalls
+    CaptureStats {
+        /// Frames captured and handed to the publisher per second
+        fps: f64,
+        /// Frames handed to the publisher since the share started, including
+        /// the ones counted in `frames_dropped`
+        frames_sent: u64,
+        /// Frames dropped since the last report because publishing lagged
+        frames_dropped: u64,
+        /// Capture restarts so far (see `CaptureRestarting`)
+        restart_attempts: u32,
+    },
+    /// The share now shows `source_id`, after `SwitchScreenShareSource`
+    CaptureSourceChanged {
+        source_id: String,
+    },
+    /// The shared source disappeared (e.g. monitor unplugged). Capture moved
+    /// to `switched_to` when it was the only display left; otherwise the
+    /// share stops and the user should pick another source.
+    CaptureSourceLost {
+        source_id: String,
+        switched_to: Option<String>,
+    },
+
+    // Recording
+    RecordingStateChanged {
+        is_recording: bool,
+        /// File being recorded, or the file just finalized
+        path: Option<String>,
+    },
 
     // Video frames
     VideoFrame {
@@ -110,23 +369,59 @@ pub enum OutgoingMessage {
         track_id: String,
         width: u32,
         height: u32,
+        /// Core's monotonic clock (ms), strictly increasing - use for ordering
         timestamp: u64,
+        /// Wall clock time (ms since Unix epoch) when the frame was relayed
+        wall_timestamp: u64,
         format: FrameFormat,
         #[serde(with = "base64_serde")]
         frame_data: Vec<u8>,
     },
 
+    /// Full-resolution snapshot of the shared screen, from `CaptureStill`
+    Still {
+        width: u32,
+        height: u32,
+        format: FrameFormat,
+        #[serde(with = "base64_serde")]
+        data: Vec<u8>,
+    },
+
     // Permissions
     PermissionState {
         state: PermissionState,
     },
 
+    // Handshake
+    /// First message on every connection: what this Core is and supports
+    Ready {
+        version: String,
+        /// `std::env::consts::OS`, e.g. "macos", "windows", "linux"
+        platform: String,
+        capabilities: Capabilities,
+        /// Format remote video is relayed in unless `JoinRoom` asks for
+        /// another; `RoomConnected` reports the one in use
+        relay_format: FrameFormat,
+    },
+
     // Responses
-    Pong,
+    Pong {
+        /// Timestamp from the Ping, echoed back unchanged
+        timestamp: Option<u64>,
+        /// Core time (ms since Unix epoch) when the Pong was sent
+        core_timestamp: u64,
+    },
+
+    /// Sent when nothing else was written for a heartbeat interval
+    /// (only if the client negotiated a heartbeat in `Hello`)
+    Heartbeat {
+        /// Core time (ms since Unix epoch)
+        core_timestamp: u64,
+    },
 
     // Errors
     Error {
-        code: String,
+        code: ErrorCode,
         message: String,
     },
 }
@@ -161,325 +456,996 @@ pub enum DataTrackMessage {
         stroke_id: String,
         tool: AnnotationTool,
         color: Color,
+        /// Fraction of the shared screen's width, before pressure; absent
+        /// from older peers, which draw at the tool's default
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        width: Option<f32>,
         point: Point,
+        /// Points after `point` already known when the stroke started, e.g.
+        /// all of a finished stroke, so they don't depend on lossy updates
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        points: Vec<Point>,
+        /// Share the stroke belongs to, as the sharer's participant id;
+        /// absent from older peers
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        share_id: Option<String>,
     },
     StrokeUpdate {
         stroke_id: String,
         points: Vec<Point>,
     },
+    /// More points of a finished stroke, following its `StrokeStart` when
+    /// they don't all fit in one message; reliable, unlike `StrokeUpdate`
+    StrokePoints {
+        stroke_id: String,
+        points: Vec<Point>,
+    },
     StrokeComplete {
         stroke_id: String,
     },
+    /// Completed strokes received, sent back to their author only; the
+    /// author re-sends strokes left unacknowledged (see `StrokeAcks`)
+    StrokeAck {
+        stroke_ids: Vec<String>,
+    },
     StrokeDelete {
         stroke_id: String,
     },
     ClearAll,
+    /// Remove all strokes by one participant (host only)
+    ClearParticipant {
+        participant_id: String,
+    },
     CursorMove {
         x: f32,
         y: f32,
         visible: bool,
+        /// Style to show the sender's cursor in; absent for a plain cursor
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        style: Option<CursorStyle>,
     },
+    /// Grant or revoke annotating for one participant (host action)
+    AnnotationPermission {
+        participant_id: String,
+        enabled: bool,
+        #[serde(default)]
+        clear_strokes: bool,
+    },
+    /// Strokes drawn before the receiver joined, sent only to them
+    Snapshot {
+        strokes: Vec<Stroke>,
+    },
+}
+
+impl DataTrackMessage {
+    /// Whether the message needs reliable (ordered, retransmitted) delivery
+    ///
+    /// Cursor moves and intermediate stroke points are superseded by the next
+    /// message, so they go lossy for lower latency. Stroke boundaries,
+    /// deletes and clears must arrive or peers are left with half-drawn or
+    /// stale strokes.
+    pub fn is_reliable(&self) -> bool {
+        !matches!(
+            self,
+            DataTrackMessage::StrokeUpdate { .. } | DataTrackMessage::CursorMove { .. }
+        )
+    }
+
+    /// Whether only a host may send the message; peers ignore it from others
+    pub fn requires_host(&self) -> bool {
+        matches!(
+            self,
+            DataTrackMessage::ClearParticipant { .. }
+                | DataTrackMessage::AnnotationPermission { .. }
+        )
+    }
+}
+
+/// State categories whose latest message is replayed to new clients
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum StickyKey {
+    ConnectionState,
+    ScreenShare,
+    PermissionState,
+    Recording,
+    FocusedShare,
+}
+
+impl OutgoingMessage {
+    /// Sticky messages describe current state: the latest one per key is kept
+    /// and replayed to clients that connect later. Everything else (frames,
+    /// participant deltas, responses, errors) is fire-and-forget and dropped
+    /// while no client is connected.
+    pub fn sticky_key(&self) -> Option<StickyKey> {
+        match self {
+            OutgoingMessage::ConnectionStateChanged { .. } => Some(StickyKey::ConnectionState),
+            OutgoingMessage::ScreenShareStarted { .. } | OutgoingMessage::ScreenShareStopped => {
+                Some(StickyKey::ScreenShare)
+            }
+            OutgoingMessage::PermissionState { .. } => Some(StickyKey::PermissionState),
+            OutgoingMessage::RecordingStateChanged { .. } => Some(StickyKey::Recording),
+            OutgoingMessage::FocusedShareChanged { .. } => Some(StickyKey::FocusedShare),
+            _ => None,
+        }
+    }
+}
+
+/// Last-known sticky messages, replayed on connect
+#[derive(Default)]
+struct StickyState {
+    messages: BTreeMap<StickyKey, OutgoingMessage>,
+}
+
+impl StickyState {
+    fn record(&mut self, msg: &OutgoingMessage) {
+        if let Some(key) = msg.sticky_key() {
+            self.messages.insert(key, msg.clone());
+        }
+    }
+
+    fn snapshot(&self) -> Vec<OutgoingMessage> {
+        self.messages.values().cloned().collect()
+    }
+}
+
+/// Author id for local strokes and cursor moves until the room reports the
+/// local participant's identity
+pub const LOCAL_PARTICIPANT_ID: &str = "local";
+
+/// Messages a client may fall behind the newest before it skips ahead
+const BROADCAST_CAPACITY: usize = 256;
+
+/// Control messages queued for the server before `send` blocks
+const CONTROL_QUEUE_CAPACITY: usize = 1024;
+
+/// Longest `send` waits for room in a full control queue before dropping
+const CONTROL_SEND_TIMEOUT: Duration = Duration::from_millis(50);
+
+/// Video frames queued per reader; older ones are dropped past this
+const FRAME_QUEUE_CAPACITY: usize = 4;
+
+/// How long a new client has to send its first message (e.g. `hello`)
+/// before sticky state is replayed
+const HELLO_GRACE_PERIOD: Duration = Duration::from_millis(100);
+
+/// How often a connection checks whether a heartbeat is due or missed
+const HEARTBEAT_TICK: Duration = Duration::from_millis(250);
+
+/// Shortest heartbeat interval a client may negotiate (ms)
+const MIN_HEARTBEAT_INTERVAL_MS: u64 = 1000;
+
+/// Intervals without any message from the client before it counts as gone
+const HEARTBEAT_MISSED_LIMIT: u32 = 3;
+
+/// Longest incoming line accepted by default, newline included
+const DEFAULT_MAX_LINE_BYTES: usize = 16 * 1024 * 1024;
+
+/// Incoming messages handled per second per client by default
+const DEFAULT_MAX_MESSAGES_PER_SEC: u32 = 1000;
+
+/// Environment variable overriding `DEFAULT_MAX_LINE_BYTES`
+const MAX_LINE_BYTES_ENV: &str = "ETCH_MAX_LINE_BYTES";
+
+/// Environment variable overriding `DEFAULT_MAX_MESSAGES_PER_SEC`
+const MAX_MESSAGES_PER_SEC_ENV: &str = "ETCH_MAX_MESSAGES_PER_SEC";
+
+/// Limits on what a single client may send
+///
+/// A line longer than `max_line_bytes` - a hostile client, or a corrupted
+/// stream that lost its newlines - closes the connection instead of being
+/// buffered without bound. Past `max_messages_per_sec`, Core stops reading
+/// from the client until the second is up, so a runaway client backs up in
+/// its own socket buffer rather than flooding the event loop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InputLimits {
+    /// Longest line, newline included
+    pub max_line_bytes: usize,
+    /// Messages handled per second before reads pause
+    pub max_messages_per_sec: u32,
+}
+
+impl Default for InputLimits {
+    fn default() -> Self {
+        Self {
+            max_line_bytes: DEFAULT_MAX_LINE_BYTES,
+            max_messages_per_sec: DEFAULT_MAX_MESSAGES_PER_SEC,
+        }
+    }
+}
+
+impl InputLimits {
+    /// Defaults, overridden by `ETCH_MAX_LINE_BYTES` and `ETCH_MAX_MESSAGES_PER_SEC`
+    pub fn from_env() -> Self {
+        fn parse<T: std::str::FromStr>(name: &str) -> Option<T> {
+            let value = std::env::var(name).ok()?;
+            let parsed = value.parse().ok();
+            if parsed.is_none() {
+                tracing::warn!("Ignoring invalid {}={:?}", name, value);
+            }
+            parsed
+        }
+
+        let defaults = Self::default();
+        Self {
+            max_line_bytes: parse(MAX_LINE_BYTES_ENV).unwrap_or(defaults.max_line_bytes),
+            max_messages_per_sec: parse(MAX_MESSAGES_PER_SEC_ENV)
+                .unwrap_or(defaults.max_messages_per_sec),
+        }
+    }
+}
+
+/// Fixed one-second window counting messages read from a client
+struct RateLimiter {
+    max_per_window: u32,
+    window_start: Instant,
+    count: u32,
+}
+
+impl RateLimiter {
+    fn new(max_per_sec: u32) -> Self {
+        Self {
+            max_per_window: max_per_sec.max(1),
+            window_start: Instant::now(),
+            count: 0,
+        }
+    }
+
+    /// Count one message, waiting for the next window if this one is full
+    async fn acquire(&mut self) {
+        if self.window_start.elapsed() >= Duration::from_secs(1) {
+            self.window_start = Instant::now();
+            self.count = 0;
+        }
+        if self.count >= self.max_per_window {
+            let next_window = self.window_start + Duration::from_secs(1);
+            tracing::warn!(
+                "Client exceeded {} messages/s, pausing reads",
+                self.max_per_window
+            );
+            tokio::time::sleep_until(next_window.into()).await;
+            self.window_start = Instant::now();
+            self.count = 0;
+        }
+        self.count += 1;
+    }
+}
+
+/// Liveness checking negotiated in `Hello`
+///
+/// The client promises to send something (a `ping` if otherwise idle) at
+/// least every interval, and Core writes a `heartbeat` whenever it had
+/// nothing else to send for that long. A client silent for
+/// `HEARTBEAT_MISSED_LIMIT` intervals - hung, or a half-closed pipe - is
+/// disconnected, so Core doesn't stay around orphaned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+pub struct HeartbeatOptions {
+    /// At least `MIN_HEARTBEAT_INTERVAL_MS`
+    pub interval_ms: u64,
+    /// Shut Core down, not just drop the connection, when the client goes silent
+    #[serde(default)]
+    pub shutdown_on_timeout: bool,
+}
+
+impl HeartbeatOptions {
+    fn interval(&self) -> Duration {
+        Duration::from_millis(self.interval_ms.max(MIN_HEARTBEAT_INTERVAL_MS))
+    }
+
+    fn timeout(&self) -> Duration {
+        self.interval() * HEARTBEAT_MISSED_LIMIT
+    }
+}
+
+/// Depth of the outgoing queues, for diagnosing a WebView that can't keep up
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+pub struct OutgoingQueueStats {
+    /// Control messages waiting for the server
+    pub control_queued: usize,
+    /// Video frames waiting for the server
+    pub frames_queued: usize,
+    /// Video frames dropped because a reader fell behind (since start)
+    pub frames_dropped: u64,
+}
+
+/// Receiving ends of `CoreSocket`'s outgoing queues
+struct Outgoing {
+    control: mpsc::Receiver<OutgoingMessage>,
+    frames: Arc<FrameQueue>,
+    dropped: Arc<AtomicU64>,
+}
+
+/// Bind the Unix socket, readable and writable by its owner only
+///
+/// A stale socket left by an earlier run of ours is replaced. Anything else
+/// at the path - a regular file, a directory, another user's socket - is
+/// left alone and binding fails, so a mistyped path can't delete a file and
+/// another user can't have us reuse a socket they control.
+#[cfg(unix)]
+fn bind_unix_socket(socket_path: &str) -> anyhow::Result<transport::UnixSocket> {
+    use std::os::unix::fs::{FileTypeExt, MetadataExt, PermissionsExt};
+
+    match std::fs::symlink_metadata(socket_path) {
+        Ok(metadata) => {
+            if !metadata.file_type().is_socket() {
+                anyhow::bail!("{} exists and is not a socket", socket_path);
+            }
+            // SAFETY: geteuid has no preconditions and cannot fail
+            let uid = unsafe { libc::geteuid() };
+            if metadata.uid() != uid {
+                anyhow::bail!("{} is a socket owned by another user", socket_path);
+            }
+            std::fs::remove_file(socket_path)?;
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e.into()),
+    }
+
+    // The socket is created owner-only rather than tightened after bind,
+    // which would leave a window where another user could connect. umask is
+    // process-wide, so files other threads create meanwhile only come out
+    // more restrictive.
+    // SAFETY: umask has no preconditions and cannot fail
+    let previous_umask = unsafe { libc::umask(0o077) };
+    let listener = tokio::net::UnixListener::bind(socket_path);
+    // SAFETY: as above
+    unsafe { libc::umask(previous_umask) };
+    let listener = listener?;
+    let file = transport::SocketFile::new(socket_path)?;
+    if let Err(e) = std::fs::set_permissions(socket_path, std::fs::Permissions::from_mode(0o600)) {
+        file.remove();
+        return Err(e.into());
+    }
+    Ok(transport::UnixSocket::new(listener, file))
+}
+
+/// Remove the socket file on SIGTERM or SIGINT, then shut Core down
+///
+/// Shutdown removes the file too, but only once it completes; this also
+/// covers a shutdown that hangs. A second signal exits at once. SIGKILL
+/// can't be caught: that leaves the file, which the next Core on the same
+/// path replaces (see `bind_unix_socket`).
+#[cfg(unix)]
+pub(crate) fn cleanup_on_signal(file: transport::SocketFile, event_loop_proxy: EventProxy) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let (Ok(mut terminate), Ok(mut interrupt)) = (
+        signal(SignalKind::terminate()),
+        signal(SignalKind::interrupt()),
+    ) else {
+        tracing::warn!("Failed to install signal handlers");
+        return;
+    };
+
+    tokio::spawn(async move {
+        tokio::select! {
+            _ = terminate.recv() => {}
+            _ = interrupt.recv() => {}
+        }
+        tracing::info!("Termination signal received, shutting down");
+        file.remove();
+        let _ = event_loop_proxy.send_event(UserEvent::Terminate);
+
+        tokio::select! {
+            _ = terminate.recv() => {}
+            _ = interrupt.recv() => {}
+        }
+        tracing::warn!("Second termination signal, exiting now");
+        std::process::exit(1);
+    });
 }
 
 /// Socket server for Tauri communication
+///
+/// Control messages go through a bounded channel: when it is full, `send`
+/// blocks for up to `CONTROL_SEND_TIMEOUT` before dropping the message.
+/// Video frames go through a latest-wins queue that never blocks, so a
+/// slow WebView costs frames rather than memory.
 pub struct CoreSocket {
-    sender: mpsc::UnboundedSender<OutgoingMessage>,
-    _shutdown: Arc<Mutex<bool>>,
+    sender: mpsc::Sender<OutgoingMessage>,
+    frames: Arc<FrameQueue>,
+    frames_dropped: Arc<AtomicU64>,
+    /// Signalled when the server task has exited
+    server_done: std::sync::mpsc::Receiver<()>,
+    /// Socket file bound by `new`, removed once the server exits
+    #[cfg(unix)]
+    socket_file: Option<transport::SocketFile>,
 }
 
 impl CoreSocket {
-    /// Create a new socket server
-    pub async fn new(
+    /// Create a new socket server, with input limits taken from the environment
+    pub async fn new(socket_path: &str, event_loop_proxy: EventProxy) -> anyhow::Result<Self> {
+        Self::with_limits(socket_path, event_loop_proxy, InputLimits::from_env()).await
+    }
+
+    /// Create a new socket server enforcing `limits` on every client
+    pub async fn with_limits(
         socket_path: &str,
-        event_loop_proxy: EventLoopProxy<UserEvent>,
+        event_loop_proxy: EventProxy,
+        limits: InputLimits,
     ) -> anyhow::Result<Self> {
-        let (sender, receiver) = mpsc::unbounded_channel();
-        let shutdown = Arc::new(Mutex::new(false));
-
-        // Remove existing socket file if it exists
+        // Bind before returning so a path we refuse to use fails `new`
         #[cfg(unix)]
         {
-            let _ = std::fs::remove_file(socket_path);
+            let transport = bind_unix_socket(socket_path)?;
+            let socket_file = transport.file().clone();
+            tracing::info!("Socket server listening on {}", socket_path);
+            Ok(Self {
+                socket_file: Some(socket_file),
+                ..Self::with_transport(transport, event_loop_proxy, limits)
+            })
         }
 
-        // Start socket server
-        let socket_path = socket_path.to_string();
-        let shutdown_clone = shutdown.clone();
+        #[cfg(windows)]
+        {
+            // Windows named pipe implementation
+            // For now, use TCP as a fallback
+            // Parse port from socket path or use default
+            let port: u16 = socket_path
+                .split('-')
+                .last()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(9876);
+
+            let listener = tokio::net::TcpListener::bind(format!("127.0.0.1:{}", port)).await?;
+            tracing::info!("Socket server listening on 127.0.0.1:{}", port);
+            Ok(Self::with_transport(listener, event_loop_proxy, limits))
+        }
+    }
+
+    /// Serve clients from any transport, e.g. `transport::memory()` in tests
+    ///
+    /// Must be called within a Tokio runtime.
+    pub fn with_transport<T: Transport>(
+        transport: T,
+        event_loop_proxy: EventProxy,
+        limits: InputLimits,
+    ) -> Self {
+        let (sender, control) = mpsc::channel(CONTROL_QUEUE_CAPACITY);
+        let frames_dropped = Arc::new(AtomicU64::new(0));
+        let frames = Arc::new(FrameQueue::new(
+            FRAME_QUEUE_CAPACITY,
+            frames_dropped.clone(),
+        ));
+        let outgoing = Outgoing {
+            control,
+            frames: frames.clone(),
+            dropped: frames_dropped.clone(),
+        };
+
+        // Start socket server; the transport is dropped (and a socket file
+        // removed) when it exits, or when the runtime shuts down under it
+        let (done_tx, server_done) = std::sync::mpsc::channel();
 
         tokio::spawn(async move {
-            if let Err(e) =
-                Self::run_server(&socket_path, receiver, event_loop_proxy, shutdown_clone).await
-            {
-                tracing::error!("Socket server error: {}", e);
-            }
+            Self::serve(transport, outgoing, limits, event_loop_proxy).await;
+            let _ = done_tx.send(());
         });
 
-        Ok(Self {
+        Self {
             sender,
-            _shutdown: shutdown,
-        })
+            frames,
+            frames_dropped,
+            server_done,
+            #[cfg(unix)]
+            socket_file: None,
+        }
+    }
+
+    /// The socket file bound by `new`
+    #[cfg(unix)]
+    pub(crate) fn socket_file(&self) -> Option<&transport::SocketFile> {
+        self.socket_file.as_ref()
     }
 
-    /// Send a message to the connected client
+    /// Send a message to all connected clients
+    ///
+    /// Call from outside the async runtime: a full control queue blocks the
+    /// calling thread briefly.
     pub fn send(&self, msg: OutgoingMessage) {
-        if let Err(e) = self.sender.send(msg) {
-            tracing::warn!("Failed to send message: {}", e);
+        if matches!(msg, OutgoingMessage::VideoFrame { .. }) {
+            self.frames.push(msg);
+            return;
+        }
+
+        let deadline = Instant::now() + CONTROL_SEND_TIMEOUT;
+        let mut msg = msg;
+        loop {
+            match self.sender.try_send(msg) {
+                Ok(()) => return,
+                Err(mpsc::error::TrySendError::Full(returned)) if Instant::now() < deadline => {
+                    msg = returned;
+                    std::thread::sleep(Duration::from_millis(1));
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to send message: {}", e);
+                    return;
+                }
+            }
         }
     }
 
-    /// Shutdown the socket server
-    pub fn shutdown(self) {
-        *self._shutdown.lock() = true;
+    /// Current depth of the outgoing queues
+    pub fn queue_stats(&self) -> OutgoingQueueStats {
+        OutgoingQueueStats {
+            control_queued: self.sender.max_capacity() - self.sender.capacity(),
+            frames_queued: self.frames.len(),
+            frames_dropped: self.frames_dropped.load(Ordering::Relaxed),
+        }
     }
 
-    #[cfg(unix)]
-    async fn run_server(
-        socket_path: &str,
-        mut outgoing: mpsc::UnboundedReceiver<OutgoingMessage>,
-        event_loop_proxy: EventLoopProxy<UserEvent>,
-        shutdown: Arc<Mutex<bool>>,
-    ) -> anyhow::Result<()> {
-        use tokio::net::UnixListener;
+    /// Shutdown the socket server (blocking)
+    ///
+    /// Closing the outgoing channel lets the server write everything already
+    /// queued before it exits; waits up to `timeout` for that to happen.
+    pub fn shutdown(self, timeout: Duration) {
+        drop(self.sender);
+        self.frames.close();
+        if self.server_done.recv_timeout(timeout).is_err() {
+            tracing::warn!("Socket server did not finish within {:?}", timeout);
+        }
+    }
 
-        let listener = UnixListener::bind(socket_path)?;
-        tracing::info!("Socket server listening on {}", socket_path);
+    /// Accept clients and fan outgoing messages out to all of them
+    ///
+    /// Every connected client receives every control message, in the order
+    /// Core sent it. A client that falls more than `BROADCAST_CAPACITY`
+    /// messages behind skips the ones it missed and gets the current sticky
+    /// state again, so one slow observer never holds back the others. Video
+    /// frames go to a latest-wins queue per client instead, so a slow client
+    /// holds at most `FRAME_QUEUE_CAPACITY` of them. Incoming messages are
+    /// handled in order per client; across clients they interleave in
+    /// arrival order.
+    ///
+    /// Returns once the outgoing channel is closed and every client has
+    /// written what was queued for it.
+    async fn serve<T: Transport>(
+        mut transport: T,
+        mut outgoing: Outgoing,
+        limits: InputLimits,
+        event_loop_proxy: EventProxy,
+    ) {
+        let (broadcast_tx, _) = broadcast::channel(BROADCAST_CAPACITY);
+        let sticky = Arc::new(Mutex::new(StickyState::default()));
+        let mut connections = JoinSet::new();
+        let mut frame_queues: Vec<Weak<FrameQueue>> = Vec::new();
 
         loop {
-            if *shutdown.lock() {
-                break;
-            }
-
             tokio::select! {
-                accept_result = listener.accept() => {
+                accept_result = transport.accept() => {
                     match accept_result {
-                        Ok((stream, _)) => {
-                            tracing::info!("Client connected");
-                            let _ = event_loop_proxy.send_event(UserEvent::SocketConnected);
-
-                            let (reader, mut writer) = stream.into_split();
-                            let mut reader = BufReader::new(reader);
-                            let proxy = event_loop_proxy.clone();
-
-                            // Handle incoming messages
-                            let read_handle = tokio::spawn(async move {
-                                let mut line = String::new();
-                                loop {
-                                    line.clear();
-                                    match reader.read_line(&mut line).await {
-                                        Ok(0) => break, // EOF
-                                        Ok(_) => {
-                                            if let Err(e) = Self::handle_message(line.trim(), &proxy) {
-                                                tracing::warn!("Failed to handle message: {}", e);
-                                            }
-                                        }
-                                        Err(e) => {
-                                            tracing::error!("Read error: {}", e);
-                                            break;
-                                        }
-                                    }
-                                }
-                            });
-
-                            // Handle outgoing messages
-                            while let Some(msg) = outgoing.recv().await {
-                                match serde_json::to_string(&msg) {
-                                    Ok(json) => {
-                                        if let Err(e) = writer.write_all(format!("{}\n", json).as_bytes()).await {
-                                            tracing::error!("Write error: {}", e);
-                                            break;
-                                        }
-                                    }
-                                    Err(e) => {
-                                        tracing::error!("Serialization error: {}", e);
-                                    }
-                                }
-                            }
-
-                            read_handle.abort();
-                            let _ = event_loop_proxy.send_event(UserEvent::SocketDisconnected);
-                            tracing::info!("Client disconnected");
+                        Ok((reader, writer)) => {
+                            // Subscribed under the sticky lock, so the replay
+                            // and the live stream neither overlap nor leave a gap
+                            let (replay, messages) = {
+                                let sticky = sticky.lock();
+                                (sticky.snapshot(), broadcast_tx.subscribe())
+                            };
+                            let frames = Arc::new(FrameQueue::new(
+                                FRAME_QUEUE_CAPACITY,
+                                outgoing.dropped.clone(),
+                            ));
+                            frame_queues.push(Arc::downgrade(&frames));
+                            connections.spawn(Self::handle_connection(
+                                reader,
+                                writer,
+                                replay,
+                                messages,
+                                frames,
+                                sticky.clone(),
+                                limits,
+                                event_loop_proxy.clone(),
+                            ));
                         }
                         Err(e) => {
                             tracing::error!("Accept error: {}", e);
                         }
                     }
                 }
+                Some(frame) = outgoing.frames.pop() => {
+                    // Connections that ended drop their queue
+                    frame_queues.retain(|queue| match queue.upgrade() {
+                        Some(queue) => {
+                            queue.push(frame.clone());
+                            true
+                        }
+                        None => false,
+                    });
+                }
+                msg = outgoing.control.recv() => match msg {
+                    Some(msg) => {
+                        let mut sticky = sticky.lock();
+                        sticky.record(&msg);
+                        // No client connected - the sticky state is kept,
+                        // fire-and-forget messages are dropped
+                        let _ = broadcast_tx.send(msg);
+                    }
+                    None => break,
+                },
+                // Reap finished connections
+                Some(_) = connections.join_next(), if !connections.is_empty() => {}
             }
         }
 
-        // Cleanup socket file
-        let _ = std::fs::remove_file(socket_path);
-        Ok(())
+        // Closing the broadcast lets each client drain its queue and finish
+        drop(broadcast_tx);
+        for queue in frame_queues.iter().filter_map(Weak::upgrade) {
+            queue.close();
+        }
+        while connections.join_next().await.is_some() {}
     }
 
-    #[cfg(windows)]
-    async fn run_server(
-        socket_path: &str,
-        mut outgoing: mpsc::UnboundedReceiver<OutgoingMessage>,
-        event_loop_proxy: EventLoopProxy<UserEvent>,
-        shutdown: Arc<Mutex<bool>>,
-    ) -> anyhow::Result<()> {
-        // Windows named pipe implementation
-        // For now, use TCP as a fallback
-        use tokio::net::TcpListener;
+    /// Serve a single client until it disconnects
+    ///
+    /// Sends `Ready` and replays sticky state first, then forwards broadcast
+    /// messages. Returns when the client closes the connection, a write fails
+    /// or the server shuts down.
+    async fn handle_connection<R, W>(
+        reader: R,
+        mut writer: W,
+        replay: Vec<OutgoingMessage>,
+        mut messages: broadcast::Receiver<OutgoingMessage>,
+        frames: Arc<FrameQueue>,
+        sticky: Arc<Mutex<StickyState>>,
+        limits: InputLimits,
+        event_loop_proxy: EventProxy,
+    ) where
+        R: AsyncRead + Unpin + Send + 'static,
+        W: AsyncWrite + Unpin,
+    {
+        tracing::info!("Client connected");
+        let _ = event_loop_proxy.send_event(UserEvent::SocketConnected);
 
-        // Parse port from socket path or use default
-        let port: u16 = socket_path
-            .split('-')
-            .last()
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(9876);
+        let mut reader = BufReader::new(reader);
+        let proxy = event_loop_proxy.clone();
+        let framing = Arc::new(Mutex::new(FramingOptions::default()));
+        let reader_framing = framing.clone();
+        let heartbeat = Arc::new(Mutex::new(None::<HeartbeatOptions>));
+        let reader_heartbeat = heartbeat.clone();
+        let last_received = Arc::new(Mutex::new(Instant::now()));
+        let reader_last_received = last_received.clone();
+        let first_message = Arc::new(Notify::new());
+        let reader_first_message = first_message.clone();
 
-        let listener = TcpListener::bind(format!("127.0.0.1:{}", port)).await?;
-        tracing::info!("Socket server listening on 127.0.0.1:{}", port);
+        // Handle incoming messages
+        let mut read_handle = tokio::spawn(async move {
+            let mut line = Vec::new();
+            let mut rate = RateLimiter::new(limits.max_messages_per_sec);
+            loop {
+                line.clear();
+                // One byte over the limit is enough to tell the line is too long
+                let mut limited = (&mut reader).take(limits.max_line_bytes as u64 + 1);
+                match limited.read_until(b'\n', &mut line).await {
+                    Ok(0) => break, // EOF
+                    Ok(_) if line.len() > limits.max_line_bytes => {
+                        tracing::error!(
+                            "Client sent a line over {} bytes, closing connection",
+                            limits.max_line_bytes
+                        );
+                        break;
+                    }
+                    Ok(_) => {
+                        let Ok(json) = std::str::from_utf8(&line) else {
+                            tracing::error!("Read error: message is not valid UTF-8");
+                            break;
+                        };
+                        rate.acquire().await;
+                        *reader_last_received.lock() = Instant::now();
+                        if let Err(e) = Self::handle_message(
+                            json.trim(),
+                            &proxy,
+                            &reader_framing,
+                            &reader_heartbeat,
+                        ) {
+                            tracing::warn!("Failed to handle message: {}", e);
+                        }
+                        reader_first_message.notify_one();
+                    }
+                    Err(e) => {
+                        tracing::error!("Read error: {}", e);
+                        break;
+                    }
+                }
+            }
+        });
 
-        loop {
-            if *shutdown.lock() {
-                break;
+        // Give the client a moment to negotiate framing before the replay
+        let _ = tokio::time::timeout(HELLO_GRACE_PERIOD, first_message.notified()).await;
+
+        let result: std::io::Result<()> = async {
+            let ready = OutgoingMessage::Ready {
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                platform: std::env::consts::OS.to_string(),
+                capabilities: Capabilities::current(),
+                relay_format: crate::relay::DEFAULT_RELAY_FORMAT,
+            };
+            let options = *framing.lock();
+            Self::write_message(&mut writer, &ready, options).await?;
+
+            // Replay last-known state
+            for msg in &replay {
+                let options = *framing.lock();
+                Self::write_message(&mut writer, msg, options).await?;
             }
 
-            tokio::select! {
-                accept_result = listener.accept() => {
-                    match accept_result {
-                        Ok((stream, _)) => {
-                            tracing::info!("Client connected");
-                            let _ = event_loop_proxy.send_event(UserEvent::SocketConnected);
-
-                            let (reader, mut writer) = stream.into_split();
-                            let mut reader = BufReader::new(reader);
-                            let proxy = event_loop_proxy.clone();
-
-                            // Handle incoming messages
-                            let read_handle = tokio::spawn(async move {
-                                let mut line = String::new();
-                                loop {
-                                    line.clear();
-                                    match reader.read_line(&mut line).await {
-                                        Ok(0) => break,
-                                        Ok(_) => {
-                                            if let Err(e) = Self::handle_message(&line.trim(), &proxy) {
-                                                tracing::warn!("Failed to handle message: {}", e);
-                                            }
-                                        }
-                                        Err(e) => {
-                                            tracing::error!("Read error: {}", e);
-                                            break;
-                                        }
-                                    }
-                                }
-                            });
-
-                            // Handle outgoing messages
-                            while let Some(msg) = outgoing.recv().await {
-                                match serde_json::to_string(&msg) {
-                                    Ok(json) => {
-                                        if let Err(e) = writer.write_all(format!("{}\n", json).as_bytes()).await {
-                                            tracing::error!("Write error: {}", e);
-                                            break;
-                                        }
-                                    }
-                                    Err(e) => {
-                                        tracing::error!("Serialization error: {}", e);
-                                    }
+            let mut heartbeat_tick = tokio::time::interval(HEARTBEAT_TICK);
+            heartbeat_tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            let mut last_sent = Instant::now();
+
+            // Handle outgoing messages until the client goes away
+            loop {
+                tokio::select! {
+                    _ = &mut read_handle => return Ok(()),
+                    Some(frame) = frames.pop() => {
+                        let options = *framing.lock();
+                        Self::write_message(&mut writer, &frame, options).await?;
+                        last_sent = Instant::now();
+                    }
+                    msg = messages.recv() => {
+                        let msg = match msg {
+                            Ok(msg) => msg,
+                            Err(broadcast::error::RecvError::Lagged(missed)) => {
+                                tracing::warn!(
+                                    "Client fell behind, skipped {} messages; resending state",
+                                    missed
+                                );
+                                let replay = sticky.lock().snapshot();
+                                for msg in &replay {
+                                    let options = *framing.lock();
+                                    Self::write_message(&mut writer, msg, options).await?;
                                 }
+                                continue;
                             }
-
-                            read_handle.abort();
-                            let _ = event_loop_proxy.send_event(UserEvent::SocketDisconnected);
-                            tracing::info!("Client disconnected");
+                            Err(broadcast::error::RecvError::Closed) => return Ok(()),
+                        };
+                        let options = *framing.lock();
+                        Self::write_message(&mut writer, &msg, options).await?;
+                        last_sent = Instant::now();
+                    }
+                    _ = heartbeat_tick.tick() => {
+                        let Some(heartbeat) = *heartbeat.lock() else { continue };
+                        if last_received.lock().elapsed() > heartbeat.timeout() {
+                            tracing::warn!(
+                                "No message from client for {:?}, dropping connection",
+                                heartbeat.timeout()
+                            );
+                            if heartbeat.shutdown_on_timeout {
+                                let _ = event_loop_proxy.send_event(UserEvent::Terminate);
+                            }
+                            return Ok(());
                         }
-                        Err(e) => {
-                            tracing::error!("Accept error: {}", e);
+                        if last_sent.elapsed() >= heartbeat.interval() {
+                            let msg = OutgoingMessage::Heartbeat {
+                                core_timestamp: crate::unix_millis(),
+                            };
+                            let options = *framing.lock();
+                            Self::write_message(&mut writer, &msg, options).await?;
+                            last_sent = Instant::now();
                         }
                     }
                 }
             }
         }
+        .await;
+
+        if let Err(e) = result {
+            tracing::error!("Write error: {}", e);
+        }
 
-        Ok(())
+        read_handle.abort();
+        let _ = event_loop_proxy.send_event(UserEvent::SocketDisconnected);
+        tracing::info!("Client disconnected");
     }
 
-    fn handle_message(json: &str, proxy: &EventLoopProxy<UserEvent>) -> anyhow::Result<()> {
-        tracing::debug!("Socket received: {}", json);
+    /// Encode and write a single outgoing message
+    async fn write_message<W: AsyncWrite + Unpin>(
+        writer: &mut W,
+        msg: &OutgoingMessage,
+        options: FramingOptions,
+    ) -> std::io::Result<()> {
+        match framing::encode_message(msg, options) {
+            Ok(bytes) => writer.write_all(&bytes).await,
+            Err(e) => {
+                // Not a connection problem - skip the message
+                tracing::error!("Serialization error: {}", e);
+                Ok(())
+            }
+        }
+    }
+
+    fn handle_message(
+        json: &str,
+        proxy: &EventProxy,
+        framing: &Mutex<FramingOptions>,
+        heartbeat: &Mutex<Option<HeartbeatOptions>>,
+    ) -> anyhow::Result<()> {
         let msg: IncomingMessage = serde_json::from_str(json)?;
+        // The raw line of a message carrying credentials is never logged;
+        // its parsed form redacts them
+        if !msg.has_credentials() {
+            tracing::debug!("Socket received: {}", json);
+        }
         tracing::debug!("Parsed message: {:?}", msg);
 
-        let event = match msg {
-            IncomingMessage::JoinRoom { server_url, token } => {
-                UserEvent::JoinRoom { server_url, token }
+        if let IncomingMessage::Unknown = msg {
+            let message_type = serde_json::from_str::<serde_json::Value>(json)
+                .ok()
+                .and_then(|value| value.get("type")?.as_str().map(str::to_string));
+            tracing::warn!("Ignoring unknown message type {:?}", message_type);
+            return Ok(());
+        }
+
+        if let IncomingMessage::Hello {
+            framing: mode,
+            compress,
+            heartbeat: heartbeat_options,
+        } = msg
+        {
+            // Connection-level setting, handled here rather than by the Application
+            if compress && mode != FramingMode::Binary {
+                tracing::warn!("Compression requires binary framing, ignoring");
             }
-            IncomingMessage::LeaveRoom => UserEvent::LeaveRoom,
-            IncomingMessage::GetAvailableContent => UserEvent::GetAvailableContent,
-            IncomingMessage::StartScreenShare {
-                source_id,
-                source_type,
-                config,
-            } => UserEvent::StartScreenShare(crate::ScreenShareMessage {
-                source_id,
-                source_type,
-                config: config.unwrap_or_default(),
-            }),
-            IncomingMessage::StopScreenShare => UserEvent::StopScreenShare,
-            IncomingMessage::SendAnnotation {
-                stroke_id,
+            let options = FramingOptions {
+                mode,
+                compress: compress && mode == FramingMode::Binary,
+            };
+            tracing::info!("Client selected {:?}", options);
+            *framing.lock() = options;
+            if let Some(heartbeat_options) = heartbeat_options {
+                tracing::info!("Client heartbeat every {:?}", heartbeat_options.interval());
+            }
+            *heartbeat.lock() = heartbeat_options;
+            return Ok(());
+        }
+
+        dispatch(proxy, msg)
+    }
+}
+
+/// Send the `UserEvent`s a command maps to
+///
+/// `Hello` only concerns the socket connection and is ignored here.
+pub(crate) fn dispatch(proxy: &EventProxy, msg: IncomingMessage) -> anyhow::Result<()> {
+    let event = match msg {
+        IncomingMessage::JoinRoom {
+            server_url,
+            token,
+            e2ee_passphrase,
+            initial_media,
+            auto_subscribe,
+            relay_format,
+        } => UserEvent::JoinRoom {
+            server_url,
+            token,
+            e2ee_passphrase,
+            initial_media: Box::new(initial_media.unwrap_or_default()),
+            auto_subscribe,
+            relay_format,
+        },
+        IncomingMessage::LeaveRoom => UserEvent::LeaveRoom,
+        IncomingMessage::GetParticipants => UserEvent::GetParticipants,
+        IncomingMessage::SetTrackSubscribed {
+            participant_id,
+            track_id,
+            subscribed,
+        } => UserEvent::SetTrackSubscribed {
+            participant_id,
+            track_id,
+            subscribed,
+        },
+        IncomingMessage::GetAvailableContent { force_refresh } => {
+            UserEvent::GetAvailableContent { force_refresh }
+        }
+        IncomingMessage::StartScreenShare {
+            source_id,
+            source_type,
+            config,
+            replace,
+        } => UserEvent::StartScreenShare(crate::ScreenShareMessage {
+            source_id,
+            source_type,
+            config: config.unwrap_or_default(),
+            replace,
+        }),
+        IncomingMessage::StopScreenShare => UserEvent::StopScreenShare,
+        IncomingMessage::PauseScreenShare => UserEvent::PauseScreenShare,
+        IncomingMessage::ResumeScreenShare => UserEvent::ResumeScreenShare,
+        IncomingMessage::SwitchScreenShareSource { source_id } => {
+            UserEvent::SwitchScreenShareSource { source_id }
+        }
+        IncomingMessage::CaptureStill => UserEvent::CaptureStill,
+        IncomingMessage::GetCaptureConfig => UserEvent::GetCaptureConfig,
+        IncomingMessage::SetCaptureQuality { framerate } => {
+            UserEvent::SetCaptureQuality { framerate }
+        }
+        IncomingMessage::StartRecording { path } => UserEvent::StartRecording { path },
+        IncomingMessage::StopRecording => UserEvent::StopRecording,
+        IncomingMessage::SendAnnotation {
+            stroke_id,
+            tool,
+            color,
+            width,
+            points,
+            share_id,
+        } => {
+            // A finished stroke from the local user: published to peers
+            // as a start carrying the first points, the rest in follow-ups
+            // sized to fit a data packet, then completion - all reliable,
+            // so no point is lost to a dropped update
+            let Some((first_point, rest)) = points.split_first() else {
+                return Ok(()); // No points, nothing to do
+            };
+            let mut batches = rest.chunks(crate::annotation::STROKE_MAX_POINTS_PER_MESSAGE);
+            let _ = proxy.send_event(UserEvent::PublishData(DataTrackMessage::StrokeStart {
+                stroke_id: stroke_id.clone(),
                 tool,
                 color,
-                points,
-            } => {
-                // For local user's stroke, we need to:
-                // 1. Publish to DataTrack (handled by room service)
-                // 2. NOT render locally (annotations show on sharer's screen, not drawer's)
-                // This is a command to publish, not to render
-                if let Some(first_point) = points.first() {
-                    UserEvent::StrokeStart {
-                        stroke_id: stroke_id.clone(),
-                        participant_id: "local".to_string(), // Will be replaced with actual ID
-                        tool,
-                        color,
-                        start_point: *first_point,
-                    }
-                } else {
-                    return Ok(()); // No points, nothing to do
-                }
+                width: Some(tool.stroke_width(width)),
+                point: *first_point,
+                points: batches.next().unwrap_or_default().to_vec(),
+                share_id,
+            }));
+            for batch in batches {
+                let _ = proxy.send_event(UserEvent::PublishData(DataTrackMessage::StrokePoints {
+                    stroke_id: stroke_id.clone(),
+                    points: batch.to_vec(),
+                }));
             }
-            IncomingMessage::DeleteAnnotation { stroke_id } => {
-                UserEvent::StrokeDelete { stroke_id }
-            }
-            IncomingMessage::ClearAnnotations => UserEvent::ClearAllAnnotations,
-            IncomingMessage::CursorMove { x, y } => UserEvent::RemoteCursorPosition {
-                participant_id: "local".to_string(),
+            UserEvent::PublishData(DataTrackMessage::StrokeComplete { stroke_id })
+        }
+        IncomingMessage::DeleteAnnotation { stroke_id } => {
+            UserEvent::PublishData(DataTrackMessage::StrokeDelete { stroke_id })
+        }
+        IncomingMessage::SetTool { tool, color } => UserEvent::SetTool { tool, color },
+        IncomingMessage::ClearAnnotations => UserEvent::PublishData(DataTrackMessage::ClearAll),
+        IncomingMessage::ClearParticipantAnnotations { participant_id } => {
+            UserEvent::PublishData(DataTrackMessage::ClearParticipant { participant_id })
+        }
+        IncomingMessage::SetAnnotationPermission {
+            participant_id,
+            enabled,
+            clear_strokes,
+        } => UserEvent::PublishData(DataTrackMessage::AnnotationPermission {
+            participant_id,
+            enabled,
+            clear_strokes,
+        }),
+        IncomingMessage::CursorMove { x, y, laser } => {
+            UserEvent::PublishData(DataTrackMessage::CursorMove {
                 x,
                 y,
                 visible: true,
-            },
-            IncomingMessage::CursorHide => UserEvent::RemoteCursorPosition {
-                participant_id: "local".to_string(),
-                x: 0.0,
-                y: 0.0,
-                visible: false,
-            },
-            IncomingMessage::SetMicMuted { muted } => UserEvent::SetMicrophoneMuted(muted),
-            IncomingMessage::SetCameraEnabled { enabled } => UserEvent::SetCameraEnabled(enabled),
-            IncomingMessage::SetAudioInputDevice { device_id } => {
-                UserEvent::SetAudioInputDevice(device_id)
-            }
-            IncomingMessage::SetVideoInputDevice { device_id } => {
-                UserEvent::SetVideoInputDevice(device_id)
-            }
-            IncomingMessage::CheckPermissions => UserEvent::CheckPermissions,
-            IncomingMessage::RequestScreenRecordingPermission => {
-                UserEvent::RequestScreenRecordingPermission
-            }
-            IncomingMessage::Ping => {
-                // Respond with pong - but we need the sender
-                // For now, just acknowledge
-                return Ok(());
-            }
-            IncomingMessage::Shutdown => UserEvent::Terminate,
-        };
+                style: laser.then_some(CursorStyle::Laser),
+            })
+        }
+        IncomingMessage::CursorHide => UserEvent::PublishData(DataTrackMessage::CursorMove {
+            x: 0.0,
+            y: 0.0,
+            visible: false,
+            style: None,
+        }),
+        IncomingMessage::SetMicMuted { muted } => UserEvent::SetMicrophoneMuted(muted),
+        IncomingMessage::SetCameraEnabled { enabled } => UserEvent::SetCameraEnabled(enabled),
+        IncomingMessage::SetAudioInputDevice { device_id } => {
+            UserEvent::SetAudioInputDevice(device_id)
+        }
+        IncomingMessage::SetVideoInputDevice { device_id } => {
+            UserEvent::SetVideoInputDevice(device_id)
+        }
+        IncomingMessage::CheckPermissions => UserEvent::CheckPermissions,
+        IncomingMessage::RequestScreenRecordingPermission => {
+            UserEvent::RequestScreenRecordingPermission
+        }
+        IncomingMessage::RequestMicrophonePermission => UserEvent::RequestMicrophonePermission,
+        IncomingMessage::RequestCameraPermission => UserEvent::RequestCameraPermission,
+        // Connection-level setting, handled by the socket
+        IncomingMessage::Hello { .. } => return Ok(()),
+        IncomingMessage::SetLogLevel { filter } => UserEvent::SetLogLevel { filter },
+        IncomingMessage::Ping { timestamp } => UserEvent::Ping { timestamp },
+        IncomingMessage::Shutdown => UserEvent::Terminate,
+        IncomingMessage::Unknown => return Ok(()),
+    };
 
-        tracing::debug!("Sending event to event loop: {:?}", event);
-        proxy.send_event(event)?;
-        Ok(())
-    }
+    tracing::debug!("Sending event to event loop: {:?}", event);
+    proxy.send_event(event)?;
+    Ok(())
 }
@@ -0,0 +1,84 @@
+//! Bounded, latest-wins queue for relayed video frames
+//!
+//! A frame is superseded by the next one from the same track, so when a
+//! reader can't keep up the oldest queued frame is dropped instead of
+//! letting the queue (and Core's memory) grow. Control messages never go
+//! through here; they use a bounded channel that applies back-pressure.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use tokio::sync::Notify;
+
+use super::OutgoingMessage;
+
+pub(crate) struct FrameQueue {
+    state: Mutex<FrameQueueState>,
+    ready: Notify,
+    capacity: usize,
+    /// Frames dropped for lack of space, shared by all queues of a server
+    dropped: Arc<AtomicU64>,
+}
+
+struct FrameQueueState {
+    frames: VecDeque<OutgoingMessage>,
+    closed: bool,
+}
+
+impl FrameQueue {
+    pub(crate) fn new(capacity: usize, dropped: Arc<AtomicU64>) -> Self {
+        Self {
+            state: Mutex::new(FrameQueueState {
+                frames: VecDeque::with_capacity(capacity),
+                closed: false,
+            }),
+            ready: Notify::new(),
+            capacity,
+            dropped,
+        }
+    }
+
+    /// Queue a frame, dropping the oldest one if the queue is full
+    pub(crate) fn push(&self, frame: OutgoingMessage) {
+        {
+            let mut state = self.state.lock();
+            if state.closed {
+                return;
+            }
+            if state.frames.len() >= self.capacity {
+                state.frames.pop_front();
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+            state.frames.push_back(frame);
+        }
+        self.ready.notify_one();
+    }
+
+    /// Next queued frame; None once the queue is closed and empty
+    pub(crate) async fn pop(&self) -> Option<OutgoingMessage> {
+        loop {
+            {
+                let mut state = self.state.lock();
+                if let Some(frame) = state.frames.pop_front() {
+                    return Some(frame);
+                }
+                if state.closed {
+                    return None;
+                }
+            }
+            self.ready.notified().await;
+        }
+    }
+
+    /// Stop accepting frames and wake the reader
+    pub(crate) fn close(&self) {
+        self.state.lock().closed = true;
+        self.ready.notify_one();
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.state.lock().frames.len()
+    }
+}
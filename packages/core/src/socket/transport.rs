@@ -0,0 +1,157 @@
+//! Where the socket server's clients come from
+//!
+//! Core listens on a Unix socket, whose file goes away with the listener
+//! (`UnixSocket`), or a TCP port on Windows. `MemoryTransport` hands the
+//! server in-process duplex streams instead, so tests can drive the real
+//! protocol without touching the filesystem.
+
+use std::future::Future;
+use std::io;
+
+use tokio::io::{AsyncRead, AsyncWrite, DuplexStream, ReadHalf, WriteHalf};
+use tokio::sync::mpsc;
+
+/// Buffer of each in-memory connection, per direction
+const MEMORY_BUFFER_BYTES: usize = 64 * 1024;
+
+/// A source of client connections for `CoreSocket`
+pub trait Transport: Send + 'static {
+    type Reader: AsyncRead + Unpin + Send + 'static;
+    type Writer: AsyncWrite + Unpin + Send + 'static;
+
+    /// Wait for the next client
+    fn accept(&mut self) -> impl Future<Output = io::Result<(Self::Reader, Self::Writer)>> + Send;
+}
+
+#[cfg(unix)]
+impl Transport for tokio::net::UnixListener {
+    type Reader = tokio::net::unix::OwnedReadHalf;
+    type Writer = tokio::net::unix::OwnedWriteHalf;
+
+    async fn accept(&mut self) -> io::Result<(Self::Reader, Self::Writer)> {
+        let (stream, _) = tokio::net::UnixListener::accept(self).await?;
+        Ok(stream.into_split())
+    }
+}
+
+/// A socket file we bound, identified by inode
+///
+/// Removing it leaves the path alone if another Core has since replaced
+/// the socket there with its own.
+#[cfg(unix)]
+#[derive(Debug, Clone)]
+pub struct SocketFile {
+    path: std::path::PathBuf,
+    ino: u64,
+}
+
+#[cfg(unix)]
+impl SocketFile {
+    pub(super) fn new(path: &str) -> io::Result<Self> {
+        use std::os::unix::fs::MetadataExt;
+
+        Ok(Self {
+            path: path.into(),
+            ino: std::fs::symlink_metadata(path)?.ino(),
+        })
+    }
+
+    /// Remove the file, if it is still ours; best effort
+    pub fn remove(&self) {
+        use std::os::unix::fs::MetadataExt;
+
+        let ours = std::fs::symlink_metadata(&self.path).is_ok_and(|m| m.ino() == self.ino);
+        if ours {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Listener on a socket file, which is removed when this is dropped
+#[cfg(unix)]
+pub struct UnixSocket {
+    listener: tokio::net::UnixListener,
+    file: SocketFile,
+}
+
+#[cfg(unix)]
+impl UnixSocket {
+    pub(super) fn new(listener: tokio::net::UnixListener, file: SocketFile) -> Self {
+        Self { listener, file }
+    }
+
+    pub fn file(&self) -> &SocketFile {
+        &self.file
+    }
+}
+
+#[cfg(unix)]
+impl Drop for UnixSocket {
+    fn drop(&mut self) {
+        self.file.remove();
+    }
+}
+
+#[cfg(unix)]
+impl Transport for UnixSocket {
+    type Reader = tokio::net::unix::OwnedReadHalf;
+    type Writer = tokio::net::unix::OwnedWriteHalf;
+
+    async fn accept(&mut self) -> io::Result<(Self::Reader, Self::Writer)> {
+        Transport::accept(&mut self.listener).await
+    }
+}
+
+impl Transport for tokio::net::TcpListener {
+    type Reader = tokio::net::tcp::OwnedReadHalf;
+    type Writer = tokio::net::tcp::OwnedWriteHalf;
+
+    async fn accept(&mut self) -> io::Result<(Self::Reader, Self::Writer)> {
+        let (stream, _) = tokio::net::TcpListener::accept(self).await?;
+        Ok(stream.into_split())
+    }
+}
+
+/// Server side of an in-memory transport
+pub struct MemoryTransport {
+    incoming: mpsc::UnboundedReceiver<DuplexStream>,
+}
+
+/// Client side of an in-memory transport; connects any number of clients
+#[derive(Clone)]
+pub struct MemoryConnector {
+    outgoing: mpsc::UnboundedSender<DuplexStream>,
+}
+
+/// A connected transport/connector pair
+pub fn memory() -> (MemoryTransport, MemoryConnector) {
+    let (outgoing, incoming) = mpsc::unbounded_channel();
+    (MemoryTransport { incoming }, MemoryConnector { outgoing })
+}
+
+impl MemoryConnector {
+    /// Open a connection, as a WebView connecting to the socket would
+    ///
+    /// Fails once the server has stopped.
+    pub fn connect(&self) -> io::Result<DuplexStream> {
+        let (client, server) = tokio::io::duplex(MEMORY_BUFFER_BYTES);
+        self.outgoing.send(server).map_err(|_| {
+            io::Error::new(io::ErrorKind::ConnectionRefused, "socket server stopped")
+        })?;
+        Ok(client)
+    }
+}
+
+impl Transport for MemoryTransport {
+    type Reader = ReadHalf<DuplexStream>;
+    type Writer = WriteHalf<DuplexStream>;
+
+    async fn accept(&mut self) -> io::Result<(Self::Reader, Self::Writer)> {
+        match self.incoming.recv().await {
+            Some(stream) => Ok(tokio::io::split(stream)),
+            // No connector left: nobody can connect again, but the server
+            // keeps running until it is shut down
+            None => std::future::pending().await,
+        }
+    }
+}
@@ -0,0 +1,140 @@
+//! Idle shutdown for orphaned Core processes
+//!
+//! Core is spawned by the Tauri app and must not outlive it. If the app
+//! crashes without calling `kill_core`, nobody sends `Shutdown` and Core
+//! would keep holding the camera and screen forever. The watchdog sends
+//! `Terminate` when no socket client connects within a grace period after
+//! startup, or when the last client disconnects and none returns in time
+//! (a WebView reload reconnects well within it).
+
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+
+use crate::{EventProxy, UserEvent};
+
+/// How long a freshly started Core waits for its first client by default
+const DEFAULT_STARTUP_GRACE: Duration = Duration::from_secs(30);
+
+/// How long Core waits for a client to come back by default
+const DEFAULT_RECONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Environment variable overriding `DEFAULT_STARTUP_GRACE` (seconds, 0 disables)
+const STARTUP_GRACE_ENV: &str = "ETCH_IDLE_STARTUP_GRACE_SECS";
+
+/// Environment variable overriding `DEFAULT_RECONNECT_TIMEOUT` (seconds, 0 disables)
+const RECONNECT_TIMEOUT_ENV: &str = "ETCH_IDLE_RECONNECT_SECS";
+
+/// How long Core may go without a socket client; `None` never shuts down
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IdleTimeouts {
+    /// From startup until the first client connects
+    pub startup_grace: Option<Duration>,
+    /// From the last client disconnecting until one connects again
+    pub reconnect_timeout: Option<Duration>,
+}
+
+impl Default for IdleTimeouts {
+    fn default() -> Self {
+        Self {
+            startup_grace: Some(DEFAULT_STARTUP_GRACE),
+            reconnect_timeout: Some(DEFAULT_RECONNECT_TIMEOUT),
+        }
+    }
+}
+
+impl IdleTimeouts {
+    /// Defaults, overridden by `ETCH_IDLE_STARTUP_GRACE_SECS` and
+    /// `ETCH_IDLE_RECONNECT_SECS`
+    pub fn from_env() -> Self {
+        fn parse(name: &str, default: Option<Duration>) -> Option<Duration> {
+            let Ok(value) = std::env::var(name) else {
+                return default;
+            };
+            match value.parse::<u64>() {
+                Ok(0) => None,
+                Ok(secs) => Some(Duration::from_secs(secs)),
+                Err(_) => {
+                    tracing::warn!("Ignoring invalid {}={:?}", name, value);
+                    default
+                }
+            }
+        }
+
+        let defaults = Self::default();
+        Self {
+            startup_grace: parse(STARTUP_GRACE_ENV, defaults.startup_grace),
+            reconnect_timeout: parse(RECONNECT_TIMEOUT_ENV, defaults.reconnect_timeout),
+        }
+    }
+}
+
+/// Counts socket clients and terminates Core once it has been alone too long
+///
+/// Inactive until `start`, so Core driven through `CoreHandle` without a
+/// socket never shuts itself down.
+#[derive(Debug)]
+pub struct IdleWatchdog {
+    timeouts: IdleTimeouts,
+    started: bool,
+    clients: usize,
+    /// Pending `Terminate`, aborted when a client connects
+    timer: Option<JoinHandle<()>>,
+}
+
+impl IdleWatchdog {
+    pub fn new(timeouts: IdleTimeouts) -> Self {
+        Self {
+            timeouts,
+            started: false,
+            clients: 0,
+            timer: None,
+        }
+    }
+
+    /// Start waiting for the first client (must be called within a tokio runtime)
+    pub fn start(&mut self, proxy: &EventProxy) {
+        self.started = true;
+        if self.clients == 0 {
+            self.arm(self.timeouts.startup_grace, proxy);
+        }
+    }
+
+    /// Handle `SocketConnected`
+    pub fn client_connected(&mut self) {
+        self.clients += 1;
+        self.disarm();
+    }
+
+    /// Handle `SocketDisconnected`
+    pub fn client_disconnected(&mut self, proxy: &EventProxy) {
+        self.clients = self.clients.saturating_sub(1);
+        if self.started && self.clients == 0 {
+            self.arm(self.timeouts.reconnect_timeout, proxy);
+        }
+    }
+
+    fn arm(&mut self, timeout: Option<Duration>, proxy: &EventProxy) {
+        self.disarm();
+        let Some(timeout) = timeout else { return };
+
+        let proxy = proxy.clone();
+        self.timer = Some(tokio::spawn(async move {
+            tokio::time::sleep(timeout).await;
+            tracing::warn!("No socket client for {:?}, shutting down", timeout);
+            let _ = proxy.send_event(UserEvent::Terminate);
+        }));
+    }
+
+    fn disarm(&mut self) {
+        if let Some(timer) = self.timer.take() {
+            timer.abort();
+        }
+    }
+}
+
+impl Drop for IdleWatchdog {
+    fn drop(&mut self) {
+        self.disarm();
+    }
+}
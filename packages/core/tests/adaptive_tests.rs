@@ -0,0 +1,86 @@
+//! Tests for adaptive screen share resolution
+
+use etch_core::capture::adaptive::AdaptiveResolution;
+use etch_core::capture::scaled_size;
+use etch_core::{ConnectionStats, Resolution};
+
+fn stats(packet_loss: f64, rtt_ms: f64, height: u32) -> ConnectionStats {
+    ConnectionStats {
+        outbound_bitrate: 1_000_000,
+        packet_loss,
+        rtt_ms: Some(rtt_ms),
+        fps: 30.0,
+        resolution: Some(Resolution {
+            width: height * 16 / 9,
+            height,
+        }),
+    }
+}
+
+#[test]
+fn test_steps_down_after_sustained_loss() {
+    let mut adaptive = AdaptiveResolution::new();
+
+    assert_eq!(adaptive.observe(&stats(0.1, 50.0, 1440)), None);
+    assert_eq!(adaptive.observe(&stats(0.1, 50.0, 1440)), None);
+    assert_eq!(adaptive.observe(&stats(0.1, 50.0, 1440)), Some(Some(1080)));
+
+    // Next step starts from the height actually published
+    for _ in 0..2 {
+        assert_eq!(adaptive.observe(&stats(0.0, 600.0, 1080)), None);
+    }
+    assert_eq!(adaptive.observe(&stats(0.0, 600.0, 1080)), Some(Some(720)));
+}
+
+#[test]
+fn test_single_bad_sample_does_not_step_down() {
+    let mut adaptive = AdaptiveResolution::new();
+
+    adaptive.observe(&stats(0.1, 50.0, 1080));
+    adaptive.observe(&stats(0.1, 50.0, 1080));
+    adaptive.observe(&stats(0.0, 50.0, 1080));
+    assert_eq!(adaptive.observe(&stats(0.1, 50.0, 1080)), None);
+    assert_eq!(adaptive.max_height(), None);
+}
+
+#[test]
+fn test_steps_back_up_after_recovery() {
+    let mut adaptive = AdaptiveResolution::new();
+    for _ in 0..3 {
+        adaptive.observe(&stats(0.1, 50.0, 1080));
+    }
+    assert_eq!(adaptive.max_height(), Some(720));
+
+    for _ in 0..9 {
+        assert_eq!(adaptive.observe(&stats(0.0, 50.0, 720)), None);
+    }
+    assert_eq!(adaptive.observe(&stats(0.0, 50.0, 720)), Some(Some(1080)));
+
+    for _ in 0..9 {
+        adaptive.observe(&stats(0.0, 50.0, 1080));
+    }
+    assert_eq!(adaptive.observe(&stats(0.0, 50.0, 1080)), Some(None));
+
+    // Already uncapped - nothing left to recover
+    for _ in 0..10 {
+        assert_eq!(adaptive.observe(&stats(0.0, 50.0, 1440)), None);
+    }
+}
+
+#[test]
+fn test_does_not_step_below_ladder() {
+    let mut adaptive = AdaptiveResolution::new();
+    for _ in 0..2 {
+        adaptive.observe(&stats(0.2, 50.0, 540));
+    }
+    assert_eq!(adaptive.observe(&stats(0.2, 50.0, 540)), None);
+}
+
+#[test]
+fn test_scaled_size_keeps_aspect_and_even_dimensions() {
+    assert_eq!(scaled_size(1920, 1080, None), (1920, 1080));
+    assert_eq!(scaled_size(1920, 1080, Some(1080)), (1920, 1080));
+    assert_eq!(scaled_size(1920, 1080, Some(720)), (1280, 720));
+    assert_eq!(scaled_size(2560, 1600, Some(540)), (864, 540));
+    assert_eq!(scaled_size(1366, 768, Some(540)), (960, 540));
+}
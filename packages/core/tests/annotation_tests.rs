@@ -1,7 +1,13 @@
 //! Tests for annotation storage and management
 
-use etch_core::annotation::AnnotationStore;
-use etch_core::{AnnotationTool, Color, Point};
+use std::time::{Duration, Instant};
+
+use etch_core::annotation::{
+    chunk_strokes, AnnotationEvent, AnnotationStore, Stroke, StrokeAcks, SNAPSHOT_CHUNK_BYTES,
+    STROKE_ACK_TIMEOUT, STROKE_MAX_POINTS_PER_MESSAGE, STROKE_RESEND_LIMIT,
+};
+use etch_core::socket::DataTrackMessage;
+use etch_core::{AnnotationTool, Color, Point, MAX_STROKE_WIDTH, MIN_STROKE_WIDTH};
 
 #[test]
 fn test_stroke_lifecycle() {
@@ -13,6 +19,7 @@ fn test_stroke_lifecycle() {
         "participant-1",
         AnnotationTool::Pen,
         Color::RED,
+        0.003,
         Point {
             x: 0.1,
             y: 0.1,
@@ -62,6 +69,7 @@ fn test_clear_all() {
         "p1",
         AnnotationTool::Pen,
         Color::RED,
+        0.003,
         Point {
             x: 0.1,
             y: 0.1,
@@ -73,6 +81,7 @@ fn test_clear_all() {
         "p2",
         AnnotationTool::Highlighter,
         Color::BLUE,
+        0.003,
         Point {
             x: 0.5,
             y: 0.5,
@@ -95,6 +104,7 @@ fn test_delete_by_participant() {
         "p1",
         AnnotationTool::Pen,
         Color::RED,
+        0.003,
         Point {
             x: 0.1,
             y: 0.1,
@@ -106,6 +116,7 @@ fn test_delete_by_participant() {
         "p2",
         AnnotationTool::Pen,
         Color::BLUE,
+        0.003,
         Point {
             x: 0.2,
             y: 0.2,
@@ -117,6 +128,7 @@ fn test_delete_by_participant() {
         "p1",
         AnnotationTool::Pen,
         Color::GREEN,
+        0.003,
         Point {
             x: 0.3,
             y: 0.3,
@@ -140,6 +152,7 @@ fn test_stroke_render_order() {
         "p1",
         AnnotationTool::Pen,
         Color::RED,
+        0.003,
         Point {
             x: 0.1,
             y: 0.1,
@@ -151,6 +164,7 @@ fn test_stroke_render_order() {
         "p2",
         AnnotationTool::Pen,
         Color::BLUE,
+        0.003,
         Point {
             x: 0.2,
             y: 0.2,
@@ -162,6 +176,7 @@ fn test_stroke_render_order() {
         "p1",
         AnnotationTool::Pen,
         Color::GREEN,
+        0.003,
         Point {
             x: 0.3,
             y: 0.3,
@@ -174,6 +189,9 @@ fn test_stroke_render_order() {
     assert_eq!(strokes[0].id, "stroke-a");
     assert_eq!(strokes[1].id, "stroke-b");
     assert_eq!(strokes[2].id, "stroke-c");
+
+    let ids: Vec<&str> = store.strokes_iter().map(|s| s.id.as_str()).collect();
+    assert_eq!(ids, ["stroke-a", "stroke-b", "stroke-c"]);
 }
 
 #[test]
@@ -195,9 +213,518 @@ fn test_highlighter_tool() {
             y: 0.1,
             pressure: 1.0,
         },
+        0.003,
     );
 
     let stroke = store.get("highlight-1").unwrap();
     assert_eq!(stroke.tool, AnnotationTool::Highlighter);
     assert_eq!(stroke.color.a, 128); // Check transparency
 }
+
+#[test]
+fn test_stroke_update_batcher_merges_per_stroke() {
+    use etch_core::annotation::StrokeUpdateBatcher;
+
+    let point = |x: f32| Point {
+        x,
+        y: 0.5,
+        pressure: 1.0,
+    };
+    let mut batcher = StrokeUpdateBatcher::new();
+
+    // First push asks for a flush, later ones ride along
+    assert!(batcher.push("stroke-1", vec![point(0.1)]));
+    assert!(!batcher.push("stroke-2", vec![point(0.5)]));
+    assert!(!batcher.push("stroke-1", vec![point(0.2), point(0.3)]));
+
+    let batch = batcher.take();
+    assert_eq!(batch.len(), 2);
+    assert_eq!(batch[0].0, "stroke-1");
+    let xs: Vec<f32> = batch[0].1.iter().map(|p| p.x).collect();
+    assert_eq!(xs, vec![0.1, 0.2, 0.3]);
+    assert_eq!(batch[1].0, "stroke-2");
+    assert_eq!(batch[1].1.len(), 1);
+
+    assert!(batcher.is_empty());
+    assert!(batcher.push("stroke-3", vec![point(0.9)]));
+}
+
+#[test]
+fn test_disabled_store_ignores_strokes() {
+    let mut store = AnnotationStore::new();
+    store.set_enabled(false);
+    assert!(!store.is_enabled());
+
+    let point = Point {
+        x: 0.1,
+        y: 0.1,
+        pressure: 1.0,
+    };
+    store.start_stroke(
+        "stroke-1",
+        "participant-1",
+        AnnotationTool::Pen,
+        Color::RED,
+        0.003,
+        point,
+    );
+    store.update_stroke("stroke-1", &[point]);
+    store.complete_stroke("stroke-1");
+
+    assert!(store.is_empty());
+}
+
+#[test]
+fn test_disabled_store_still_clears() {
+    let mut store = AnnotationStore::new();
+    let point = Point {
+        x: 0.1,
+        y: 0.1,
+        pressure: 1.0,
+    };
+    store.start_stroke(
+        "stroke-1",
+        "participant-1",
+        AnnotationTool::Pen,
+        Color::RED,
+        0.003,
+        point,
+    );
+
+    store.set_enabled(false);
+    store.delete_stroke("stroke-1");
+    assert_eq!(store.len(), 1);
+
+    store.clear_all();
+    assert!(store.is_empty());
+}
+
+#[test]
+fn test_revoked_participant_cannot_annotate() {
+    let mut store = AnnotationStore::new();
+    let point = Point {
+        x: 0.1,
+        y: 0.1,
+        pressure: 1.0,
+    };
+    store.start_stroke(
+        "stroke-1",
+        "participant-1",
+        AnnotationTool::Pen,
+        Color::RED,
+        0.003,
+        point,
+    );
+
+    store.set_participant_enabled("participant-1", false, false);
+    assert!(!store.can_annotate("participant-1"));
+    assert!(store.can_annotate("participant-2"));
+
+    // Existing stroke is kept but frozen; new strokes are ignored
+    store.update_stroke("stroke-1", &[point]);
+    store.start_stroke(
+        "stroke-2",
+        "participant-1",
+        AnnotationTool::Pen,
+        Color::RED,
+        0.003,
+        point,
+    );
+    store.start_stroke(
+        "stroke-3",
+        "participant-2",
+        AnnotationTool::Pen,
+        Color::BLUE,
+        0.003,
+        point,
+    );
+    assert_eq!(store.get("stroke-1").unwrap().points.len(), 1);
+    assert!(store.get("stroke-2").is_none());
+    assert!(store.get("stroke-3").is_some());
+
+    // Revoking with clear removes the participant's strokes only
+    store.set_participant_enabled("participant-1", false, true);
+    assert!(store.get("stroke-1").is_none());
+    assert_eq!(store.len(), 1);
+
+    store.set_participant_enabled("participant-1", true, false);
+    assert!(store.can_annotate("participant-1"));
+}
+
+#[test]
+fn test_invalid_points_do_not_reach_store() {
+    let mut store = AnnotationStore::new();
+    let point = |x: f32, y: f32| Point {
+        x,
+        y,
+        pressure: 1.0,
+    };
+
+    // A non-finite start point drops the whole stroke
+    store.start_stroke(
+        "stroke-nan",
+        "participant-1",
+        AnnotationTool::Pen,
+        Color::RED,
+        0.003,
+        point(f32::NAN, 0.5),
+    );
+    assert!(store.get("stroke-nan").is_none());
+
+    store.start_stroke(
+        "stroke-1",
+        "participant-1",
+        AnnotationTool::Pen,
+        Color::RED,
+        0.003,
+        point(-0.5, 2.0),
+    );
+    store.update_stroke(
+        "stroke-1",
+        &[
+            point(f32::INFINITY, 0.5),
+            point(0.5, f32::NEG_INFINITY),
+            Point {
+                x: 0.5,
+                y: 0.5,
+                pressure: f32::NAN,
+            },
+            point(1.5, 0.25),
+        ],
+    );
+
+    // Out-of-range points are clamped, non-finite ones dropped
+    let stroke = store.get("stroke-1").unwrap();
+    assert_eq!(stroke.points.len(), 2);
+    assert_eq!((stroke.points[0].x, stroke.points[0].y), (0.0, 1.0));
+    assert_eq!((stroke.points[1].x, stroke.points[1].y), (1.0, 0.25));
+    assert!(stroke
+        .points
+        .iter()
+        .all(|p| p.x.is_finite() && p.y.is_finite() && p.pressure.is_finite()));
+}
+
+fn stroke(id: &str, participant_id: &str, points: usize) -> Stroke {
+    let mut stroke = Stroke::new(
+        id.to_string(),
+        participant_id.to_string(),
+        AnnotationTool::Pen,
+        Color::RED,
+        0.003,
+        Point {
+            x: 0.1,
+            y: 0.1,
+            pressure: 1.0,
+        },
+    );
+    let rest: Vec<Point> = (1..points)
+        .map(|i| Point {
+            x: 0.1,
+            y: i as f32 / points as f32,
+            pressure: 1.0,
+        })
+        .collect();
+    stroke.add_points(&rest);
+    stroke.complete();
+    stroke
+}
+
+#[test]
+fn test_snapshot_keeps_newest_strokes_in_order() {
+    let mut store = AnnotationStore::new();
+    for id in ["stroke-1", "stroke-2", "stroke-3"] {
+        store.insert_stroke(stroke(id, "participant-1", 2));
+    }
+
+    let ids: Vec<String> = store.snapshot(2).into_iter().map(|s| s.id).collect();
+    assert_eq!(ids, ["stroke-2", "stroke-3"]);
+    assert_eq!(store.snapshot(10).len(), 3);
+}
+
+#[test]
+fn test_insert_stroke_skips_known_and_revoked() {
+    let mut store = AnnotationStore::new();
+    assert!(store.insert_stroke(stroke("stroke-1", "participant-1", 3)));
+
+    // A second snapshot doesn't replace what we already have
+    assert!(!store.insert_stroke(stroke("stroke-1", "participant-1", 5)));
+    assert_eq!(store.get("stroke-1").unwrap().points.len(), 3);
+
+    store.set_participant_enabled("participant-2", false, false);
+    assert!(!store.insert_stroke(stroke("stroke-2", "participant-2", 3)));
+
+    let mut invalid = stroke("stroke-3", "participant-1", 1);
+    invalid.points[0].x = f32::NAN;
+    assert!(!store.insert_stroke(invalid));
+    assert_eq!(store.len(), 1);
+}
+
+#[test]
+fn test_chunk_strokes_bounds_payload_size() {
+    let strokes: Vec<Stroke> = (0..20)
+        .map(|i| stroke(&format!("stroke-{:02}", i), "participant-1", 50))
+        .collect();
+    let one = serde_json::to_vec(&strokes[0]).unwrap().len();
+
+    let chunks = chunk_strokes(strokes, one * 3);
+    assert_eq!(chunks.len(), 7);
+    assert!(chunks.iter().all(|chunk| chunk.len() <= 3));
+    assert_eq!(chunks.iter().map(Vec::len).sum::<usize>(), 20);
+
+    // An oversized stroke still goes out, on its own, thinned out to fit
+    let big = stroke("big", "participant-1", 2000);
+    let last = *big.points.last().unwrap();
+    let chunks = chunk_strokes(vec![big], SNAPSHOT_CHUNK_BYTES);
+    assert_eq!(chunks.len(), 1);
+    let thinned = &chunks[0][0];
+    assert!(serde_json::to_vec(thinned).unwrap().len() <= SNAPSHOT_CHUNK_BYTES);
+    assert!(thinned.points.len() > 100);
+    assert_eq!(thinned.points.last().unwrap().y, last.y);
+}
+
+#[test]
+fn test_stroke_message_fits_a_data_packet() {
+    // Coordinates with as many digits as an f32 prints
+    let point = Point {
+        x: 0.123_456_79,
+        y: 0.987_654_3,
+        pressure: 0.555_555_6,
+    };
+    let msg = DataTrackMessage::StrokeStart {
+        stroke_id: uuid::Uuid::new_v4().to_string(),
+        tool: AnnotationTool::Highlighter,
+        color: Color::RED,
+        width: Some(0.003),
+        point,
+        points: vec![point; STROKE_MAX_POINTS_PER_MESSAGE],
+        share_id: Some(uuid::Uuid::new_v4().to_string()),
+    };
+    assert!(serde_json::to_vec(&msg).unwrap().len() <= SNAPSHOT_CHUNK_BYTES);
+}
+
+#[test]
+fn test_event_log_replays_strokes_in_time_order() {
+    let mut store = AnnotationStore::new();
+    let p = |x: f32| Point {
+        x,
+        y: 0.5,
+        pressure: 1.0,
+    };
+
+    store.start_stroke(
+        "stroke-1",
+        "participant-1",
+        AnnotationTool::Pen,
+        Color::RED,
+        0.003,
+        p(0.1),
+    );
+    std::thread::sleep(Duration::from_millis(3));
+    store.update_stroke("stroke-1", &[p(0.2), p(0.3)]);
+    store
+        .start_stroke(
+            "stroke-2",
+            "participant-2",
+            AnnotationTool::Pen,
+            Color::BLUE,
+            0.003,
+            p(0.6),
+        )
+        .unwrap()
+        .share_id = Some("alice".to_string());
+    std::thread::sleep(Duration::from_millis(3));
+    store.update_stroke("stroke-1", &[p(0.4)]);
+    store.complete_stroke("stroke-1");
+
+    let stroke = store.get("stroke-1").unwrap();
+    assert_eq!(stroke.point_times.len(), stroke.points.len());
+    assert!(stroke.point_times.windows(2).all(|w| w[0] <= w[1]));
+
+    let log = store.event_log();
+    assert!(log.windows(2).all(|w| w[0].at() <= w[1].at()));
+    let kinds: Vec<(&str, &str, usize)> = log
+        .iter()
+        .map(|event| match event {
+            AnnotationEvent::StrokeStart { stroke_id, .. } => ("start", stroke_id.as_str(), 1),
+            AnnotationEvent::StrokePoints {
+                stroke_id, points, ..
+            } => ("points", stroke_id.as_str(), points.len()),
+            AnnotationEvent::StrokeComplete { stroke_id, .. } => {
+                ("complete", stroke_id.as_str(), 0)
+            }
+        })
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            ("start", "stroke-1", 1),
+            ("points", "stroke-1", 2),
+            ("start", "stroke-2", 1),
+            ("points", "stroke-1", 1),
+            ("complete", "stroke-1", 0),
+        ]
+    );
+
+    // Replays land on the share each stroke was drawn on
+    let share_ids: Vec<Option<&str>> = log
+        .iter()
+        .filter_map(|event| match event {
+            AnnotationEvent::StrokeStart { share_id, .. } => Some(share_id.as_deref()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(share_ids, [None, Some("alice")]);
+}
+
+#[test]
+fn test_stroke_width_defaults_and_range() {
+    let pen = AnnotationTool::Pen;
+    let highlighter = AnnotationTool::Highlighter;
+    assert!(highlighter.default_width() > pen.default_width());
+
+    assert_eq!(pen.stroke_width(None), pen.default_width());
+    assert_eq!(pen.stroke_width(Some(0.0)), pen.default_width());
+    assert_eq!(pen.stroke_width(Some(f32::NAN)), pen.default_width());
+    assert_eq!(pen.stroke_width(Some(0.01)), 0.01);
+    assert_eq!(pen.stroke_width(Some(1.0)), MAX_STROKE_WIDTH);
+    assert_eq!(pen.stroke_width(Some(1e-6)), MIN_STROKE_WIDTH);
+
+    let mut store = AnnotationStore::new();
+    store.start_stroke(
+        "wide",
+        "p1",
+        highlighter,
+        Color::RED,
+        f32::INFINITY,
+        Point {
+            x: 0.5,
+            y: 0.5,
+            pressure: 1.0,
+        },
+    );
+    assert_eq!(
+        store.get("wide").unwrap().width,
+        highlighter.default_width()
+    );
+
+    // Snapshots from peers without widths draw at the tool's default
+    let mut old = stroke("old", "p2", 2);
+    old.width = 0.0;
+    assert!(store.insert_stroke(old));
+    assert_eq!(store.get("old").unwrap().width, pen.default_width());
+}
+
+#[test]
+fn test_snapshot_completes_a_stroke_left_incomplete() {
+    let mut store = AnnotationStore::new();
+    store.start_stroke(
+        "stroke-1",
+        "participant-1",
+        AnnotationTool::Pen,
+        Color::RED,
+        0.003,
+        Point {
+            x: 0.1,
+            y: 0.1,
+            pressure: 1.0,
+        },
+    );
+
+    // StrokeComplete and the remaining points never arrived
+    assert!(store.insert_stroke(stroke("stroke-1", "participant-1", 4)));
+    let repaired = store.get("stroke-1").unwrap();
+    assert!(repaired.completed);
+    assert_eq!(repaired.points.len(), 4);
+    assert_eq!(store.strokes().len(), 1);
+
+    // Another author can't take the stroke over
+    store.start_stroke(
+        "stroke-2",
+        "participant-1",
+        AnnotationTool::Pen,
+        Color::RED,
+        0.003,
+        Point {
+            x: 0.1,
+            y: 0.1,
+            pressure: 1.0,
+        },
+    );
+    assert!(!store.insert_stroke(stroke("stroke-2", "participant-2", 4)));
+    assert!(!store.get("stroke-2").unwrap().completed);
+}
+
+#[test]
+fn test_stroke_acks_resend_until_acknowledged() {
+    let start = Instant::now();
+    let mut acks = StrokeAcks::new();
+    assert!(acks.track("stroke-1", ["alice".to_string(), "bob".to_string()], start));
+    assert!(!acks.track("stroke-2", ["alice".to_string()], start));
+
+    assert!(acks.due(start).is_empty());
+
+    acks.ack("stroke-1", "alice");
+    acks.ack("stroke-2", "alice");
+    let later = start + STROKE_ACK_TIMEOUT;
+    assert_eq!(
+        acks.due(later),
+        vec![("stroke-1".to_string(), vec!["bob".to_string()])]
+    );
+    // Not due again until another timeout has passed
+    assert!(acks.due(later).is_empty());
+
+    acks.peer_left("bob");
+    assert!(acks.is_empty());
+}
+
+#[test]
+fn test_stroke_acks_give_up_after_resend_limit() {
+    let mut now = Instant::now();
+    let mut acks = StrokeAcks::new();
+    acks.track("stroke-1", ["alice".to_string()], now);
+
+    for _ in 0..STROKE_RESEND_LIMIT {
+        now += STROKE_ACK_TIMEOUT;
+        assert_eq!(acks.due(now).len(), 1);
+    }
+    now += STROKE_ACK_TIMEOUT;
+    assert!(acks.due(now).is_empty());
+    assert!(acks.is_empty());
+
+    // Nobody to wait for
+    assert!(!acks.track("stroke-2", Vec::new(), now));
+    assert!(acks.is_empty());
+}
+
+#[test]
+fn test_strokes_for_share() {
+    let mut store = AnnotationStore::new();
+    let point = Point {
+        x: 0.5,
+        y: 0.5,
+        pressure: 1.0,
+    };
+    for (stroke_id, share_id) in [("a", Some("alice")), ("b", Some("bob")), ("old", None)] {
+        let stroke = store
+            .start_stroke(
+                stroke_id,
+                "p1",
+                AnnotationTool::Pen,
+                Color::RED,
+                0.003,
+                point,
+            )
+            .unwrap();
+        stroke.share_id = share_id.map(str::to_string);
+    }
+
+    let ids = |share_id| -> Vec<String> {
+        store
+            .strokes_for_share(share_id)
+            .map(|s| s.id.clone())
+            .collect()
+    };
+    assert_eq!(ids(Some("alice")), ["a", "old"]);
+    assert_eq!(ids(Some("bob")), ["b", "old"]);
+    assert_eq!(ids(None), ["a", "b", "old"]);
+}
@@ -0,0 +1,523 @@
+//! Tests for driving the Application through CoreHandle
+//!
+//! These run the real event handlers, draining the application's event
+//! channel on the test thread the way `Application::run_headless` does.
+
+use std::sync::mpsc::Receiver;
+use std::time::{Duration, Instant};
+
+use etch_core::socket::IncomingMessage;
+use etch_core::{
+    AnnotationTool, Application, Color, ConnectionState, CursorStyle, EventProxy, ParticipantData,
+    ParticipantRole, Point, Resolution, TrackSourceType, UserEvent, ANIMATION_FRAME_INTERVAL,
+    CURSOR_SMOOTHING, LASER_TRAIL_DURATION,
+};
+
+fn new_app() -> (Application, Receiver<UserEvent>) {
+    let (sender, events) = std::sync::mpsc::channel();
+    let app = Application::new(EventProxy::Channel(sender), Default::default());
+    (app, events)
+}
+
+/// Handle queued events, including the ones handlers dispatch in turn
+fn drain(app: &mut Application, events: &Receiver<UserEvent>) {
+    while let Ok(event) = events.try_recv() {
+        app.handle_user_event(event);
+    }
+}
+
+fn point(x: f32, y: f32) -> Point {
+    Point {
+        x,
+        y,
+        pressure: 1.0,
+    }
+}
+
+#[tokio::test]
+async fn test_no_capture_config_while_not_sharing() {
+    let (app, _events) = new_app();
+    assert!(app.capture_config().is_none());
+}
+
+#[tokio::test]
+async fn test_send_annotation_keeps_custom_color() {
+    let (mut app, events) = new_app();
+    let core = app.handle();
+
+    // Off the palette and translucent
+    let faint = Color::rgba(12, 200, 180, 255).with_alpha(64);
+    core.send_annotation(
+        "stroke-1",
+        AnnotationTool::Highlighter,
+        faint,
+        None,
+        vec![point(0.1, 0.1), point(0.2, 0.2)],
+    )
+    .unwrap();
+    drain(&mut app, &events);
+
+    assert_eq!(app.annotations().get("stroke-1").unwrap().color, faint);
+}
+
+#[tokio::test]
+async fn test_send_annotation_adds_local_stroke() {
+    let (mut app, events) = new_app();
+    let core = app.handle();
+
+    core.send_annotation(
+        "stroke-1",
+        AnnotationTool::Pen,
+        Color::RED,
+        None,
+        vec![point(0.1, 0.1), point(0.2, 0.2), point(0.3, 0.3)],
+    )
+    .unwrap();
+    drain(&mut app, &events);
+
+    let stroke = app.annotations().get("stroke-1").unwrap();
+    assert_eq!(stroke.points.len(), 3);
+    assert_eq!(stroke.width, AnnotationTool::Pen.default_width());
+    assert!(stroke.completed);
+
+    core.clear_annotations().unwrap();
+    drain(&mut app, &events);
+    assert!(app.annotations().is_empty());
+}
+
+#[tokio::test]
+async fn test_long_stroke_keeps_every_point() {
+    let (mut app, events) = new_app();
+    let points: Vec<Point> = (0..1000).map(|i| point(i as f32 / 1000.0, 0.5)).collect();
+
+    // More points than one data packet holds: split across messages
+    app.handle()
+        .send_annotation("long", AnnotationTool::Pen, Color::RED, None, points)
+        .unwrap();
+    drain(&mut app, &events);
+
+    let stroke = app.annotations().get("long").unwrap();
+    assert_eq!(stroke.points.len(), 1000);
+    assert_eq!(stroke.points[999].x, 0.999);
+    assert!(stroke.completed);
+}
+
+#[tokio::test]
+async fn test_annotations_follow_displayed_share() {
+    let (mut app, events) = new_app();
+    let core = app.handle();
+    for sharer in ["alice", "bob"] {
+        app.handle_user_event(UserEvent::RemoteTrackPublished {
+            participant_id: sharer.to_string(),
+            track_id: format!("TR_{sharer}"),
+            source: TrackSourceType::ScreenShare,
+        });
+    }
+    drain(&mut app, &events);
+    // The first share takes focus
+    assert_eq!(app.displayed_share_id(), Some("alice"));
+
+    // Drawn locally: targets the share on screen
+    core.send_annotation(
+        "local",
+        AnnotationTool::Pen,
+        Color::RED,
+        None,
+        vec![point(0.1, 0.1)],
+    )
+    .unwrap();
+    drain(&mut app, &events);
+
+    // From peers: one per share, and one from a peer predating share ids
+    for (stroke_id, share_id) in [
+        ("on-alice", Some("alice")),
+        ("on-bob", Some("bob")),
+        ("legacy", None),
+    ] {
+        let payload = serde_json::json!({
+            "type": "stroke_start",
+            "stroke_id": stroke_id,
+            "tool": "pen",
+            "color": {"r": 0, "g": 0, "b": 255, "a": 255},
+            "point": {"x": 0.5, "y": 0.5},
+            "share_id": share_id,
+        });
+        app.handle_user_event(UserEvent::DataReceived {
+            participant_id: "carol".to_string(),
+            payload: serde_json::to_vec(&payload).unwrap(),
+        });
+    }
+    drain(&mut app, &events);
+
+    let local = app.annotations().get("local").unwrap();
+    assert_eq!(local.share_id.as_deref(), Some("alice"));
+    let displayed: Vec<&str> = app.displayed_annotations().map(|s| s.id.as_str()).collect();
+    assert_eq!(displayed, ["local", "on-alice", "legacy"]);
+    assert_eq!(app.annotations().len(), 4);
+}
+
+#[tokio::test]
+async fn test_share_focus_follows_publications() {
+    let (mut app, events) = new_app();
+    for sharer in ["alice", "bob"] {
+        app.handle_user_event(UserEvent::RemoteTrackPublished {
+            participant_id: sharer.to_string(),
+            track_id: format!("TR_{sharer}"),
+            source: TrackSourceType::ScreenShare,
+        });
+    }
+    drain(&mut app, &events);
+    assert_eq!(app.displayed_share_id(), Some("alice"));
+
+    // Not subscribing to the focused share leaves it focused
+    app.handle_user_event(UserEvent::TrackUnsubscribed {
+        participant_id: "alice".to_string(),
+        track_id: "TR_alice".to_string(),
+    });
+    drain(&mut app, &events);
+    assert_eq!(app.displayed_share_id(), Some("alice"));
+
+    // Ending it moves focus to the remaining share
+    app.handle_user_event(UserEvent::RemoteTrackUnpublished {
+        participant_id: "alice".to_string(),
+        track_id: "TR_alice".to_string(),
+    });
+    drain(&mut app, &events);
+    assert_eq!(app.displayed_share_id(), Some("bob"));
+}
+
+#[tokio::test]
+async fn test_capture_panic_ends_share() {
+    let (mut app, events) = new_app();
+    app.handle_user_event(UserEvent::ScreenShareStateChanged {
+        is_sharing: true,
+        source_id: Some("screen:1".to_string()),
+    });
+    assert!(app.is_sharing());
+
+    // Sent by the capture thread before it has finished exiting
+    app.handle_user_event(UserEvent::CapturePanicked {
+        message: "Screen capture crashed: boom".to_string(),
+    });
+    drain(&mut app, &events);
+    assert!(!app.is_sharing());
+}
+
+#[tokio::test]
+async fn test_set_tool_updates_current_tool() {
+    let (mut app, events) = new_app();
+    assert_eq!(app.current_tool(), (AnnotationTool::Pen, Color::RED));
+
+    app.handle()
+        .set_tool(AnnotationTool::Highlighter, Color::BLUE)
+        .unwrap();
+    drain(&mut app, &events);
+
+    assert_eq!(
+        app.current_tool(),
+        (AnnotationTool::Highlighter, Color::BLUE)
+    );
+}
+
+#[tokio::test]
+async fn test_capture_resolution_tracks_display_and_resets_on_stop() {
+    let (mut app, _events) = new_app();
+    assert_eq!(app.capture_resolution(), None);
+
+    app.handle_user_event(UserEvent::CaptureResolutionChanged {
+        width: 2560,
+        height: 1440,
+    });
+    app.handle_user_event(UserEvent::CaptureResolutionChanged {
+        width: 1920,
+        height: 1080,
+    });
+    assert_eq!(
+        app.capture_resolution(),
+        Some(Resolution {
+            width: 1920,
+            height: 1080
+        })
+    );
+
+    app.handle_user_event(UserEvent::ScreenShareStateChanged {
+        is_sharing: false,
+        source_id: None,
+    });
+    assert_eq!(app.capture_resolution(), None);
+}
+
+#[tokio::test]
+async fn test_shutdown_requests_exit() {
+    let (mut app, events) = new_app();
+    assert_eq!(app.connection_state(), ConnectionState::Disconnected);
+    assert!(!app.exit_requested());
+
+    app.handle().shutdown().unwrap();
+    drain(&mut app, &events);
+
+    assert!(app.exit_requested());
+}
+
+fn participant(id: &str, is_local: bool) -> ParticipantData {
+    ParticipantData {
+        id: id.to_string(),
+        name: id.to_string(),
+        is_local,
+        role: ParticipantRole::Participant,
+        metadata: String::new(),
+    }
+}
+
+#[tokio::test]
+async fn test_participants_roster_lists_local_first() {
+    let (mut app, events) = new_app();
+    assert!(app.participants().is_empty());
+
+    app.handle_user_event(UserEvent::ParticipantConnected(participant("carol", false)));
+    app.handle_user_event(UserEvent::ParticipantConnected(participant("bob", false)));
+    app.handle_user_event(UserEvent::LocalParticipant(participant("alice", true)));
+    app.handle().get_participants().unwrap();
+    drain(&mut app, &events);
+
+    let participants = app.participants();
+    let ids: Vec<&str> = participants.iter().map(|p| p.id.as_str()).collect();
+    assert_eq!(ids, ["alice", "bob", "carol"]);
+    assert!(participants[0].is_local);
+    assert!(participants[1..].iter().all(|p| !p.is_local));
+}
+
+/// Deliver a DataTrack message as if `participant_id` had sent it
+fn receive(app: &mut Application, participant_id: &str, payload: serde_json::Value) {
+    app.handle_user_event(UserEvent::DataReceived {
+        participant_id: participant_id.to_string(),
+        payload: serde_json::to_vec(&payload).unwrap(),
+    });
+}
+
+#[tokio::test]
+async fn test_annotation_permission_only_from_host() {
+    let (mut app, events) = new_app();
+    let host = ParticipantData {
+        role: ParticipantRole::Host,
+        ..participant("hana", false)
+    };
+    for data in [
+        host,
+        participant("bob", false),
+        participant("mallory", false),
+    ] {
+        app.handle_user_event(UserEvent::ParticipantConnected(data));
+    }
+    receive(
+        &mut app,
+        "bob",
+        serde_json::json!({
+            "type": "stroke_start",
+            "stroke_id": "bob-1",
+            "tool": "pen",
+            "color": {"r": 0, "g": 0, "b": 255, "a": 255},
+            "point": {"x": 0.5, "y": 0.5},
+        }),
+    );
+    drain(&mut app, &events);
+    assert!(app.annotations().get("bob-1").is_some());
+
+    let revoke = serde_json::json!({
+        "type": "annotation_permission",
+        "participant_id": "bob",
+        "enabled": false,
+        "clear_strokes": true,
+    });
+    receive(&mut app, "mallory", revoke.clone());
+    drain(&mut app, &events);
+    assert!(app.annotations().get("bob-1").is_some());
+
+    receive(&mut app, "hana", revoke);
+    drain(&mut app, &events);
+    assert!(app.annotations().get("bob-1").is_none());
+}
+
+#[tokio::test]
+async fn test_snapshot_strokes_by_others_only_from_host_or_sharer() {
+    let (mut app, events) = new_app();
+    let host = ParticipantData {
+        role: ParticipantRole::Host,
+        ..participant("hana", false)
+    };
+    for data in [
+        host,
+        participant("sam", false),
+        participant("mallory", false),
+    ] {
+        app.handle_user_event(UserEvent::ParticipantConnected(data));
+    }
+    app.handle_user_event(UserEvent::RemoteTrackPublished {
+        participant_id: "sam".to_string(),
+        track_id: "TR_sam".to_string(),
+        source: TrackSourceType::ScreenShare,
+    });
+    drain(&mut app, &events);
+
+    let snapshot = |stroke_id: &str, author: &str| {
+        serde_json::json!({
+            "type": "snapshot",
+            "strokes": [{
+                "id": stroke_id,
+                "participant_id": author,
+                "tool": "pen",
+                "color": {"r": 0, "g": 0, "b": 255, "a": 255},
+                "points": [{"x": 0.5, "y": 0.5, "pressure": 1.0}],
+                "completed": true,
+            }],
+        })
+    };
+    receive(&mut app, "mallory", snapshot("forged", "hana"));
+    receive(&mut app, "mallory", snapshot("own", "mallory"));
+    receive(&mut app, "hana", snapshot("from-host", "bob"));
+    receive(&mut app, "sam", snapshot("from-sharer", "bob"));
+    drain(&mut app, &events);
+
+    assert!(app.annotations().get("forged").is_none());
+    for stroke_id in ["own", "from-host", "from-sharer"] {
+        assert!(app.annotations().get(stroke_id).is_some(), "{stroke_id}");
+    }
+}
+
+fn cursor_move(participant_id: &str, x: f32, y: f32, visible: bool) -> UserEvent {
+    UserEvent::RemoteCursorPosition {
+        participant_id: participant_id.to_string(),
+        x,
+        y,
+        visible,
+    }
+}
+
+#[tokio::test]
+async fn test_remote_cursor_glides_to_reported_position() {
+    let (mut app, _events) = new_app();
+
+    // A new cursor appears where it was first reported
+    app.handle_user_event(cursor_move("bob", 0.2, 0.2, true));
+    assert!(!app.advance_cursors(CURSOR_SMOOTHING));
+
+    app.handle_user_event(cursor_move("bob", 0.8, 0.2, true));
+    assert!(app.advance_cursors(CURSOR_SMOOTHING));
+    let cursor = app.remote_cursors().next().unwrap();
+    assert!(cursor.x > 0.2 && cursor.x < 0.8);
+    assert_eq!(cursor.target_x, 0.8);
+
+    // Converges within a few time constants
+    for _ in 0..20 {
+        app.advance_cursors(CURSOR_SMOOTHING);
+    }
+    assert_eq!(app.remote_cursors().next().unwrap().x, 0.8);
+    assert!(!app.advance_cursors(Duration::from_millis(16)));
+}
+
+#[tokio::test]
+async fn test_animation_clock_runs_only_while_cursors_move() {
+    let (mut app, _events) = new_app();
+    let start = Instant::now();
+    assert_eq!(app.animate(start), None);
+
+    app.handle_user_event(cursor_move("bob", 0.2, 0.2, true));
+    app.handle_user_event(cursor_move("bob", 0.8, 0.2, true));
+    // Long after the last frame: steps one frame, not the whole gap
+    let now = start + Duration::from_secs(10);
+    assert_eq!(app.animate(now), Some(now + ANIMATION_FRAME_INTERVAL));
+    let x = app.remote_cursors().next().unwrap().x;
+    assert!(x > 0.2 && x < 0.5);
+
+    // Events arriving between frames don't step (or send) an extra frame
+    let between = now + ANIMATION_FRAME_INTERVAL / 2;
+    assert_eq!(app.animate(between), Some(now + ANIMATION_FRAME_INTERVAL));
+    assert_eq!(app.remote_cursors().next().unwrap().x, x);
+
+    // Frame by frame until the cursor settles, then back to idle
+    let mut frame = now;
+    let mut frames = 0;
+    while let Some(next) = app.animate(frame) {
+        frame = next;
+        frames += 1;
+        assert!(frames < 100, "cursor never settled");
+    }
+    assert_eq!(app.remote_cursors().next().unwrap().x, 0.8);
+    assert_eq!(app.animate(frame + Duration::from_secs(1)), None);
+}
+
+#[tokio::test]
+async fn test_reappearing_cursor_does_not_glide() {
+    let (mut app, _events) = new_app();
+    app.handle_user_event(cursor_move("bob", 0.2, 0.2, true));
+    app.handle_user_event(cursor_move("bob", 0.2, 0.2, false));
+
+    app.handle_user_event(cursor_move("bob", 0.9, 0.9, true));
+    let cursor = app.remote_cursors().next().unwrap();
+    assert_eq!((cursor.x, cursor.y), (0.9, 0.9));
+}
+
+#[tokio::test]
+async fn test_join_with_microphone_or_camera_is_refused() {
+    for media in [r#"{"microphone":true}"#, r#"{"camera":true}"#] {
+        let (mut app, events) = new_app();
+        let json = format!(
+            r#"{{"type":"join_room","server_url":"wss://livekit.example.com","token":"eyJ...","initial_media":{}}}"#,
+            media
+        );
+        app.handle()
+            .send(serde_json::from_str(&json).unwrap())
+            .unwrap();
+        drain(&mut app, &events);
+
+        // Refused up front: no connection attempt was started
+        assert_eq!(
+            app.connection_state(),
+            ConnectionState::Disconnected,
+            "{media}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_laser_pointer_fades_without_strokes() {
+    let (mut app, events) = new_app();
+    let core = app.handle();
+
+    for x in [0.2, 0.5] {
+        core.send(IncomingMessage::CursorMove {
+            x,
+            y: 0.5,
+            laser: true,
+        })
+        .unwrap();
+        drain(&mut app, &events);
+    }
+    assert!(app.annotations().is_empty());
+    let cursor = app.remote_cursors().next().unwrap();
+    assert_eq!(cursor.style, CursorStyle::Laser);
+
+    // Gliding toward the new position leaves a trail behind the dot
+    assert!(app.advance_cursors(Duration::from_millis(16)));
+    assert!(!app.remote_cursors().next().unwrap().trail.is_empty());
+
+    // Once the dot settles the trail fades out and redraws stop
+    for _ in 0..50 {
+        app.advance_cursors(Duration::from_millis(16));
+    }
+    assert!(app.remote_cursors().next().unwrap().trail.is_empty());
+    assert!(!app.advance_cursors(LASER_TRAIL_DURATION));
+
+    // A plain move turns the pointer back into a cursor
+    core.send(IncomingMessage::CursorMove {
+        x: 0.5,
+        y: 0.5,
+        laser: false,
+    })
+    .unwrap();
+    drain(&mut app, &events);
+    assert_ne!(
+        app.remote_cursors().next().unwrap().style,
+        CursorStyle::Laser
+    );
+}
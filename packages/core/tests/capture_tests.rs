@@ -0,0 +1,139 @@
+//! Tests for the screen capturer's lifecycle
+
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use etch_core::capture::audio::interleave_f32_planes;
+use etch_core::capture::convert::abgr_to_nv12;
+use etch_core::capture::{
+    next_loop_event, CaptureState, Capturer, LoopEvent, PixelFormat, StreamMessage,
+};
+use etch_core::{CaptureConfig, SourceType};
+
+/// Poll until the capture thread has ended on its own
+fn wait_for_exit(capturer: &Capturer) {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !capturer.capture_thread_exited() {
+        assert!(Instant::now() < deadline, "capture thread didn't exit");
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn test_capturer_idle_after_capture_thread_exits() {
+    let mut capturer = Capturer::new();
+    capturer
+        .start_capture(
+            "screen:4294967295",
+            SourceType::Screen,
+            &CaptureConfig::default(),
+        )
+        .unwrap();
+
+    // No such display (or no display at all), so the thread ends without
+    // being stopped
+    wait_for_exit(&capturer);
+    assert!(!capturer.is_capturing());
+    assert_eq!(capturer.state(), CaptureState::Idle);
+    assert!(!capturer.pause_capture());
+    assert!(!capturer.capture_still());
+
+    capturer.stop_capture();
+    assert!(!capturer.capture_thread_exited());
+}
+
+#[test]
+fn test_capture_loop_exits_when_control_channel_disconnects() {
+    let interval = Duration::from_millis(1);
+    let (tx, rx) = mpsc::channel();
+    assert!(matches!(next_loop_event(&rx, interval), LoopEvent::Tick));
+
+    tx.send(StreamMessage::SetPaused(true)).unwrap();
+    assert!(matches!(
+        next_loop_event(&rx, interval),
+        LoopEvent::Message(StreamMessage::SetPaused(true))
+    ));
+
+    // The Capturer holding the sender went away without stopping capture
+    drop(tx);
+    assert!(matches!(next_loop_event(&rx, interval), LoopEvent::Exit(_)));
+}
+
+#[test]
+fn test_capture_loop_exits_on_stop_and_failure() {
+    for msg in [StreamMessage::Stop, StreamMessage::Failed] {
+        let (tx, rx) = mpsc::channel();
+        tx.send(msg).unwrap();
+        assert!(matches!(
+            next_loop_event(&rx, Duration::from_secs(1)),
+            LoopEvent::Exit(_)
+        ));
+    }
+}
+
+#[test]
+fn test_pixel_format_defaults_to_i420() {
+    let config: CaptureConfig =
+        serde_json::from_str(r#"{"width":1920,"height":1080,"framerate":30,"bitrate":4000000}"#)
+            .unwrap();
+    assert_eq!(config.pixel_format, PixelFormat::I420);
+
+    let config: CaptureConfig = serde_json::from_str(
+        r#"{"width":1920,"height":1080,"framerate":30,"bitrate":4000000,"pixel_format":"nv12"}"#,
+    )
+    .unwrap();
+    assert_eq!(config.pixel_format, PixelFormat::Nv12);
+}
+
+#[test]
+fn test_abgr_to_nv12_matches_bt601() {
+    // 2x2: white, black / red, blue (bytes R, G, B, A)
+    let src = [
+        255, 255, 255, 255, 0, 0, 0, 255, //
+        255, 0, 0, 255, 0, 0, 255, 255,
+    ];
+    let mut y = [0u8; 4];
+    let mut uv = [0u8; 2];
+    abgr_to_nv12(&src, 8, &mut y, 2, &mut uv, 2, 2, 2);
+
+    assert_eq!(y, [235, 16, 82, 41]);
+    // Chroma of the block's average color (128, 64, 128)
+    assert_eq!(uv, [147, 152]);
+}
+
+#[test]
+fn test_abgr_to_nv12_odd_size() {
+    // 3x3 mid-gray, with padded strides
+    let src: Vec<u8> = (0..3)
+        .flat_map(|_| [[128, 128, 128, 255]; 4].concat())
+        .collect();
+    let mut y = [0u8; 4 * 3];
+    let mut uv = [0u8; 4 * 2];
+    abgr_to_nv12(&src, 16, &mut y, 4, &mut uv, 4, 3, 3);
+
+    for row in y.chunks(4) {
+        assert_eq!(row[..3], [126, 126, 126]);
+        assert_eq!(row[3], 0, "padding left alone");
+    }
+    for row in uv.chunks(4) {
+        assert_eq!(row, [128, 128, 128, 128]);
+    }
+}
+
+#[test]
+fn test_interleave_planar_system_audio() {
+    let plane =
+        |samples: &[f32]| -> Vec<u8> { samples.iter().flat_map(|s| s.to_ne_bytes()).collect() };
+    let left = plane(&[1.0, -1.0, 0.0]);
+    let right = plane(&[0.5, 2.0]);
+
+    assert_eq!(
+        interleave_f32_planes(&[&left, &right]),
+        vec![i16::MAX, i16::MAX / 2, -i16::MAX, i16::MAX]
+    );
+    // A single buffer is already interleaved
+    assert_eq!(
+        interleave_f32_planes(&[&left]),
+        vec![i16::MAX, -i16::MAX, 0]
+    );
+}
@@ -0,0 +1,32 @@
+//! Tests for monotonic frame timestamps
+
+use std::time::Duration;
+
+use etch_core::clock::{self, FrameClock};
+
+#[test]
+fn test_frame_clock_strictly_increasing() {
+    let clock = FrameClock::micros();
+
+    // Far faster than the clock ticks - ties must still be broken
+    let mut last = 0;
+    for _ in 0..10_000 {
+        let next = clock.next();
+        assert!(next > last);
+        last = next;
+    }
+}
+
+#[test]
+fn test_frame_clock_millis_follows_elapsed_time() {
+    clock::start();
+    // Past the epoch, so the first timestamp is not nudged forward
+    std::thread::sleep(Duration::from_millis(5));
+    let frame_clock = FrameClock::millis();
+
+    let first = frame_clock.next();
+    std::thread::sleep(Duration::from_millis(20));
+    let second = frame_clock.next();
+
+    assert!(second >= first + 20);
+}
@@ -0,0 +1,54 @@
+//! Tests for DataTrack end-to-end encryption
+//!
+//! Run with: cargo test --features e2ee
+
+#![cfg(feature = "e2ee")]
+
+use etch_core::room::e2ee::DataCipher;
+
+#[test]
+fn test_round_trip() {
+    let cipher = DataCipher::from_passphrase("correct horse battery staple");
+    let plaintext = br#"{"type":"clear_all"}"#;
+
+    let sealed = cipher.encrypt(plaintext).unwrap();
+    assert_ne!(&sealed[..], &plaintext[..]);
+
+    let opened = cipher.decrypt(&sealed).unwrap();
+    assert_eq!(opened, plaintext);
+}
+
+#[test]
+fn test_same_passphrase_interoperates() {
+    let sender = DataCipher::from_passphrase("shared");
+    let receiver = DataCipher::from_passphrase("shared");
+
+    let sealed = sender.encrypt(b"hello").unwrap();
+    assert_eq!(receiver.decrypt(&sealed).unwrap(), b"hello");
+}
+
+#[test]
+fn test_wrong_passphrase_fails() {
+    let sender = DataCipher::from_passphrase("shared");
+    let receiver = DataCipher::from_passphrase("different");
+
+    let sealed = sender.encrypt(b"hello").unwrap();
+    assert!(receiver.decrypt(&sealed).is_err());
+}
+
+#[test]
+fn test_plaintext_and_truncated_payloads_fail() {
+    let cipher = DataCipher::from_passphrase("shared");
+
+    assert!(cipher.decrypt(br#"{"type":"clear_all"}"#).is_err());
+    assert!(cipher.decrypt(b"short").is_err());
+}
+
+#[test]
+fn test_nonce_is_random_per_message() {
+    let cipher = DataCipher::from_passphrase("shared");
+
+    let a = cipher.encrypt(b"same").unwrap();
+    let b = cipher.encrypt(b"same").unwrap();
+    assert_ne!(a, b);
+}
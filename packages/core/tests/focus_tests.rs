@@ -0,0 +1,79 @@
+//! Tests for picking the remote screen share to spotlight
+
+use std::time::{Duration, Instant};
+
+use etch_core::room::focus::{FocusedShare, ShareFocus, FOCUS_HOLD};
+
+fn share(participant_id: &str, track_id: &str) -> FocusedShare {
+    FocusedShare {
+        participant_id: participant_id.to_string(),
+        track_id: track_id.to_string(),
+    }
+}
+
+fn speakers(ids: &[&str]) -> Vec<(String, f32)> {
+    ids.iter().map(|id| (id.to_string(), 0.5)).collect()
+}
+
+#[test]
+fn test_first_share_takes_focus() {
+    let mut focus = ShareFocus::new();
+    let now = Instant::now();
+
+    assert!(focus.share_added(share("alice", "TR_a"), now));
+    assert!(!focus.share_added(share("bob", "TR_b"), now));
+    assert_eq!(focus.focused(), Some(&share("alice", "TR_a")));
+}
+
+#[test]
+fn test_dominant_speaker_share_takes_focus_after_hold() {
+    let mut focus = ShareFocus::new();
+    let start = Instant::now();
+    focus.share_added(share("alice", "TR_a"), start);
+    focus.share_added(share("bob", "TR_b"), start);
+
+    // Within the hold, speech doesn't move focus
+    assert!(!focus.speakers_changed(&speakers(&["bob"]), start + Duration::from_millis(500)));
+
+    let later = start + FOCUS_HOLD;
+    assert!(focus.speakers_changed(&speakers(&["carol", "bob", "alice"]), later));
+    assert_eq!(focus.focused(), Some(&share("bob", "TR_b")));
+}
+
+#[test]
+fn test_focus_kept_when_no_sharer_speaks() {
+    let mut focus = ShareFocus::new();
+    let start = Instant::now();
+    focus.share_added(share("alice", "TR_a"), start);
+
+    assert!(!focus.speakers_changed(&speakers(&["carol"]), start + FOCUS_HOLD));
+    assert!(!focus.speakers_changed(&[], start + FOCUS_HOLD));
+    assert_eq!(focus.focused(), Some(&share("alice", "TR_a")));
+}
+
+#[test]
+fn test_focus_moves_when_focused_share_ends() {
+    let mut focus = ShareFocus::new();
+    let now = Instant::now();
+    focus.share_added(share("alice", "TR_a"), now);
+    focus.share_added(share("bob", "TR_b"), now);
+
+    assert!(!focus.track_removed("TR_b", now));
+    focus.share_added(share("carol", "TR_c"), now);
+
+    assert!(focus.participant_left("alice", now));
+    assert_eq!(focus.focused(), Some(&share("carol", "TR_c")));
+
+    assert!(focus.track_removed("TR_c", now));
+    assert_eq!(focus.focused(), None);
+}
+
+#[test]
+fn test_clear_drops_focus() {
+    let mut focus = ShareFocus::new();
+    focus.share_added(share("alice", "TR_a"), Instant::now());
+
+    assert!(focus.clear());
+    assert!(!focus.clear());
+    assert_eq!(focus.focused(), None);
+}
@@ -0,0 +1,96 @@
+//! Tests for what the capture pipeline records and snapshots
+//!
+//! ffmpeg is replaced by a script that writes the raw I420 stream it is fed
+//! straight to the output file, so a recording's size counts its frames.
+
+#![cfg(unix)]
+
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+
+use livekit::webrtc::prelude::VideoBuffer;
+use parking_lot::Mutex;
+
+use etch_core::capture::pipeline::FramePipeline;
+use etch_core::capture::PixelFormat;
+use etch_core::recording::Recorder;
+
+const WIDTH: u32 = 64;
+const HEIGHT: u32 = 48;
+
+/// Packed I420 size of one captured frame
+const FRAME_BYTES: u64 = (WIDTH * HEIGHT * 3 / 2) as u64;
+
+/// Directory holding the fake ffmpeg, which every test in this file uses
+fn test_dir() -> &'static PathBuf {
+    static DIR: OnceLock<PathBuf> = OnceLock::new();
+    DIR.get_or_init(|| {
+        let dir = std::env::temp_dir().join(format!("etch-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let ffmpeg = dir.join("ffmpeg");
+        std::fs::write(
+            &ffmpeg,
+            "#!/bin/sh\n[ \"$1\" = \"-version\" ] && exit 0\nfor arg; do out=\"$arg\"; done\nexec cat > \"$out\"\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&ffmpeg, std::fs::Permissions::from_mode(0o755)).unwrap();
+        std::env::set_var("ETCH_FFMPEG_PATH", &ffmpeg);
+        dir
+    })
+}
+
+fn abgr_frame() -> Vec<u8> {
+    vec![128; (WIDTH * HEIGHT * 4) as usize]
+}
+
+fn pipeline(
+    pixel_format: PixelFormat,
+    recorder: Option<Recorder>,
+) -> (FramePipeline, Arc<Mutex<Option<Recorder>>>) {
+    let recorder = Arc::new(Mutex::new(recorder));
+    let pipeline =
+        FramePipeline::new(Arc::new(Mutex::new(None)), pixel_format, recorder.clone()).unwrap();
+    (pipeline, recorder)
+}
+
+#[test]
+fn test_recording_continues_across_height_cap() {
+    for pixel_format in [PixelFormat::I420, PixelFormat::Nv12] {
+        let path = test_dir().join(format!("{:?}.mp4", pixel_format));
+        let (mut pipeline, recorder) = pipeline(pixel_format, Some(Recorder::new(&path).unwrap()));
+        let frame = abgr_frame();
+
+        for _ in 0..3 {
+            pipeline.push(&frame, WIDTH * 4, WIDTH, HEIGHT);
+        }
+        // Adaptive step-down mid-recording
+        pipeline.set_max_height(Some(HEIGHT / 2));
+        assert_eq!(pipeline.output_size(WIDTH, HEIGHT), (WIDTH / 2, HEIGHT / 2));
+        for _ in 0..3 {
+            pipeline.push(&frame, WIDTH * 4, WIDTH, HEIGHT);
+        }
+
+        let recorder = recorder.lock().take().unwrap();
+        recorder.finish().unwrap();
+        assert_eq!(
+            std::fs::metadata(&path).unwrap().len(),
+            6 * FRAME_BYTES,
+            "{:?}",
+            pixel_format
+        );
+    }
+}
+
+#[test]
+fn test_still_keeps_capture_resolution() {
+    let (mut pipeline, _) = pipeline(PixelFormat::I420, None);
+    assert!(pipeline.still().is_none());
+
+    pipeline.set_max_height(Some(HEIGHT / 2));
+    pipeline.push(&abgr_frame(), WIDTH * 4, WIDTH, HEIGHT);
+
+    let still = pipeline.still().unwrap();
+    assert_eq!((still.width(), still.height()), (WIDTH, HEIGHT));
+}
@@ -0,0 +1,134 @@
+//! Tests for binding the Core socket server on Unix
+
+#![cfg(unix)]
+
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::time::Duration;
+
+use etch_core::socket::{CoreSocket, InputLimits};
+use etch_core::{EventProxy, UserEvent};
+
+fn socket_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("etch-test-{}-{}.sock", name, uuid::Uuid::new_v4()))
+}
+
+fn proxy() -> EventProxy {
+    let (sender, _events) = mpsc::channel();
+    EventProxy::Channel(sender)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_socket_is_owner_only() {
+    let path = socket_path("mode");
+    let socket = CoreSocket::new(path.to_str().unwrap(), proxy())
+        .await
+        .unwrap();
+
+    let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+
+    tokio::task::spawn_blocking(move || socket.shutdown(Duration::from_secs(1)))
+        .await
+        .unwrap();
+    assert!(!path.exists());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_stale_socket_is_replaced() {
+    let path = socket_path("stale");
+    let stale = std::os::unix::net::UnixListener::bind(&path).unwrap();
+    drop(stale);
+
+    let socket = CoreSocket::new(path.to_str().unwrap(), proxy()).await;
+    assert!(socket.is_ok());
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_shutdown_leaves_a_replacing_socket_alone() {
+    let path = socket_path("replaced");
+    let first = CoreSocket::new(path.to_str().unwrap(), proxy())
+        .await
+        .unwrap();
+    // A second Core on the same path replaces the first's socket
+    let second = CoreSocket::new(path.to_str().unwrap(), proxy())
+        .await
+        .unwrap();
+
+    tokio::task::spawn_blocking(move || first.shutdown(Duration::from_secs(1)))
+        .await
+        .unwrap();
+    assert!(path.exists());
+
+    tokio::task::spawn_blocking(move || second.shutdown(Duration::from_secs(1)))
+        .await
+        .unwrap();
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_socket_removed_when_runtime_stops() {
+    let path = socket_path("runtime");
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let socket = runtime
+        .block_on(CoreSocket::new(path.to_str().unwrap(), proxy()))
+        .unwrap();
+    assert!(path.exists());
+
+    // The server never shut down, as when Core exits without Terminate
+    drop(runtime);
+    assert!(!path.exists());
+    drop(socket);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_refuses_to_clobber_regular_file() {
+    let path = socket_path("file");
+    std::fs::write(&path, b"not a socket").unwrap();
+
+    let socket = CoreSocket::new(path.to_str().unwrap(), proxy()).await;
+    assert!(socket.is_err());
+    assert_eq!(std::fs::read(&path).unwrap(), b"not a socket");
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_oversized_line_closes_connection() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let path = socket_path("limit");
+    let limits = InputLimits {
+        max_line_bytes: 64,
+        ..InputLimits::default()
+    };
+    let (sender, events) = mpsc::channel();
+    let socket =
+        CoreSocket::with_limits(path.to_str().unwrap(), EventProxy::Channel(sender), limits)
+            .await
+            .unwrap();
+
+    let mut client = tokio::net::UnixStream::connect(&path).await.unwrap();
+    client.write_all(&[b'x'; 256]).await.unwrap();
+
+    // Core hangs up after Ready instead of waiting for a newline
+    let mut received = Vec::new();
+    tokio::time::timeout(Duration::from_secs(2), client.read_to_end(&mut received))
+        .await
+        .expect("connection was not closed")
+        .unwrap();
+    assert!(String::from_utf8_lossy(&received).contains("\"type\":\"ready\""));
+
+    let disconnected = tokio::task::spawn_blocking(move || {
+        std::iter::from_fn(|| events.recv_timeout(Duration::from_secs(2)).ok())
+            .any(|event| matches!(event, UserEvent::SocketDisconnected))
+    })
+    .await
+    .unwrap();
+    assert!(disconnected);
+
+    tokio::task::spawn_blocking(move || socket.shutdown(Duration::from_secs(1)))
+        .await
+        .unwrap();
+}
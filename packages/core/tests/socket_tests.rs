@@ -3,10 +3,18 @@
 //! These tests verify that IncomingMessage and OutgoingMessage types
 //! serialize/deserialize correctly according to the socket protocol specification.
 
-use etch_core::socket::{IncomingMessage, OutgoingMessage};
+use etch_core::capture::RestartPolicy;
+use etch_core::socket::framing::{
+    encode_message, FramingMode, FramingOptions, FLAG_COMPRESSED, TAG_JSON, TAG_VIDEO_FRAME,
+};
+use etch_core::socket::{
+    DataTrackMessage, IncomingMessage, OutgoingMessage, OutgoingQueueStats, StickyKey,
+};
 use etch_core::{
-    AnnotationTool, ConnectionState, FrameFormat, ParticipantData, ParticipantRole,
-    PermissionState, PermissionStatus, ScreenInfo, SourceType,
+    ActiveSpeaker, AnnotationTool, Capabilities, CaptureConfig, Color, ConnectionState,
+    CursorState, CursorStyle, ErrorCode, FrameFormat, ParticipantData, ParticipantRole,
+    PermissionState, PermissionStatus, Point, Resolution, ScreenInfo, SourceType, TrackSourceType,
+    TrailDot, VideoCodecPreference,
 };
 
 // ============================================================================
@@ -19,14 +27,98 @@ fn test_parse_join_room() {
     let msg: IncomingMessage = serde_json::from_str(json).unwrap();
 
     match msg {
-        IncomingMessage::JoinRoom { server_url, token } => {
+        IncomingMessage::JoinRoom {
+            server_url,
+            token,
+            e2ee_passphrase,
+            initial_media,
+            auto_subscribe,
+            relay_format,
+        } => {
             assert_eq!(server_url, "wss://livekit.example.com");
             assert_eq!(token, "eyJ...");
+            assert_eq!(e2ee_passphrase, None);
+            assert!(initial_media.is_none());
+            assert!(auto_subscribe);
+            assert_eq!(relay_format, FrameFormat::Jpeg);
+        }
+        _ => panic!("Expected JoinRoom"),
+    }
+}
+
+#[test]
+fn test_parse_join_room_without_auto_subscribe() {
+    let json = r#"{"type":"join_room","server_url":"wss://livekit.example.com","token":"eyJ...","auto_subscribe":false}"#;
+    let msg: IncomingMessage = serde_json::from_str(json).unwrap();
+
+    match msg {
+        IncomingMessage::JoinRoom { auto_subscribe, .. } => assert!(!auto_subscribe),
+        _ => panic!("Expected JoinRoom"),
+    }
+}
+
+#[test]
+fn test_parse_set_track_subscribed() {
+    let json = r#"{"type":"set_track_subscribed","participant_id":"alice","track_id":"TR_abc","subscribed":false}"#;
+    let msg: IncomingMessage = serde_json::from_str(json).unwrap();
+
+    match msg {
+        IncomingMessage::SetTrackSubscribed {
+            participant_id,
+            track_id,
+            subscribed,
+        } => {
+            assert_eq!(participant_id, "alice");
+            assert_eq!(track_id, "TR_abc");
+            assert!(!subscribed);
+        }
+        _ => panic!("Expected SetTrackSubscribed"),
+    }
+}
+
+#[test]
+fn test_parse_join_room_with_initial_media() {
+    let json = r#"{"type":"join_room","server_url":"wss://livekit.example.com","token":"eyJ...","initial_media":{"screen_source_id":"screen-0","microphone":true}}"#;
+    let msg: IncomingMessage = serde_json::from_str(json).unwrap();
+
+    match msg {
+        IncomingMessage::JoinRoom { initial_media, .. } => {
+            let media = initial_media.unwrap();
+            assert_eq!(media.screen_source_id.as_deref(), Some("screen-0"));
+            assert!(media.screen_config.is_none());
+            assert!(media.microphone);
+            assert!(!media.camera);
         }
         _ => panic!("Expected JoinRoom"),
     }
 }
 
+#[test]
+fn test_parse_join_room_with_e2ee_passphrase() {
+    let json = r#"{"type":"join_room","server_url":"wss://livekit.example.com","token":"eyJ...","e2ee_passphrase":"secret"}"#;
+    let msg: IncomingMessage = serde_json::from_str(json).unwrap();
+
+    match msg {
+        IncomingMessage::JoinRoom {
+            e2ee_passphrase, ..
+        } => {
+            assert_eq!(e2ee_passphrase.as_deref(), Some("secret"));
+        }
+        _ => panic!("Expected JoinRoom"),
+    }
+}
+
+#[test]
+fn test_join_room_debug_redacts_credentials() {
+    let json = r#"{"type":"join_room","server_url":"wss://livekit.example.com","token":"eyJ.secret.token","e2ee_passphrase":"hunter2"}"#;
+    let msg: IncomingMessage = serde_json::from_str(json).unwrap();
+
+    let logged = format!("{:?}", msg);
+    assert!(logged.contains("wss://livekit.example.com"));
+    assert!(!logged.contains("eyJ.secret.token"));
+    assert!(!logged.contains("hunter2"));
+}
+
 #[test]
 fn test_parse_leave_room() {
     let json = r#"{"type":"leave_room"}"#;
@@ -35,12 +127,37 @@ fn test_parse_leave_room() {
     assert!(matches!(msg, IncomingMessage::LeaveRoom));
 }
 
+#[test]
+fn test_parse_get_participants() {
+    let msg: IncomingMessage = serde_json::from_str(r#"{"type":"get_participants"}"#).unwrap();
+
+    assert!(matches!(msg, IncomingMessage::GetParticipants));
+}
+
 #[test]
 fn test_parse_get_available_content() {
     let json = r#"{"type":"get_available_content"}"#;
     let msg: IncomingMessage = serde_json::from_str(json).unwrap();
 
-    assert!(matches!(msg, IncomingMessage::GetAvailableContent));
+    assert!(matches!(
+        msg,
+        IncomingMessage::GetAvailableContent {
+            force_refresh: false
+        }
+    ));
+}
+
+#[test]
+fn test_parse_get_available_content_force_refresh() {
+    let json = r#"{"type":"get_available_content","force_refresh":true}"#;
+    let msg: IncomingMessage = serde_json::from_str(json).unwrap();
+
+    assert!(matches!(
+        msg,
+        IncomingMessage::GetAvailableContent {
+            force_refresh: true
+        }
+    ));
 }
 
 #[test]
@@ -53,6 +170,7 @@ fn test_parse_start_screen_share() {
             source_id,
             source_type,
             config,
+            replace,
         } => {
             assert_eq!(source_id, "screen-0");
             assert_eq!(source_type, SourceType::Screen);
@@ -61,11 +179,72 @@ fn test_parse_start_screen_share() {
             assert_eq!(config.height, 1080);
             assert_eq!(config.framerate, 60);
             assert_eq!(config.bitrate, 6000000);
+            assert!(!config.capture_audio);
+            assert_eq!(config.codec, VideoCodecPreference::Vp9);
+            assert!(config.simulcast);
+            assert!(!replace);
+        }
+        _ => panic!("Expected StartScreenShare"),
+    }
+}
+
+#[test]
+fn test_parse_start_screen_share_with_codec() {
+    let json = r#"{"type":"start_screen_share","source_id":"screen-0","source_type":"screen","config":{"width":1920,"height":1080,"framerate":60,"bitrate":3000000,"codec":"av1","simulcast":false}}"#;
+    let msg: IncomingMessage = serde_json::from_str(json).unwrap();
+
+    match msg {
+        IncomingMessage::StartScreenShare { config, .. } => {
+            let config = config.unwrap();
+            assert_eq!(config.codec, VideoCodecPreference::Av1);
+            assert_eq!(config.bitrate, 3_000_000);
+            assert!(!config.simulcast);
+        }
+        _ => panic!("Expected StartScreenShare"),
+    }
+}
+
+#[test]
+fn test_parse_start_screen_share_with_audio() {
+    let json = r#"{"type":"start_screen_share","source_id":"screen-0","source_type":"screen","config":{"width":1920,"height":1080,"framerate":60,"bitrate":6000000,"capture_audio":true}}"#;
+    let msg: IncomingMessage = serde_json::from_str(json).unwrap();
+
+    match msg {
+        IncomingMessage::StartScreenShare { config, .. } => {
+            assert!(config.unwrap().capture_audio);
+        }
+        _ => panic!("Expected StartScreenShare"),
+    }
+}
+
+#[test]
+fn test_parse_start_screen_share_with_restart_policy() {
+    let json = r#"{"type":"start_screen_share","source_id":"screen-0","source_type":"screen","config":{"width":1920,"height":1080,"framerate":60,"bitrate":6000000,"restart_policy":{"max_restart_attempts":10,"restart_delay_ms":1000}}}"#;
+    let msg: IncomingMessage = serde_json::from_str(json).unwrap();
+
+    match msg {
+        IncomingMessage::StartScreenShare { config, .. } => {
+            let policy = config.unwrap().restart_policy;
+            assert_eq!(policy.max_restart_attempts, 10);
+            assert_eq!(policy.restart_delay_ms, 1000);
+            // Unset fields keep their defaults
+            assert_eq!(policy.max_failures, RestartPolicy::default().max_failures);
         }
         _ => panic!("Expected StartScreenShare"),
     }
 }
 
+#[test]
+fn test_parse_start_screen_share_replace() {
+    let json = r#"{"type":"start_screen_share","source_id":"screen-1","source_type":"screen","replace":true}"#;
+    let msg: IncomingMessage = serde_json::from_str(json).unwrap();
+
+    match msg {
+        IncomingMessage::StartScreenShare { replace, .. } => assert!(replace),
+        _ => panic!("Expected StartScreenShare"),
+    }
+}
+
 #[test]
 fn test_parse_start_screen_share_window_type() {
     let json = r#"{"type":"start_screen_share","source_id":"window-123","source_type":"window"}"#;
@@ -76,6 +255,7 @@ fn test_parse_start_screen_share_window_type() {
             source_id,
             source_type,
             config,
+            ..
         } => {
             assert_eq!(source_id, "window-123");
             assert_eq!(source_type, SourceType::Window);
@@ -93,6 +273,172 @@ fn test_parse_stop_screen_share() {
     assert!(matches!(msg, IncomingMessage::StopScreenShare));
 }
 
+#[test]
+fn test_parse_pause_and_resume_screen_share() {
+    let pause: IncomingMessage = serde_json::from_str(r#"{"type":"pause_screen_share"}"#).unwrap();
+    let resume: IncomingMessage =
+        serde_json::from_str(r#"{"type":"resume_screen_share"}"#).unwrap();
+
+    assert!(matches!(pause, IncomingMessage::PauseScreenShare));
+    assert!(matches!(resume, IncomingMessage::ResumeScreenShare));
+}
+
+#[test]
+fn test_parse_capture_still() {
+    let msg: IncomingMessage = serde_json::from_str(r#"{"type":"capture_still"}"#).unwrap();
+
+    assert!(matches!(msg, IncomingMessage::CaptureStill));
+}
+
+#[test]
+fn test_capture_config_round_trip() {
+    let msg: IncomingMessage = serde_json::from_str(r#"{"type":"get_capture_config"}"#).unwrap();
+    assert!(matches!(msg, IncomingMessage::GetCaptureConfig));
+
+    let json = serde_json::to_string(&OutgoingMessage::CaptureConfig { config: None }).unwrap();
+    assert_eq!(json, r#"{"type":"capture_config","config":null}"#);
+
+    let msg = OutgoingMessage::CaptureConfig {
+        config: Some(CaptureConfig {
+            width: 1280,
+            height: 720,
+            ..Default::default()
+        }),
+    };
+    let json = serde_json::to_value(&msg).unwrap();
+    assert_eq!(json["config"]["width"], 1280);
+    assert_eq!(json["config"]["codec"], "vp9");
+}
+
+#[test]
+fn test_parse_set_capture_quality() {
+    let json = r#"{"type":"set_capture_quality","framerate":15}"#;
+    let msg: IncomingMessage = serde_json::from_str(json).unwrap();
+
+    match msg {
+        IncomingMessage::SetCaptureQuality { framerate } => {
+            assert_eq!(framerate, Some(15));
+        }
+        _ => panic!("Expected SetCaptureQuality"),
+    }
+}
+
+#[test]
+fn test_serialize_still() {
+    let msg = OutgoingMessage::Still {
+        width: 2560,
+        height: 1440,
+        format: FrameFormat::Jpeg,
+        data: vec![0xFF, 0xD8, 0xFF],
+    };
+
+    let json = serde_json::to_string(&msg).unwrap();
+    assert!(json.contains("\"type\":\"still\""));
+    assert!(json.contains("\"width\":2560"));
+    assert!(json.contains("\"format\":\"jpeg\""));
+    assert!(json.contains("\"data\":\"/9j/\""));
+}
+
+#[test]
+fn test_parse_switch_screen_share_source() {
+    let json = r#"{"type":"switch_screen_share_source","source_id":"screen:2"}"#;
+    let msg: IncomingMessage = serde_json::from_str(json).unwrap();
+
+    match msg {
+        IncomingMessage::SwitchScreenShareSource { source_id } => {
+            assert_eq!(source_id, "screen:2")
+        }
+        _ => panic!("Expected SwitchScreenShareSource"),
+    }
+
+    let json = serde_json::to_string(&OutgoingMessage::CaptureSourceChanged {
+        source_id: "screen:2".to_string(),
+    })
+    .unwrap();
+    assert_eq!(
+        json,
+        r#"{"type":"capture_source_changed","source_id":"screen:2"}"#
+    );
+}
+
+#[test]
+fn test_serialize_screen_share_pause_changed() {
+    let msg = OutgoingMessage::ScreenSharePauseChanged { paused: true };
+
+    let json = serde_json::to_string(&msg).unwrap();
+    assert!(json.contains("\"type\":\"screen_share_pause_changed\""));
+    assert!(json.contains("\"paused\":true"));
+}
+
+#[test]
+fn test_serialize_capture_restart_status() {
+    let msg = OutgoingMessage::CaptureRestarting { attempt: 2, max: 5 };
+    let json = serde_json::to_string(&msg).unwrap();
+    assert!(json.contains("\"type\":\"capture_restarting\""));
+    assert!(json.contains("\"attempt\":2"));
+    assert!(json.contains("\"max\":5"));
+
+    let json = serde_json::to_string(&OutgoingMessage::CaptureRestored).unwrap();
+    assert_eq!(json, r#"{"type":"capture_restored"}"#);
+
+    let json = serde_json::to_string(&OutgoingMessage::CaptureGivingUp).unwrap();
+    assert_eq!(json, r#"{"type":"capture_giving_up"}"#);
+}
+
+#[test]
+fn test_serialize_capture_stats() {
+    let msg = OutgoingMessage::CaptureStats {
+        fps: 29.5,
+        frames_sent: 1200,
+        frames_dropped: 2,
+        restart_attempts: 1,
+    };
+    let json = serde_json::to_string(&msg).unwrap();
+    assert_eq!(
+        json,
+        r#"{"type":"capture_stats","fps":29.5,"frames_sent":1200,"frames_dropped":2,"restart_attempts":1}"#
+    );
+}
+
+#[test]
+fn test_serialize_capture_source_lost() {
+    let msg = OutgoingMessage::CaptureSourceLost {
+        source_id: "screen:2".to_string(),
+        switched_to: Some("screen:1".to_string()),
+    };
+    let json = serde_json::to_string(&msg).unwrap();
+    assert_eq!(
+        json,
+        r#"{"type":"capture_source_lost","source_id":"screen:2","switched_to":"screen:1"}"#
+    );
+
+    let msg = OutgoingMessage::CaptureSourceLost {
+        source_id: "screen:2".to_string(),
+        switched_to: None,
+    };
+    let json = serde_json::to_string(&msg).unwrap();
+    assert!(json.contains("\"switched_to\":null"));
+}
+
+#[test]
+fn test_parse_start_recording() {
+    let json = r#"{"type":"start_recording","path":"/tmp/session.mp4"}"#;
+    let msg: IncomingMessage = serde_json::from_str(json).unwrap();
+
+    match msg {
+        IncomingMessage::StartRecording { path } => assert_eq!(path, "/tmp/session.mp4"),
+        _ => panic!("Expected StartRecording"),
+    }
+}
+
+#[test]
+fn test_parse_stop_recording() {
+    let json = r#"{"type":"stop_recording"}"#;
+    let msg: IncomingMessage = serde_json::from_str(json).unwrap();
+
+    assert!(matches!(msg, IncomingMessage::StopRecording));
+}
+
 #[test]
 fn test_parse_send_annotation() {
     let json = r#"{"type":"send_annotation","stroke_id":"stroke-123","tool":"pen","color":{"r":255,"g":0,"b":0,"a":255},"points":[{"x":0.1,"y":0.2,"pressure":0.5},{"x":0.3,"y":0.4}]}"#;
@@ -103,9 +449,11 @@ fn test_parse_send_annotation() {
             stroke_id,
             tool,
             color,
+            width,
             points,
         } => {
             assert_eq!(stroke_id, "stroke-123");
+            assert_eq!(width, None);
             assert_eq!(tool, AnnotationTool::Pen);
             assert_eq!(color.r, 255);
             assert_eq!(color.g, 0);
@@ -134,6 +482,25 @@ fn test_parse_annotation_highlighter_tool() {
     }
 }
 
+#[test]
+fn test_parse_set_annotation_permission() {
+    let json = r#"{"type":"set_annotation_permission","participant_id":"alice","enabled":false}"#;
+    let msg: IncomingMessage = serde_json::from_str(json).unwrap();
+
+    match msg {
+        IncomingMessage::SetAnnotationPermission {
+            participant_id,
+            enabled,
+            clear_strokes,
+        } => {
+            assert_eq!(participant_id, "alice");
+            assert!(!enabled);
+            assert!(!clear_strokes); // Defaults to keeping strokes
+        }
+        _ => panic!("Expected SetAnnotationPermission"),
+    }
+}
+
 #[test]
 fn test_parse_delete_annotation() {
     let json = r#"{"type":"delete_annotation","stroke_id":"stroke-123"}"#;
@@ -147,6 +514,21 @@ fn test_parse_delete_annotation() {
     }
 }
 
+#[test]
+fn test_parse_set_tool() {
+    let json =
+        r#"{"type":"set_tool","tool":"highlighter","color":{"r":87,"g":166,"b":255,"a":255}}"#;
+    let msg: IncomingMessage = serde_json::from_str(json).unwrap();
+
+    match msg {
+        IncomingMessage::SetTool { tool, color } => {
+            assert_eq!(tool, AnnotationTool::Highlighter);
+            assert_eq!(color, Color::BLUE);
+        }
+        _ => panic!("Expected SetTool"),
+    }
+}
+
 #[test]
 fn test_parse_clear_annotations() {
     let json = r#"{"type":"clear_annotations"}"#;
@@ -161,14 +543,26 @@ fn test_parse_cursor_move() {
     let msg: IncomingMessage = serde_json::from_str(json).unwrap();
 
     match msg {
-        IncomingMessage::CursorMove { x, y } => {
+        IncomingMessage::CursorMove { x, y, laser } => {
             assert!((x - 0.5).abs() < 0.001);
             assert!((y - 0.75).abs() < 0.001);
+            assert!(!laser);
         }
         _ => panic!("Expected CursorMove"),
     }
 }
 
+#[test]
+fn test_parse_laser_cursor_move() {
+    let json = r#"{"type":"cursor_move","x":0.5,"y":0.75,"laser":true}"#;
+    let msg: IncomingMessage = serde_json::from_str(json).unwrap();
+
+    assert!(matches!(
+        msg,
+        IncomingMessage::CursorMove { laser: true, .. }
+    ));
+}
+
 #[test]
 fn test_parse_cursor_hide() {
     let json = r#"{"type":"cursor_hide"}"#;
@@ -222,12 +616,69 @@ fn test_parse_request_screen_recording_permission() {
     ));
 }
 
+#[test]
+fn test_parse_request_media_permissions() {
+    let json = r#"{"type":"request_microphone_permission"}"#;
+    let msg: IncomingMessage = serde_json::from_str(json).unwrap();
+    assert!(matches!(msg, IncomingMessage::RequestMicrophonePermission));
+
+    let json = r#"{"type":"request_camera_permission"}"#;
+    let msg: IncomingMessage = serde_json::from_str(json).unwrap();
+    assert!(matches!(msg, IncomingMessage::RequestCameraPermission));
+}
+
 #[test]
 fn test_parse_ping() {
     let json = r#"{"type":"ping"}"#;
     let msg: IncomingMessage = serde_json::from_str(json).unwrap();
 
-    assert!(matches!(msg, IncomingMessage::Ping));
+    assert!(matches!(msg, IncomingMessage::Ping { timestamp: None }));
+}
+
+#[test]
+fn test_parse_set_log_level() {
+    let json = r#"{"type":"set_log_level","filter":"etch_core=debug,etch_core::room=trace"}"#;
+    let msg: IncomingMessage = serde_json::from_str(json).unwrap();
+
+    match msg {
+        IncomingMessage::SetLogLevel { filter } => {
+            assert_eq!(filter, "etch_core=debug,etch_core::room=trace");
+        }
+        _ => panic!("Expected SetLogLevel"),
+    }
+}
+
+#[test]
+fn test_set_log_filter_rejects_invalid_directives() {
+    assert!(etch_core::logging::set_filter("etch_core=loudest").is_err());
+}
+
+#[test]
+fn test_parse_ping_with_timestamp() {
+    let json = r#"{"type":"ping","timestamp":1700000000000}"#;
+    let msg: IncomingMessage = serde_json::from_str(json).unwrap();
+
+    match msg {
+        IncomingMessage::Ping { timestamp } => {
+            assert_eq!(timestamp, Some(1700000000000));
+        }
+        _ => panic!("Expected Ping"),
+    }
+}
+
+#[test]
+fn test_parse_hello_binary_framing() {
+    let json = r#"{"type":"hello","framing":"binary"}"#;
+    let msg: IncomingMessage = serde_json::from_str(json).unwrap();
+
+    assert!(matches!(
+        msg,
+        IncomingMessage::Hello {
+            framing: FramingMode::Binary,
+            compress: false,
+            heartbeat: None,
+        }
+    ));
 }
 
 #[test]
@@ -272,6 +723,7 @@ fn test_serialize_participant_joined() {
             name: "Alice".to_string(),
             is_local: false,
             role: ParticipantRole::Participant,
+            metadata: String::new(),
         },
     };
 
@@ -281,6 +733,25 @@ fn test_serialize_participant_joined() {
     assert!(json.contains("\"name\":\"Alice\""));
 }
 
+#[test]
+fn test_serialize_participants() {
+    let msg = OutgoingMessage::Participants {
+        participants: vec![ParticipantData {
+            id: "participant-123".to_string(),
+            name: "Alice".to_string(),
+            is_local: true,
+            role: ParticipantRole::Host,
+            metadata: String::new(),
+        }],
+    };
+
+    let json = serde_json::to_string(&msg).unwrap();
+    assert!(json.contains("\"type\":\"participants\""));
+    assert!(json.contains("\"id\":\"participant-123\""));
+    assert!(json.contains("\"is_local\":true"));
+    assert_eq!(msg.sticky_key(), None);
+}
+
 #[test]
 fn test_serialize_participant_left() {
     let msg = OutgoingMessage::ParticipantLeft {
@@ -303,6 +774,121 @@ fn test_serialize_connection_state_changed() {
     assert!(json.contains("\"state\":\"connected\""));
 }
 
+#[test]
+fn test_serialize_room_disconnected() {
+    let msg = OutgoingMessage::RoomDisconnected {
+        reason: "duplicate_identity".to_string(),
+    };
+
+    let json = serde_json::to_string(&msg).unwrap();
+    assert_eq!(
+        json,
+        r#"{"type":"room_disconnected","reason":"duplicate_identity"}"#
+    );
+    assert_eq!(msg.sticky_key(), None);
+}
+
+#[test]
+fn test_serialize_track_subscribed() {
+    let msg = OutgoingMessage::TrackSubscribed {
+        participant_id: "alice".to_string(),
+        track_id: "TR_screen".to_string(),
+        source: TrackSourceType::ScreenShare,
+    };
+
+    let json = serde_json::to_string(&msg).unwrap();
+    assert_eq!(
+        json,
+        r#"{"type":"track_subscribed","participant_id":"alice","track_id":"TR_screen","source":"screen_share"}"#
+    );
+}
+
+#[test]
+fn test_serialize_connection_state_reconnecting() {
+    // Sent while LiveKit recovers a dropped connection; the share keeps
+    // running and `connected` follows once it is restored
+    let msg = OutgoingMessage::ConnectionStateChanged {
+        state: ConnectionState::Reconnecting,
+    };
+
+    let json = serde_json::to_string(&msg).unwrap();
+    assert!(json.contains("\"type\":\"connection_state_changed\""));
+    assert!(json.contains("\"state\":\"reconnecting\""));
+}
+
+#[test]
+fn test_serialize_active_speakers_changed() {
+    let msg = OutgoingMessage::ActiveSpeakersChanged {
+        speakers: vec![ActiveSpeaker {
+            participant_id: "participant-123".to_string(),
+            audio_level: 0.5,
+        }],
+    };
+
+    let json = serde_json::to_string(&msg).unwrap();
+    assert!(json.contains("\"type\":\"active_speakers_changed\""));
+    assert!(json.contains("\"participant_id\":\"participant-123\""));
+    assert!(json.contains("\"audio_level\":0.5"));
+}
+
+#[test]
+fn test_serialize_remote_cursors() {
+    let msg = OutgoingMessage::RemoteCursors {
+        cursors: vec![CursorState {
+            participant_id: "participant-123".to_string(),
+            x: 0.25,
+            y: 0.5,
+            visible: true,
+            style: CursorStyle::Laser,
+            hotspot: CursorStyle::Laser.hotspot(),
+            color: Color::BLUE,
+            trail: vec![TrailDot {
+                x: 0.2,
+                y: 0.5,
+                opacity: 0.5,
+            }],
+        }],
+    };
+
+    let json = serde_json::to_string(&msg).unwrap();
+    assert!(json.contains("\"type\":\"remote_cursors\""));
+    assert!(json.contains("\"participant_id\":\"participant-123\""));
+    assert!(json.contains("\"x\":0.25"));
+    assert!(json.contains("\"visible\":true"));
+    assert!(json.contains("\"style\":\"laser\""));
+    assert!(json.contains("\"hotspot\":[0.5,0.5]"));
+    assert!(json.contains("\"trail\":[{\"x\":0.2,\"y\":0.5,\"opacity\":0.5}]"));
+}
+
+#[test]
+fn test_serialize_connection_stats() {
+    let msg = OutgoingMessage::ConnectionStats {
+        outbound_bitrate: 2_500_000,
+        packet_loss: 0.02,
+        rtt_ms: Some(42.0),
+        fps: 30.0,
+        resolution: Some(Resolution {
+            width: 1920,
+            height: 1080,
+        }),
+        outgoing_queue: OutgoingQueueStats {
+            control_queued: 3,
+            frames_queued: 1,
+            frames_dropped: 12,
+        },
+    };
+
+    let json = serde_json::to_string(&msg).unwrap();
+    assert!(json.contains("\"type\":\"connection_stats\""));
+    assert!(json.contains("\"outbound_bitrate\":2500000"));
+    assert!(json.contains("\"packet_loss\":0.02"));
+    assert!(json.contains("\"rtt_ms\":42.0"));
+    assert!(json.contains("\"resolution\":{\"width\":1920,\"height\":1080}"));
+    assert!(json.contains(
+        "\"outgoing_queue\":{\"control_queued\":3,\"frames_queued\":1,\"frames_dropped\":12}"
+    ));
+}
+
 #[test]
 fn test_serialize_screen_share_started() {
     let msg = OutgoingMessage::ScreenShareStarted {
@@ -322,6 +908,19 @@ fn test_serialize_screen_share_stopped() {
     assert!(json.contains("\"type\":\"screen_share_stopped\""));
 }
 
+#[test]
+fn test_serialize_recording_state_changed() {
+    let msg = OutgoingMessage::RecordingStateChanged {
+        is_recording: true,
+        path: Some("/tmp/session.mp4".to_string()),
+    };
+
+    let json = serde_json::to_string(&msg).unwrap();
+    assert!(json.contains("\"type\":\"recording_state_changed\""));
+    assert!(json.contains("\"is_recording\":true"));
+    assert!(json.contains("\"path\":\"/tmp/session.mp4\""));
+}
+
 #[test]
 fn test_serialize_video_frame() {
     let msg = OutgoingMessage::VideoFrame {
@@ -330,6 +929,7 @@ fn test_serialize_video_frame() {
         width: 1920,
         height: 1080,
         timestamp: 1234567890,
+        wall_timestamp: 1700000000000,
         format: FrameFormat::Jpeg,
         frame_data: vec![0xFF, 0xD8, 0xFF], // JPEG header
     };
@@ -339,6 +939,7 @@ fn test_serialize_video_frame() {
     assert!(json.contains("\"width\":1920"));
     assert!(json.contains("\"height\":1080"));
     assert!(json.contains("\"format\":\"jpeg\""));
+    assert!(json.contains("\"wall_timestamp\":1700000000000"));
     // frame_data should be base64 encoded
     assert!(json.contains("\"frame_data\":"));
 }
@@ -362,35 +963,120 @@ fn test_serialize_permission_state() {
 
 #[test]
 fn test_serialize_pong() {
-    let msg = OutgoingMessage::Pong;
+    let msg = OutgoingMessage::Pong {
+        timestamp: Some(1700000000000),
+        core_timestamp: 1700000000005,
+    };
 
     let json = serde_json::to_string(&msg).unwrap();
     assert!(json.contains("\"type\":\"pong\""));
+    assert!(json.contains("\"timestamp\":1700000000000"));
+    assert!(json.contains("\"core_timestamp\":1700000000005"));
+}
+
+#[test]
+fn test_serialize_ready() {
+    let msg = OutgoingMessage::Ready {
+        version: "0.1.0".to_string(),
+        platform: "macos".to_string(),
+        capabilities: Capabilities {
+            window_capture: false,
+            system_audio: false,
+            recording: true,
+            e2ee: true,
+            codecs: vec![VideoCodecPreference::Vp8, VideoCodecPreference::Vp9],
+            relay_formats: vec![FrameFormat::Jpeg, FrameFormat::Nv12],
+        },
+        relay_format: FrameFormat::Nv12,
+    };
+
+    let json = serde_json::to_string(&msg).unwrap();
+    assert!(json.contains("\"type\":\"ready\""));
+    assert!(json.contains("\"platform\":\"macos\""));
+    assert!(json.contains("\"window_capture\":false"));
+    assert!(json.contains("\"e2ee\":true"));
+    assert!(json.contains("\"codecs\":[\"vp8\",\"vp9\"]"));
+    assert!(json.contains("\"relay_formats\":[\"jpeg\",\"nv12\"]"));
+    assert!(json.contains("\"relay_format\":\"nv12\""));
+}
+
+#[test]
+fn test_current_capabilities_match_build() {
+    let capabilities = Capabilities::current();
+    assert_eq!(capabilities.e2ee, cfg!(feature = "e2ee"));
+    assert!(capabilities.codecs.contains(&VideoCodecPreference::Vp8));
+    assert!(capabilities.relay_formats.contains(&FrameFormat::Jpeg));
+}
+
+#[test]
+fn test_serialize_room_connected_reports_relay_format() {
+    let msg = OutgoingMessage::RoomConnected {
+        room_name: "standup".to_string(),
+        relay_format: FrameFormat::Nv12,
+    };
+
+    let json = serde_json::to_string(&msg).unwrap();
+    assert_eq!(
+        json,
+        r#"{"type":"room_connected","room_name":"standup","relay_format":"nv12"}"#
+    );
+    assert_eq!(msg.sticky_key(), None);
+}
+
+#[test]
+fn test_parse_join_room_with_relay_format() {
+    let json = r#"{"type":"join_room","server_url":"wss://livekit.example.com","token":"eyJ...","relay_format":"nv12"}"#;
+    let msg: IncomingMessage = serde_json::from_str(json).unwrap();
+
+    match msg {
+        IncomingMessage::JoinRoom { relay_format, .. } => {
+            assert_eq!(relay_format, FrameFormat::Nv12)
+        }
+        _ => panic!("Expected JoinRoom"),
+    }
 }
 
 #[test]
 fn test_serialize_error() {
     let msg = OutgoingMessage::Error {
-        code: "ROOM_CONNECTION_FAILED".to_string(),
+        code: ErrorCode::RoomJoinFailed,
         message: "Failed to connect to room: timeout".to_string(),
     };
 
     let json = serde_json::to_string(&msg).unwrap();
     assert!(json.contains("\"type\":\"error\""));
-    assert!(json.contains("\"code\":\"ROOM_CONNECTION_FAILED\""));
+    assert!(json.contains("\"code\":\"room_join_failed\""));
     assert!(json.contains("\"message\":\"Failed to connect to room: timeout\""));
 }
 
+#[test]
+fn test_error_code_wire_form() {
+    for code in [
+        ErrorCode::SocketInitFailed,
+        ErrorCode::AlreadySharing,
+        ErrorCode::E2eeUnavailable,
+        ErrorCode::NotHost,
+    ] {
+        let json = serde_json::to_string(&code).unwrap();
+        assert_eq!(json, format!("\"{}\"", code.as_str()));
+        assert_eq!(serde_json::from_str::<ErrorCode>(&json).unwrap(), code);
+    }
+}
+
 // ============================================================================
 // Error Handling Tests
 // ============================================================================
 
 #[test]
-fn test_parse_invalid_message_type() {
+fn test_parse_unknown_message_type() {
+    // Types from a newer WebView parse as Unknown (and are skipped)
     let json = r#"{"type":"invalid_type"}"#;
-    let result: Result<IncomingMessage, _> = serde_json::from_str(json);
+    let msg: IncomingMessage = serde_json::from_str(json).unwrap();
+    assert!(matches!(msg, IncomingMessage::Unknown));
 
-    assert!(result.is_err());
+    let json = r#"{"type":"future_feature","enabled":true,"level":3}"#;
+    let msg: IncomingMessage = serde_json::from_str(json).unwrap();
+    assert!(matches!(msg, IncomingMessage::Unknown));
 }
 
 #[test]
@@ -425,3 +1111,313 @@ fn test_parse_malformed_json() {
 
     assert!(result.is_err());
 }
+
+// ============================================================================
+// Sticky State Tests
+// ============================================================================
+
+#[test]
+fn test_state_messages_are_sticky() {
+    let connection = OutgoingMessage::ConnectionStateChanged {
+        state: ConnectionState::Connected,
+    };
+    let started = OutgoingMessage::ScreenShareStarted {
+        sharer_id: "local".to_string(),
+    };
+
+    assert_eq!(connection.sticky_key(), Some(StickyKey::ConnectionState));
+    assert_eq!(started.sticky_key(), Some(StickyKey::ScreenShare));
+    assert_eq!(
+        OutgoingMessage::ScreenShareStopped.sticky_key(),
+        Some(StickyKey::ScreenShare)
+    );
+    assert_eq!(
+        OutgoingMessage::RecordingStateChanged {
+            is_recording: false,
+            path: None,
+        }
+        .sticky_key(),
+        Some(StickyKey::Recording)
+    );
+}
+
+#[test]
+fn test_event_messages_are_fire_and_forget() {
+    let left = OutgoingMessage::ParticipantLeft {
+        participant_id: "participant-456".to_string(),
+    };
+
+    assert_eq!(left.sticky_key(), None);
+    assert_eq!(sample_video_frame().sticky_key(), None);
+}
+
+// ============================================================================
+// DataTrack Tests
+// ============================================================================
+
+#[test]
+fn test_data_track_reliability() {
+    let point = Point {
+        x: 0.1,
+        y: 0.2,
+        pressure: 1.0,
+    };
+
+    assert!(DataTrackMessage::StrokeStart {
+        stroke_id: "stroke-1".to_string(),
+        tool: AnnotationTool::Pen,
+        color: Color::PALETTE[0],
+        width: None,
+        point,
+        points: vec![],
+        share_id: None,
+    }
+    .is_reliable());
+    assert!(DataTrackMessage::StrokeComplete {
+        stroke_id: "stroke-1".to_string()
+    }
+    .is_reliable());
+    assert!(DataTrackMessage::StrokeAck {
+        stroke_ids: vec!["stroke-1".to_string()]
+    }
+    .is_reliable());
+    assert!(DataTrackMessage::StrokeDelete {
+        stroke_id: "stroke-1".to_string()
+    }
+    .is_reliable());
+    assert!(DataTrackMessage::ClearAll.is_reliable());
+    assert!(DataTrackMessage::Snapshot { strokes: vec![] }.is_reliable());
+
+    assert!(!DataTrackMessage::StrokeUpdate {
+        stroke_id: "stroke-1".to_string(),
+        points: vec![point],
+    }
+    .is_reliable());
+    assert!(!DataTrackMessage::CursorMove {
+        x: 0.5,
+        y: 0.5,
+        visible: true,
+        style: None,
+    }
+    .is_reliable());
+}
+
+#[test]
+fn test_data_track_host_only_messages() {
+    let clear = DataTrackMessage::ClearParticipant {
+        participant_id: "alice".to_string(),
+    };
+    assert!(clear.requires_host());
+    assert!(clear.is_reliable());
+    assert!(!DataTrackMessage::ClearAll.requires_host());
+    assert!(DataTrackMessage::AnnotationPermission {
+        participant_id: "alice".to_string(),
+        enabled: false,
+        clear_strokes: true,
+    }
+    .requires_host());
+
+    let json = serde_json::to_string(&clear).unwrap();
+    assert_eq!(
+        json,
+        r#"{"type":"clear_participant","participant_id":"alice"}"#
+    );
+}
+
+#[test]
+fn test_stroke_start_width_is_optional() {
+    let json = r#"{"type":"stroke_start","stroke_id":"s1","tool":"highlighter","color":{"r":255,"g":255,"b":0,"a":128},"point":{"x":0.5,"y":0.5}}"#;
+    match serde_json::from_str::<DataTrackMessage>(json).unwrap() {
+        DataTrackMessage::StrokeStart { width, .. } => assert_eq!(width, None),
+        _ => panic!("Expected StrokeStart"),
+    }
+
+    let msg = DataTrackMessage::StrokeStart {
+        stroke_id: "s1".to_string(),
+        tool: AnnotationTool::Pen,
+        color: Color::RED,
+        width: Some(0.004),
+        point: Point {
+            x: 0.5,
+            y: 0.5,
+            pressure: 1.0,
+        },
+        points: vec![],
+        share_id: None,
+    };
+    let json = serde_json::to_string(&msg).unwrap();
+    assert!(json.contains("\"width\":0.004"));
+    assert!(!json.contains("\"points\""));
+    assert!(!json.contains("\"share_id\""));
+}
+
+#[test]
+fn test_stroke_start_share_id() {
+    let json = r#"{"type":"stroke_start","stroke_id":"s1","tool":"pen","color":{"r":255,"g":0,"b":0,"a":255},"point":{"x":0.1,"y":0.1},"share_id":"alice"}"#;
+    match serde_json::from_str::<DataTrackMessage>(json).unwrap() {
+        DataTrackMessage::StrokeStart { share_id, .. } => {
+            assert_eq!(share_id.as_deref(), Some("alice"))
+        }
+        _ => panic!("Expected StrokeStart"),
+    }
+
+    let json = r#"{"type":"send_annotation","stroke_id":"s1","tool":"pen","color":{"r":255,"g":0,"b":0,"a":255},"points":[{"x":0.1,"y":0.1}],"share_id":"bob"}"#;
+    match serde_json::from_str::<IncomingMessage>(json).unwrap() {
+        IncomingMessage::SendAnnotation { share_id, .. } => {
+            assert_eq!(share_id.as_deref(), Some("bob"))
+        }
+        _ => panic!("Expected SendAnnotation"),
+    }
+}
+
+#[test]
+fn test_stroke_start_carries_known_points() {
+    let json = r#"{"type":"stroke_start","stroke_id":"s1","tool":"pen","color":{"r":255,"g":0,"b":0,"a":255},"point":{"x":0.1,"y":0.1},"points":[{"x":0.2,"y":0.2},{"x":0.3,"y":0.3}]}"#;
+    match serde_json::from_str::<DataTrackMessage>(json).unwrap() {
+        DataTrackMessage::StrokeStart { points, .. } => assert_eq!(points.len(), 2),
+        _ => panic!("Expected StrokeStart"),
+    }
+}
+
+// ============================================================================
+// Framing Tests
+// ============================================================================
+
+fn sample_video_frame() -> OutgoingMessage {
+    OutgoingMessage::VideoFrame {
+        participant_id: "participant-123".to_string(),
+        track_id: "track-456".to_string(),
+        width: 2,
+        height: 1,
+        timestamp: 1234567890,
+        wall_timestamp: 1700000000000,
+        format: FrameFormat::Rgba,
+        frame_data: vec![1, 2, 3, 4, 5, 6, 7, 8],
+    }
+}
+
+fn json_framing() -> FramingOptions {
+    FramingOptions::default()
+}
+
+fn binary_framing(compress: bool) -> FramingOptions {
+    FramingOptions {
+        mode: FramingMode::Binary,
+        compress,
+    }
+}
+
+#[test]
+fn test_encode_json_framing_is_newline_delimited() {
+    let bytes = encode_message(&OutgoingMessage::ScreenShareStopped, json_framing()).unwrap();
+
+    assert_eq!(bytes.last(), Some(&b'\n'));
+    let json = std::str::from_utf8(&bytes).unwrap();
+    assert!(json.contains("\"type\":\"screen_share_stopped\""));
+}
+
+#[test]
+fn test_encode_binary_framing_control_message() {
+    let bytes =
+        encode_message(&OutgoingMessage::ScreenShareStopped, binary_framing(false)).unwrap();
+
+    let len = u32::from_le_bytes(bytes[0..4].try_into().unwrap()) as usize;
+    assert_eq!(len, bytes.len() - 4);
+    assert_eq!(bytes[4], TAG_JSON);
+
+    let json = std::str::from_utf8(&bytes[5..]).unwrap();
+    assert!(json.contains("\"type\":\"screen_share_stopped\""));
+}
+
+#[test]
+fn test_encode_binary_framing_video_frame() {
+    let bytes = encode_message(&sample_video_frame(), binary_framing(false)).unwrap();
+
+    let len = u32::from_le_bytes(bytes[0..4].try_into().unwrap()) as usize;
+    assert_eq!(len, bytes.len() - 4);
+    assert_eq!(bytes[4], TAG_VIDEO_FRAME);
+
+    let payload = &bytes[5..];
+    let header_len = u32::from_le_bytes(payload[0..4].try_into().unwrap()) as usize;
+    let header = std::str::from_utf8(&payload[4..4 + header_len]).unwrap();
+    assert!(header.contains("\"type\":\"video_frame\""));
+    assert!(header.contains("\"width\":2"));
+    assert!(!header.contains("frame_data"));
+
+    // Raw bytes follow the header without base64
+    assert_eq!(&payload[4 + header_len..], &[1, 2, 3, 4, 5, 6, 7, 8]);
+}
+
+#[test]
+fn test_parse_hello_with_compression() {
+    let json = r#"{"type":"hello","framing":"binary","compress":true}"#;
+    let msg: IncomingMessage = serde_json::from_str(json).unwrap();
+
+    assert!(matches!(
+        msg,
+        IncomingMessage::Hello {
+            framing: FramingMode::Binary,
+            compress: true,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn test_parse_hello_with_heartbeat() {
+    let json = r#"{"type":"hello","framing":"json","heartbeat":{"interval_ms":5000,"shutdown_on_timeout":true}}"#;
+    let msg: IncomingMessage = serde_json::from_str(json).unwrap();
+
+    match msg {
+        IncomingMessage::Hello { heartbeat, .. } => {
+            let heartbeat = heartbeat.unwrap();
+            assert_eq!(heartbeat.interval_ms, 5000);
+            assert!(heartbeat.shutdown_on_timeout);
+        }
+        _ => panic!("Expected Hello"),
+    }
+}
+
+#[test]
+fn test_serialize_heartbeat() {
+    let msg = OutgoingMessage::Heartbeat {
+        core_timestamp: 1_700_000_000_000,
+    };
+
+    let json = serde_json::to_string(&msg).unwrap();
+    assert_eq!(
+        json,
+        r#"{"type":"heartbeat","core_timestamp":1700000000000}"#
+    );
+    assert_eq!(msg.sticky_key(), None);
+}
+
+#[test]
+fn test_encode_compressed_video_frame() {
+    let msg = OutgoingMessage::VideoFrame {
+        participant_id: "participant-123".to_string(),
+        track_id: "track-456".to_string(),
+        width: 64,
+        height: 64,
+        timestamp: 1234567890,
+        wall_timestamp: 1700000000000,
+        format: FrameFormat::Rgba,
+        frame_data: vec![0x7f; 64 * 64 * 4],
+    };
+
+    let plain = encode_message(&msg, binary_framing(false)).unwrap();
+    let compressed = encode_message(&msg, binary_framing(true)).unwrap();
+
+    assert_eq!(compressed[4], TAG_VIDEO_FRAME | FLAG_COMPRESSED);
+    assert!(compressed.len() < plain.len());
+
+    let len = u32::from_le_bytes(compressed[0..4].try_into().unwrap()) as usize;
+    assert_eq!(len, compressed.len() - 4);
+}
+
+#[test]
+fn test_small_messages_are_not_compressed() {
+    let bytes = encode_message(&OutgoingMessage::ScreenShareStopped, binary_framing(true)).unwrap();
+
+    assert_eq!(bytes[4], TAG_JSON);
+}
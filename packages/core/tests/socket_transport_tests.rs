@@ -0,0 +1,146 @@
+//! Tests for the socket protocol over an in-memory transport
+//!
+//! These drive the real server - framing, dispatch, broadcast - from a
+//! client on the other end of a duplex stream, without binding a socket.
+
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, DuplexStream, ReadHalf, WriteHalf};
+
+use etch_core::socket::{transport, CoreSocket, InputLimits, OutgoingMessage};
+use etch_core::{AnnotationTool, Application, Color, EventProxy, UserEvent};
+
+struct Client {
+    reader: BufReader<ReadHalf<DuplexStream>>,
+    writer: WriteHalf<DuplexStream>,
+}
+
+impl Client {
+    fn connect(connector: &transport::MemoryConnector) -> Self {
+        let (reader, writer) = tokio::io::split(connector.connect().unwrap());
+        Self {
+            reader: BufReader::new(reader),
+            writer,
+        }
+    }
+
+    async fn send(&mut self, json: &str) {
+        self.writer.write_all(json.as_bytes()).await.unwrap();
+        self.writer.write_all(b"\n").await.unwrap();
+    }
+
+    /// Next message from Core
+    async fn recv(&mut self) -> serde_json::Value {
+        let mut line = String::new();
+        tokio::time::timeout(Duration::from_secs(2), self.reader.read_line(&mut line))
+            .await
+            .expect("no message from Core")
+            .unwrap();
+        serde_json::from_str(&line).unwrap()
+    }
+
+    /// Skip messages until one of type `message_type`
+    async fn recv_type(&mut self, message_type: &str) -> serde_json::Value {
+        loop {
+            let msg = self.recv().await;
+            if msg["type"] == message_type {
+                return msg;
+            }
+        }
+    }
+}
+
+/// Next event the server dispatched
+async fn next_event(events: &mpsc::Receiver<UserEvent>) -> UserEvent {
+    let deadline = Instant::now() + Duration::from_secs(2);
+    loop {
+        if let Ok(event) = events.try_recv() {
+            return event;
+        }
+        assert!(Instant::now() < deadline, "no event from the socket");
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_commands_reach_the_event_loop() {
+    let (sender, events) = mpsc::channel();
+    let (transport, connector) = transport::memory();
+    let _socket = CoreSocket::with_transport(
+        transport,
+        EventProxy::Channel(sender),
+        InputLimits::default(),
+    );
+
+    let mut client = Client::connect(&connector);
+    assert_eq!(client.recv().await["type"], "ready");
+    assert!(matches!(
+        next_event(&events).await,
+        UserEvent::SocketConnected
+    ));
+
+    client
+        .send(r#"{"type":"set_tool","tool":"highlighter","color":{"r":0,"g":0,"b":255,"a":255}}"#)
+        .await;
+    match next_event(&events).await {
+        UserEvent::SetTool { tool, color } => {
+            assert_eq!(tool, AnnotationTool::Highlighter);
+            assert_eq!(color, Color::BLUE);
+        }
+        other => panic!("Expected SetTool, got {:?}", other),
+    }
+
+    drop(client);
+    assert!(matches!(
+        next_event(&events).await,
+        UserEvent::SocketDisconnected
+    ));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_messages_go_to_every_client() {
+    let (sender, _events) = mpsc::channel();
+    let (transport, connector) = transport::memory();
+    let socket = CoreSocket::with_transport(
+        transport,
+        EventProxy::Channel(sender),
+        InputLimits::default(),
+    );
+
+    let mut first = Client::connect(&connector);
+    let mut second = Client::connect(&connector);
+    for client in [&mut first, &mut second] {
+        assert_eq!(client.recv().await["type"], "ready");
+    }
+
+    socket.send(OutgoingMessage::ScreenShareStopped);
+    for client in [&mut first, &mut second] {
+        assert_eq!(client.recv().await["type"], "screen_share_stopped");
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_application_answers_over_the_socket() {
+    let (sender, events) = mpsc::channel();
+    let proxy = EventProxy::Channel(sender);
+    let (transport, connector) = transport::memory();
+    let socket = CoreSocket::with_transport(transport, proxy.clone(), InputLimits::default());
+    let mut app = Application::new(proxy, Arc::new(Mutex::new(Some(socket))));
+
+    let mut client = Client::connect(&connector);
+    client.send(r#"{"type":"ping","timestamp":42}"#).await;
+    loop {
+        let event = next_event(&events).await;
+        let ping = matches!(event, UserEvent::Ping { .. });
+        app.handle_user_event(event);
+        if ping {
+            break;
+        }
+    }
+
+    let pong = client.recv_type("pong").await;
+    assert_eq!(pong["timestamp"], 42);
+    assert!(pong["core_timestamp"].as_u64().unwrap() > 0);
+}
@@ -0,0 +1,73 @@
+//! Tests for connection stats summarization
+
+use etch_core::room::stats::StatsSampler;
+use etch_core::Resolution;
+use livekit::webrtc::stats::{
+    CandidatePairStats, OutboundRtpStats, RemoteInboundRtpStats, RtcStats,
+};
+
+fn outbound_video(bytes_sent: u64, width: u32, height: u32, fps: f64) -> RtcStats {
+    let mut stats = OutboundRtpStats::default();
+    stats.stream.kind = "video".to_string();
+    stats.sent.bytes_sent = bytes_sent;
+    stats.outbound.frame_width = width;
+    stats.outbound.frame_height = height;
+    stats.outbound.frames_per_second = fps;
+    RtcStats::OutboundRtp(stats)
+}
+
+fn remote_inbound(fraction_lost: f64, round_trip_time: f64) -> RtcStats {
+    let mut stats = RemoteInboundRtpStats::default();
+    stats.remote_inbound.fraction_lost = fraction_lost;
+    stats.remote_inbound.round_trip_time = round_trip_time;
+    RtcStats::RemoteInboundRtp(stats)
+}
+
+#[test]
+fn test_sample_reports_largest_video_layer() {
+    let mut sampler = StatsSampler::default();
+    let stats = sampler.sample(&[
+        outbound_video(1000, 640, 360, 15.0),
+        outbound_video(4000, 1920, 1080, 30.0),
+        remote_inbound(0.1, 0.05),
+        remote_inbound(0.3, 0.05),
+    ]);
+
+    assert_eq!(
+        stats.resolution,
+        Some(Resolution {
+            width: 1920,
+            height: 1080
+        })
+    );
+    assert_eq!(stats.fps, 30.0);
+    assert!((stats.packet_loss - 0.2).abs() < 1e-9);
+    assert_eq!(stats.rtt_ms, Some(50.0));
+    // First sample has no previous counters to compare against
+    assert_eq!(stats.outbound_bitrate, 0);
+}
+
+#[test]
+fn test_sample_prefers_candidate_pair_rtt() {
+    let mut pair = CandidatePairStats::default();
+    pair.candidate_pair.nominated = true;
+    pair.candidate_pair.current_round_trip_time = 0.02;
+
+    let mut sampler = StatsSampler::default();
+    let stats = sampler.sample(&[remote_inbound(0.0, 0.05), RtcStats::CandidatePair(pair)]);
+
+    assert_eq!(stats.rtt_ms, Some(20.0));
+    assert_eq!(stats.resolution, None);
+}
+
+#[test]
+fn test_sample_computes_bitrate_between_samples() {
+    let mut sampler = StatsSampler::default();
+    sampler.sample(&[outbound_video(0, 1280, 720, 30.0)]);
+    std::thread::sleep(std::time::Duration::from_millis(50));
+    let stats = sampler.sample(&[outbound_video(100_000, 1280, 720, 30.0)]);
+
+    assert!(stats.outbound_bitrate > 0);
+    // 800 kbit over at least 50ms
+    assert!(stats.outbound_bitrate <= 16_000_000);
+}
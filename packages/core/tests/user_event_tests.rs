@@ -3,7 +3,11 @@
 //! These tests verify that the socket message handling correctly
 //! translates IncomingMessages to UserEvents.
 
-use etch_core::{AnnotationTool, CaptureConfig, Color, SourceType, UserEvent};
+use etch_core::capture::{PixelFormat, RestartPolicy};
+use etch_core::{
+    AnnotationTool, CaptureConfig, Color, ParticipantData, ParticipantRole, SourceType, UserEvent,
+    VideoCodecPreference,
+};
 
 // Note: The socket::handle_message function is private, so we can't test it directly.
 // These tests verify the UserEvent enum structure and associated types.
@@ -16,6 +20,7 @@ fn test_user_event_screen_share_message_default_config() {
     assert_eq!(default.height, 1080);
     assert_eq!(default.framerate, 60);
     assert_eq!(default.bitrate, 6_000_000); // 6 Mbps
+    assert!(default.simulcast);
 }
 
 #[test]
@@ -63,11 +68,13 @@ fn test_user_event_variants_exist() {
 
     fn match_event(event: &UserEvent) -> &'static str {
         match event {
-            UserEvent::GetAvailableContent => "get_content",
+            UserEvent::GetAvailableContent { .. } => "get_content",
             UserEvent::StartScreenShare(_) => "start_share",
             UserEvent::StopScreenShare => "stop_share",
             UserEvent::JoinRoom { .. } => "join_room",
             UserEvent::LeaveRoom => "leave_room",
+            UserEvent::RoomReconnected => "room_reconnected",
+            UserEvent::LocalParticipant(_) => "local_participant",
             UserEvent::Terminate => "terminate",
             UserEvent::CheckPermissions => "check_perms",
             UserEvent::RequestScreenRecordingPermission => "request_perm",
@@ -83,9 +90,25 @@ fn test_user_event_variants_exist() {
     }
 
     // Test a few key variants
-    assert_eq!(match_event(&UserEvent::GetAvailableContent), "get_content");
+    assert_eq!(
+        match_event(&UserEvent::GetAvailableContent {
+            force_refresh: false
+        }),
+        "get_content"
+    );
     assert_eq!(match_event(&UserEvent::StopScreenShare), "stop_share");
     assert_eq!(match_event(&UserEvent::LeaveRoom), "leave_room");
+    assert_eq!(match_event(&UserEvent::RoomReconnected), "room_reconnected");
+    assert_eq!(
+        match_event(&UserEvent::LocalParticipant(ParticipantData {
+            id: "alice".to_string(),
+            name: "Alice".to_string(),
+            is_local: true,
+            role: ParticipantRole::Participant,
+            metadata: String::new(),
+        })),
+        "local_participant"
+    );
     assert_eq!(match_event(&UserEvent::Terminate), "terminate");
     assert_eq!(match_event(&UserEvent::CheckPermissions), "check_perms");
 }
@@ -102,13 +125,22 @@ fn test_screen_share_message_creation() {
             height: 1440,
             framerate: 60,
             bitrate: 8_000_000,
+            capture_audio: true,
+            codec: VideoCodecPreference::H264,
+            simulcast: false,
+            restart_policy: RestartPolicy::default(),
+            pixel_format: PixelFormat::Nv12,
         },
+        replace: false,
     };
 
     assert_eq!(msg.source_id, "screen-0");
     assert_eq!(msg.source_type, SourceType::Screen);
     assert_eq!(msg.config.width, 2560);
     assert_eq!(msg.config.height, 1440);
+    assert!(msg.config.capture_audio);
+    assert_eq!(msg.config.codec, VideoCodecPreference::H264);
+    assert_eq!(msg.config.pixel_format, PixelFormat::Nv12);
 }
 
 #[test]
@@ -156,11 +188,22 @@ fn test_cursor_style_variants() {
         CursorStyle::Highlighter,
         CursorStyle::Eraser,
         CursorStyle::Hidden,
+        CursorStyle::Laser,
     ];
 
-    // Verify all 5 styles exist and Default is the expected first value
+    // Verify all 6 styles exist and Default is the expected first value
     assert_eq!(styles[0], CursorStyle::Default);
-    assert_eq!(styles.len(), 5);
+    assert_eq!(styles.len(), 6);
+}
+
+#[test]
+fn test_cursor_style_hotspots() {
+    use etch_core::CursorStyle;
+
+    // The arrow tip is its top-left corner; round shapes are centred
+    assert_eq!(CursorStyle::Default.hotspot(), (0.0, 0.0));
+    assert_eq!(CursorStyle::Pen.hotspot(), (0.0, 1.0));
+    assert_eq!(CursorStyle::Laser.hotspot(), (0.5, 0.5));
 }
 
 #[test]
@@ -175,6 +218,36 @@ fn test_participant_role_variants() {
     assert_ne!(host, participant);
 }
 
+#[test]
+fn test_participant_role_from_metadata() {
+    use std::collections::HashMap;
+
+    let no_attributes = HashMap::new();
+    assert_eq!(
+        ParticipantRole::from_participant(r#"{"role":"host"}"#, &no_attributes),
+        ParticipantRole::Host
+    );
+    assert_eq!(
+        ParticipantRole::from_participant(r#"{"role":"viewer"}"#, &no_attributes),
+        ParticipantRole::Participant
+    );
+    assert_eq!(
+        ParticipantRole::from_participant("not json", &no_attributes),
+        ParticipantRole::Participant
+    );
+    assert_eq!(
+        ParticipantRole::from_participant("", &no_attributes),
+        ParticipantRole::Participant
+    );
+
+    // The role attribute takes precedence over metadata
+    let attributes = HashMap::from([("role".to_string(), "Host".to_string())]);
+    assert_eq!(
+        ParticipantRole::from_participant(r#"{"role":"participant"}"#, &attributes),
+        ParticipantRole::Host
+    );
+}
+
 #[test]
 fn test_permission_status_variants() {
     use etch_core::PermissionStatus;
@@ -198,6 +271,43 @@ fn test_permission_status_variants() {
     }
 }
 
+#[test]
+fn test_permission_state_has_pending() {
+    use etch_core::{NeededPermissions, PermissionState, PermissionStatus};
+
+    let none = NeededPermissions::default();
+    let mut state = PermissionState {
+        screen_recording: PermissionStatus::Granted,
+        microphone: PermissionStatus::NotApplicable,
+        camera: PermissionStatus::Restricted,
+        accessibility: PermissionStatus::NotApplicable,
+    };
+    // Nothing the user can still grant - no polling needed
+    assert!(!state.has_pending(none));
+
+    state.microphone = PermissionStatus::NotDetermined;
+    state.camera = PermissionStatus::Denied;
+    // Media nobody has asked for doesn't keep polling alive
+    assert!(!state.has_pending(none));
+    assert!(state.has_pending(NeededPermissions {
+        microphone: true,
+        camera: false,
+    }));
+
+    state.microphone = PermissionStatus::Granted;
+    assert!(!state.has_pending(NeededPermissions {
+        microphone: true,
+        camera: false,
+    }));
+    assert!(state.has_pending(NeededPermissions {
+        microphone: false,
+        camera: true,
+    }));
+
+    state.screen_recording = PermissionStatus::Denied;
+    assert!(state.has_pending(none));
+}
+
 #[test]
 fn test_point_default_pressure() {
     use etch_core::Point;
@@ -224,6 +334,18 @@ fn test_point_with_pressure() {
     assert!((point.pressure - 0.5).abs() < 0.001);
 }
 
+#[test]
+fn test_sanitize_coordinate() {
+    use etch_core::sanitize_coordinate;
+
+    assert_eq!(sanitize_coordinate(0.25), Some(0.25));
+    assert_eq!(sanitize_coordinate(-1.0), Some(0.0));
+    assert_eq!(sanitize_coordinate(1e9), Some(1.0));
+    assert_eq!(sanitize_coordinate(f32::NAN), None);
+    assert_eq!(sanitize_coordinate(f32::INFINITY), None);
+    assert_eq!(sanitize_coordinate(f32::NEG_INFINITY), None);
+}
+
 // ============================================================================
 // Thumbnail tests (Story 3-12)
 // ============================================================================
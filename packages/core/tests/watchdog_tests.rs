@@ -0,0 +1,64 @@
+//! Tests for the idle shutdown watchdog
+
+use std::sync::mpsc;
+use std::time::Duration;
+
+use etch_core::watchdog::{IdleTimeouts, IdleWatchdog};
+use etch_core::{EventProxy, UserEvent};
+
+const TIMEOUT: Duration = Duration::from_millis(50);
+
+fn watchdog() -> (IdleWatchdog, EventProxy, mpsc::Receiver<UserEvent>) {
+    let (sender, events) = mpsc::channel();
+    let watchdog = IdleWatchdog::new(IdleTimeouts {
+        startup_grace: Some(TIMEOUT),
+        reconnect_timeout: Some(TIMEOUT),
+    });
+    (watchdog, EventProxy::Channel(sender), events)
+}
+
+fn terminated(events: &mpsc::Receiver<UserEvent>) -> bool {
+    matches!(events.recv_timeout(TIMEOUT * 4), Ok(UserEvent::Terminate))
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_terminates_when_no_client_connects() {
+    let (mut watchdog, proxy, events) = watchdog();
+    watchdog.start(&proxy);
+    assert!(terminated(&events));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_connected_client_keeps_core_alive() {
+    let (mut watchdog, proxy, events) = watchdog();
+    watchdog.start(&proxy);
+    watchdog.client_connected();
+    assert!(!terminated(&events));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_terminates_when_client_does_not_return() {
+    let (mut watchdog, proxy, events) = watchdog();
+    watchdog.start(&proxy);
+    watchdog.client_connected();
+    watchdog.client_disconnected(&proxy);
+    assert!(terminated(&events));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_remaining_client_keeps_core_alive() {
+    let (mut watchdog, proxy, events) = watchdog();
+    watchdog.start(&proxy);
+    watchdog.client_connected();
+    watchdog.client_connected();
+    watchdog.client_disconnected(&proxy);
+    assert!(!terminated(&events));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_not_started_never_terminates() {
+    let (mut watchdog, proxy, events) = watchdog();
+    watchdog.client_connected();
+    watchdog.client_disconnected(&proxy);
+    assert!(!terminated(&events));
+}